@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use analytical_expression_parser::ast::LiteralValue;
+use analytical_expression_parser::bytecode::Program;
+use analytical_expression_parser::{compile, evaluate, evaluate_rows, parse};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+const EXPRESSION: &str = r#"if region = "EU" and quantity > 10 then price * quantity * (1 - discount) else price * quantity"#;
+
+fn rows(count: usize) -> Vec<HashMap<String, LiteralValue>> {
+    (0..count)
+        .map(|i| {
+            HashMap::from([
+                (
+                    "region".to_string(),
+                    LiteralValue::StringValue(if i % 2 == 0 { "EU" } else { "US" }.to_string()),
+                ),
+                ("quantity".to_string(), LiteralValue::NumberValue((i % 20) as f64)),
+                ("price".to_string(), LiteralValue::NumberValue(9.99)),
+                ("discount".to_string(), LiteralValue::NumberValue(0.1)),
+            ])
+        })
+        .collect()
+}
+
+fn bench_evaluate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("evaluate");
+    for count in [1_000, 10_000] {
+        let rows = rows(count);
+        group.throughput(Throughput::Elements(count as u64));
+
+        group.bench_with_input(BenchmarkId::new("parse_per_row", count), &rows, |b, rows| {
+            b.iter(|| {
+                for row in rows {
+                    black_box(evaluate(&parse(EXPRESSION).unwrap(), row).unwrap());
+                }
+            })
+        });
+
+        let expression = parse(EXPRESSION).unwrap();
+        group.bench_with_input(BenchmarkId::new("clone_per_row", count), &rows, |b, rows| {
+            b.iter(|| {
+                for row in rows {
+                    black_box(evaluate(&expression.clone(), row).unwrap());
+                }
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("borrowed", count), &rows, |b, rows| {
+            b.iter(|| {
+                for result in evaluate_rows(&expression, rows) {
+                    black_box(result.unwrap());
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+//tree-walking evaluation of rows looked up by field name against the compiled forms on rows of columns
+fn bench_compiled(c: &mut Criterion) {
+    const COLUMNS: [&str; 4] = ["region", "quantity", "price", "discount"];
+    let count = 1_000_000;
+    let rows = rows(count);
+    let column_rows: Vec<Vec<LiteralValue>> = rows
+        .iter()
+        .map(|row| COLUMNS.iter().map(|column| row[*column].clone()).collect())
+        .collect();
+    let expression = parse(EXPRESSION).unwrap();
+
+    let mut group = c.benchmark_group("compiled");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(count as u64));
+    group.bench_with_input(BenchmarkId::new("tree_walking", count), &rows, |b, rows| {
+        b.iter(|| {
+            for result in evaluate_rows(&expression, rows) {
+                black_box(result.unwrap());
+            }
+        })
+    });
+    group.bench_with_input(BenchmarkId::new("closures", count), &column_rows, |b, rows| {
+        let compiled = compile(&expression, &COLUMNS);
+        b.iter(|| {
+            for row in rows {
+                black_box(compiled.evaluate(row).unwrap());
+            }
+        })
+    });
+    group.bench_with_input(BenchmarkId::new("bytecode", count), &column_rows, |b, rows| {
+        let program = Program::compile(&expression, &COLUMNS);
+        b.iter(|| {
+            for row in rows {
+                black_box(program.evaluate(row).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_evaluate, bench_compiled);
+criterion_main!(benches);
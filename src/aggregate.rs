@@ -0,0 +1,662 @@
+use std::collections::{HashMap, HashSet};
+use std::ptr;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+
+use crate::ast::{CaseBranch, Expression, FilterContext, Interval, LiteralValue, Span, ValueType, WhereModifier};
+use crate::error::{Error, ErrorKind, ValueTypeError};
+use crate::eval::{compare_values, eval_condition, eval_node, promote, EvalContext, NumericOperands};
+use crate::functions::FunctionRegistry;
+use crate::lod;
+
+/// Evaluates an expression over a set of rows with the built-in functions.
+///
+/// Aggregate functions (`sum`, `avg`, `min`, `max`, `count`, `countd`, `median`, `stddev`,
+/// `variance` and `percentile`) evaluate their argument for every row and combine the values,
+/// so `sum(price * qty) / count(order_id)` is a single value for all rows. WHERE modifiers
+/// restrict the rows seen by the aggregates they contain. Fields used outside of aggregates
+/// have no single value and are reported as errors.
+///
+/// ```
+/// use analytical_expression_parser::ast::LiteralValue;
+/// use analytical_expression_parser::{evaluate_aggregate, parse};
+/// use std::collections::HashMap;
+///
+/// let rows: Vec<HashMap<String, LiteralValue>> = [("EU", 10), ("US", 20), ("EU", 30)]
+///     .iter()
+///     .map(|(region, sales)| {
+///         HashMap::from([
+///             ("region".to_string(), LiteralValue::StringValue(region.to_string())),
+///             ("sales".to_string(), LiteralValue::IntegerValue(*sales)),
+///         ])
+///     })
+///     .collect();
+/// let expression = parse(r#"sum(sales) [where region = "EU"]"#).unwrap();
+/// assert_eq!(evaluate_aggregate(&expression, &rows).unwrap(), LiteralValue::IntegerValue(40));
+/// ```
+pub fn evaluate_aggregate<C, I>(expression: &Expression, rows: I) -> Result<LiteralValue, Error>
+where
+    C: EvalContext,
+    I: IntoIterator<Item = C>,
+{
+    aggregate_rows(expression, rows, &[], FunctionRegistry::builtins())
+}
+
+pub(crate) fn is_aggregate(function_name: &str) -> bool {
+    aggregate_arity(function_name).is_some()
+}
+
+/// Number of arguments of an aggregate function, `None` for other functions.
+pub(crate) fn aggregate_arity(function_name: &str) -> Option<usize> {
+    match function_name.to_ascii_lowercase().as_str() {
+        "sum" | "avg" | "min" | "max" | "count" | "countd" | "median" | "stddev" | "variance" => Some(1),
+        "percentile" => Some(2),
+        _ => None,
+    }
+}
+
+/// A filter set on the dashboard: a condition on one field that restricts the rows of every
+/// aggregate, unless a WHERE modifier around the aggregate ignores filters on that field.
+///
+/// ```
+/// use analytical_expression_parser::ast::LiteralValue;
+/// use analytical_expression_parser::{evaluate_aggregate_with_filters, parse, DashboardFilter};
+/// use std::collections::HashMap;
+///
+/// let rows: Vec<HashMap<String, LiteralValue>> = [("EU", 10), ("US", 20), ("EU", 30)]
+///     .iter()
+///     .map(|(region, sales)| {
+///         HashMap::from([
+///             ("region".to_string(), LiteralValue::StringValue(region.to_string())),
+///             ("sales".to_string(), LiteralValue::IntegerValue(*sales)),
+///         ])
+///     })
+///     .collect();
+/// let filters = [DashboardFilter::new("region", parse(r#"region = "EU""#).unwrap())];
+/// let sales = |input| evaluate_aggregate_with_filters(&parse(input).unwrap(), &rows, &filters).unwrap();
+/// assert_eq!(sales("sum(sales)"), LiteralValue::IntegerValue(40));
+/// assert_eq!(sales("sum(sales) [where ignore filters on region]"), LiteralValue::IntegerValue(60));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DashboardFilter {
+    /// Field the filter is set on, matched against the fields listed in `allow` and `ignore`.
+    pub field_id: String,
+    /// Rows are kept when the condition is true, NULL drops them like false.
+    pub condition: Expression,
+}
+
+impl DashboardFilter {
+    pub fn new(field_id: &str, condition: Expression) -> Self {
+        DashboardFilter {
+            field_id: field_id.to_string(),
+            condition,
+        }
+    }
+}
+
+/// Evaluates an expression over a set of rows like [`evaluate_aggregate`], with the rows of every
+/// aggregate restricted by the dashboard filters that apply to it.
+///
+/// Without a filter context in the WHERE modifiers around an aggregate, all dashboard filters
+/// apply. `allow filters on` keeps only the filters on the listed fields, `ignore filters on`
+/// drops them and `ignore all filters` drops all of them. Nested modifiers narrow the filters
+/// further, and the filter expressions of all modifiers are combined with AND.
+pub fn evaluate_aggregate_with_filters<C, I>(
+    expression: &Expression,
+    rows: I,
+    dashboard_filters: &[DashboardFilter],
+) -> Result<LiteralValue, Error>
+where
+    C: EvalContext,
+    I: IntoIterator<Item = C>,
+{
+    aggregate_rows(expression, rows, dashboard_filters, FunctionRegistry::builtins())
+}
+
+//an aggregate call found in the expression, with all the filters that apply to its rows
+struct AggregateCall<'a> {
+    node: &'a Expression,
+    function_name: &'a str,
+    argument: &'a Expression,
+    filters: Vec<&'a Expression>,
+    span: Span,
+}
+
+//filters that apply inside of the WHERE modifiers around an expression
+#[derive(Clone)]
+pub(crate) struct FilterScope<'a> {
+    dashboard_filters: Vec<&'a DashboardFilter>,
+    additional_filters: Vec<&'a Expression>,
+}
+
+impl<'a> FilterScope<'a> {
+    pub(crate) fn new(dashboard_filters: &'a [DashboardFilter]) -> Self {
+        FilterScope {
+            dashboard_filters: dashboard_filters.iter().collect(),
+            additional_filters: Vec::new(),
+        }
+    }
+
+    pub(crate) fn apply(&mut self, where_modifier: &'a WhereModifier) {
+        let references = |fields: &[Expression], filter: &DashboardFilter| {
+            fields.iter().any(
+                |field| matches!(field, Expression::FieldReference { field_id, .. } if *field_id == filter.field_id),
+            )
+        };
+        match &where_modifier.filter_context {
+            None => {}
+            Some(FilterContext::AllFiltersIgnored()) => self.dashboard_filters.clear(),
+            Some(FilterContext::AllowedFilters { allowed_filters }) => self
+                .dashboard_filters
+                .retain(|filter| references(allowed_filters, filter)),
+            Some(FilterContext::IgnoredFilters { ignored_filters }) => self
+                .dashboard_filters
+                .retain(|filter| !references(ignored_filters, filter)),
+        }
+        self.additional_filters.extend(&where_modifier.additional_filters);
+    }
+
+    pub(crate) fn filters(&self) -> Vec<&'a Expression> {
+        self.dashboard_filters
+            .iter()
+            .map(|filter| &filter.condition)
+            .chain(self.additional_filters.iter().copied())
+            .collect()
+    }
+}
+
+pub(crate) fn aggregate_rows<C, I>(
+    expression: &Expression,
+    rows: I,
+    dashboard_filters: &[DashboardFilter],
+    functions: &FunctionRegistry,
+) -> Result<LiteralValue, Error>
+where
+    C: EvalContext,
+    I: IntoIterator<Item = C>,
+{
+    if lod::has_level_of_detail(expression) {
+        let rows = rows.into_iter().collect::<Vec<_>>();
+        let mut groups = lod::aggregate_groups(expression, &rows, &[], dashboard_filters, functions)?;
+        //without query groups, all rows form a single group
+        return Ok(groups.pop().map_or(LiteralValue::NullValue, |group| group.value));
+    }
+    aggregate_scope(expression, rows, &FilterScope::new(dashboard_filters), functions)
+}
+
+/// Evaluates an expression without GROUP BY modifiers over the rows, with the filters of `scope`
+/// applied to all aggregates.
+pub(crate) fn aggregate_scope<C, I>(
+    expression: &Expression,
+    rows: I,
+    scope: &FilterScope,
+    functions: &FunctionRegistry,
+) -> Result<LiteralValue, Error>
+where
+    C: EvalContext,
+    I: IntoIterator<Item = C>,
+{
+    let mut calls = Vec::new();
+    collect_aggregates(expression, scope, &mut calls)?;
+    let mut accumulators = calls
+        .iter()
+        .map(|call| Accumulator::new(call, functions))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for row in rows {
+        for (call, accumulator) in calls.iter().zip(accumulators.iter_mut()) {
+            let mut included = true;
+            for filter in &call.filters {
+                if eval_condition(filter, &row, functions)? != Some(true) {
+                    included = false;
+                    break;
+                }
+            }
+            if included {
+                let value = eval_node(call.argument, &row, functions)?;
+                accumulator.add(value).map_err(|kind| Error::new(kind, call.span))?;
+            }
+        }
+    }
+
+    let mut results = Vec::with_capacity(calls.len());
+    for (call, accumulator) in calls.iter().zip(accumulators) {
+        let value = accumulator.finish().map_err(|kind| Error::new(kind, call.span))?;
+        results.push((call.node, value));
+    }
+    //what is left after replacing the aggregates refers to no fields
+    let expression = replace_aggregates(expression, &results);
+    eval_node(&expression, &HashMap::<String, LiteralValue>::new(), functions)
+}
+
+//find the aggregate calls outside of other aggregates, fields must not be used there
+fn collect_aggregates<'a>(
+    expression: &'a Expression,
+    scope: &FilterScope<'a>,
+    calls: &mut Vec<AggregateCall<'a>>,
+) -> Result<(), Error> {
+    match expression {
+        Expression::Function {
+            function_name,
+            params,
+            span,
+        } if is_aggregate(function_name) => {
+            let expected = aggregate_arity(function_name).unwrap_or_default();
+            if params.len() != expected {
+                return Err(Error::new(
+                    ErrorKind::ArityMismatch {
+                        function_name: function_name.clone(),
+                        expected_min: expected,
+                        expected_max: Some(expected),
+                        actual: params.len(),
+                    },
+                    *span,
+                ));
+            }
+            if let Some(nested) = params.iter().find_map(find_aggregate) {
+                return Err(nested);
+            }
+            calls.push(AggregateCall {
+                node: expression,
+                function_name,
+                argument: &params[0],
+                filters: scope.filters(),
+                span: *span,
+            });
+            Ok(())
+        }
+        //values of GROUP BY modifiers have to be the same for all rows
+        Expression::FieldReference { field_id, span } if lod::is_level_of_detail_field(field_id) => {
+            calls.push(AggregateCall {
+                node: expression,
+                function_name: lod::LEVEL_OF_DETAIL,
+                argument: expression,
+                filters: scope.filters(),
+                span: *span,
+            });
+            Ok(())
+        }
+        Expression::FieldReference { field_id, span } => Err(Error::new(
+            ErrorKind::FieldNotAggregated {
+                field_id: field_id.clone(),
+            },
+            *span,
+        )),
+        Expression::ModifierExpression {
+            expression,
+            where_modifier,
+            group_by_modifier,
+            span,
+        } => {
+            //GROUP BY modifiers are replaced by their values before aggregates are collected
+            if group_by_modifier.is_some() {
+                return Err(Error::new(ErrorKind::UnsupportedModifier, *span));
+            }
+            let mut scope = scope.clone();
+            if let Some(where_modifier) = where_modifier {
+                scope.apply(where_modifier);
+            }
+            collect_aggregates(expression, &scope, calls)
+        }
+        _ => expression
+            .children()
+            .into_iter()
+            .try_for_each(|child| collect_aggregates(child, scope, calls)),
+    }
+}
+
+//aggregates inside aggregates have no meaning
+fn find_aggregate(expression: &Expression) -> Option<Error> {
+    if let Expression::Function {
+        function_name, span, ..
+    } = expression
+    {
+        if is_aggregate(function_name) {
+            return Some(Error::new(
+                ErrorKind::UnexpectedAggregate {
+                    function_name: function_name.clone(),
+                },
+                *span,
+            ));
+        }
+    }
+    expression.children().into_iter().find_map(find_aggregate)
+}
+
+//copy of the expression with aggregate calls replaced by their results and modifiers removed
+fn replace_aggregates(expression: &Expression, results: &[(&Expression, LiteralValue)]) -> Expression {
+    if let Some((_, value)) = results.iter().find(|(node, _)| ptr::eq(*node, expression)) {
+        return Expression::Literal {
+            value: value.clone(),
+            span: expression.span(),
+        };
+    }
+    let mapped = match expression {
+        Expression::ModifierExpression { expression, .. } => Ok(replace_aggregates(expression, results)),
+        _ => map_children(expression, |child| Ok(replace_aggregates(child, results))),
+    };
+    mapped.unwrap_or_else(|never: std::convert::Infallible| match never {})
+}
+
+/// Copy of the expression with every direct child replaced by the result of `map`,
+/// modifiers are kept around their mapped expression.
+pub(crate) fn map_children<'a, E>(
+    expression: &'a Expression,
+    mut map: impl FnMut(&'a Expression) -> Result<Expression, E>,
+) -> Result<Expression, E> {
+    let mapped = match expression {
+        Expression::Function {
+            function_name,
+            params,
+            span,
+        } => Expression::Function {
+            function_name: function_name.clone(),
+            params: params.iter().map(&mut map).collect::<Result<_, _>>()?,
+            span: *span,
+        },
+        Expression::IfExpression {
+            condition,
+            result,
+            else_result,
+            span,
+        } => Expression::IfExpression {
+            condition: Box::new(map(condition)?),
+            result: Box::new(map(result)?),
+            else_result: Box::new(map(else_result)?),
+            span: *span,
+        },
+        Expression::CaseExpression {
+            cases,
+            else_result,
+            span,
+        } => Expression::CaseExpression {
+            cases: cases
+                .iter()
+                .map(|case| {
+                    Ok(CaseBranch {
+                        condition: map(&case.condition)?,
+                        result: map(&case.result)?,
+                        span: case.span,
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+            else_result: match else_result {
+                Some(else_result) => Some(Box::new(map(else_result)?)),
+                None => None,
+            },
+            span: *span,
+        },
+        Expression::ModifierExpression {
+            expression,
+            where_modifier,
+            group_by_modifier,
+            span,
+        } => Expression::ModifierExpression {
+            expression: Box::new(map(expression)?),
+            where_modifier: where_modifier.clone(),
+            group_by_modifier: group_by_modifier.clone(),
+            span: *span,
+        },
+        Expression::Literal { .. } | Expression::FieldReference { .. } => expression.clone(),
+    };
+    Ok(mapped)
+}
+
+//values of aggregates that need all of them at once
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Distribution {
+    Median,
+    Percentile(f64),
+    StandardDeviation,
+    Variance,
+}
+
+enum Accumulator {
+    Sum(Option<LiteralValue>),
+    Average {
+        sum: Option<LiteralValue>,
+        count: i64,
+    },
+    Min(Option<LiteralValue>),
+    Max(Option<LiteralValue>),
+    Count(i64),
+    CountDistinct(HashSet<DistinctKey>),
+    Unique(Option<LiteralValue>),
+    Values {
+        distribution: Distribution,
+        values: Vec<LiteralValue>,
+    },
+}
+
+impl Accumulator {
+    fn new(call: &AggregateCall, functions: &FunctionRegistry) -> Result<Self, Error> {
+        let values = |distribution| Accumulator::Values {
+            distribution,
+            values: Vec::new(),
+        };
+        let accumulator = match call.function_name.to_ascii_lowercase().as_str() {
+            "sum" => Accumulator::Sum(None),
+            "avg" => Accumulator::Average { sum: None, count: 0 },
+            "min" => Accumulator::Min(None),
+            "max" => Accumulator::Max(None),
+            "count" => Accumulator::Count(0),
+            "countd" => Accumulator::CountDistinct(HashSet::new()),
+            "median" => values(Distribution::Median),
+            "stddev" => values(Distribution::StandardDeviation),
+            "variance" => values(Distribution::Variance),
+            lod::LEVEL_OF_DETAIL => Accumulator::Unique(None),
+            _ => values(Distribution::Percentile(percentile_argument(call, functions)?)),
+        };
+        Ok(accumulator)
+    }
+
+    //NULL values are ignored by all aggregates
+    fn add(&mut self, value: LiteralValue) -> Result<(), ErrorKind> {
+        if value == LiteralValue::NullValue {
+            return Ok(());
+        }
+        match self {
+            Accumulator::Sum(sum) => *sum = Some(add_numbers(sum.take(), value)?),
+            Accumulator::Average { sum, count } => {
+                *sum = Some(add_numbers(sum.take(), value)?);
+                *count += 1;
+            }
+            Accumulator::Min(min) => *min = Some(pick(min.take(), value, |ordering| ordering.is_lt())?),
+            Accumulator::Max(max) => *max = Some(pick(max.take(), value, |ordering| ordering.is_gt())?),
+            Accumulator::Count(count) => *count += 1,
+            Accumulator::CountDistinct(keys) => {
+                keys.insert(DistinctKey::from(value));
+            }
+            Accumulator::Unique(None) => *self = Accumulator::Unique(Some(value)),
+            Accumulator::Unique(Some(unique)) => {
+                if DistinctKey::from(unique.clone()) != DistinctKey::from(value) {
+                    return Err(ErrorKind::AmbiguousLevelOfDetail);
+                }
+            }
+            Accumulator::Values { values, .. } => {
+                check_numeric(&value)?;
+                values.push(value);
+            }
+        }
+        Ok(())
+    }
+
+    //aggregates of no values are NULL, except for the counts
+    fn finish(self) -> Result<LiteralValue, ErrorKind> {
+        match self {
+            Accumulator::Sum(sum) | Accumulator::Min(sum) | Accumulator::Max(sum) | Accumulator::Unique(sum) => {
+                Ok(sum.unwrap_or(LiteralValue::NullValue))
+            }
+            Accumulator::Average { sum: None, .. } => Ok(LiteralValue::NullValue),
+            Accumulator::Average { sum: Some(sum), count } => divide(&sum, count),
+            Accumulator::Count(count) => Ok(LiteralValue::IntegerValue(count)),
+            Accumulator::CountDistinct(keys) => Ok(LiteralValue::IntegerValue(keys.len() as i64)),
+            Accumulator::Values { distribution, values } => finish_distribution(distribution, values),
+        }
+    }
+}
+
+//the fraction of percentile(value, fraction) is the same for all rows
+fn percentile_argument(call: &AggregateCall, functions: &FunctionRegistry) -> Result<f64, Error> {
+    let Expression::Function { params, .. } = call.node else {
+        unreachable!("aggregate calls are functions")
+    };
+    let fraction = eval_node(&params[1], &HashMap::<String, LiteralValue>::new(), functions)?;
+    let fraction = f64::try_from(fraction).map_err(|e| e.at(params[1].span()))?;
+    if !(0.0..=1.0).contains(&fraction) {
+        return Err(Error::new(
+            ErrorKind::InvalidArgument {
+                function_name: call.function_name.to_string(),
+                reason: "the percentile has to be between 0 and 1".to_string(),
+            },
+            params[1].span(),
+        ));
+    }
+    Ok(fraction)
+}
+
+fn check_numeric(value: &LiteralValue) -> Result<(), ErrorKind> {
+    match promote(value, &LiteralValue::IntegerValue(0)) {
+        Some(_) => Ok(()),
+        None => Err(ValueTypeError::new(ValueType::Number, value).into()),
+    }
+}
+
+fn add_numbers(sum: Option<LiteralValue>, value: LiteralValue) -> Result<LiteralValue, ErrorKind> {
+    check_numeric(&value)?;
+    let Some(sum) = sum else {
+        return Ok(value);
+    };
+    let result = match promote(&sum, &value) {
+        Some(NumericOperands::Integer(left, right)) => left.checked_add(right).map(LiteralValue::IntegerValue),
+        Some(NumericOperands::Decimal(left, right)) => left.checked_add(right).map(LiteralValue::DecimalValue),
+        Some(NumericOperands::Float(left, right)) => Some(LiteralValue::NumberValue(left + right)),
+        None => return Err(ValueTypeError::new(ValueType::Number, &value).into()),
+    };
+    result.ok_or(ErrorKind::NumericOverflow)
+}
+
+//exact sums are divided exactly, like the `/` operator
+fn divide(sum: &LiteralValue, count: i64) -> Result<LiteralValue, ErrorKind> {
+    match promote(sum, &LiteralValue::IntegerValue(count)) {
+        Some(NumericOperands::Float(sum, count)) => Ok(LiteralValue::NumberValue(sum / count)),
+        Some(NumericOperands::Integer(sum, count)) => Decimal::from(sum)
+            .checked_div(Decimal::from(count))
+            .map(|average| LiteralValue::DecimalValue(average.normalize()))
+            .ok_or(ErrorKind::NumericOverflow),
+        Some(NumericOperands::Decimal(sum, count)) => sum
+            .checked_div(count)
+            .map(|average| LiteralValue::DecimalValue(average.normalize()))
+            .ok_or(ErrorKind::NumericOverflow),
+        None => Err(ValueTypeError::new(ValueType::Number, sum).into()),
+    }
+}
+
+//keep the value for which `better` holds compared to the current one, values have to be comparable
+fn pick(
+    current: Option<LiteralValue>,
+    value: LiteralValue,
+    better: impl Fn(std::cmp::Ordering) -> bool,
+) -> Result<LiteralValue, ErrorKind> {
+    let Some(current) = current else {
+        return Ok(value);
+    };
+    let ordering = compare_values(&value, &current, Span::default()).map_err(|e| e.kind().clone())?;
+    Ok(if ordering.is_some_and(better) { value } else { current })
+}
+
+fn finish_distribution(distribution: Distribution, values: Vec<LiteralValue>) -> Result<LiteralValue, ErrorKind> {
+    let exact = values
+        .iter()
+        .map(|value| match value {
+            LiteralValue::IntegerValue(value) => Some(Decimal::from(*value)),
+            LiteralValue::DecimalValue(value) => Some(*value),
+            _ => None,
+        })
+        .collect::<Option<Vec<Decimal>>>();
+    let fraction = match distribution {
+        Distribution::Median => 0.5,
+        Distribution::Percentile(fraction) => fraction,
+        Distribution::StandardDeviation | Distribution::Variance => {
+            let values = values.into_iter().map(f64::try_from).collect::<Result<Vec<_>, _>>()?;
+            let Some(variance) = sample_variance(&values) else {
+                return Ok(LiteralValue::NullValue);
+            };
+            let result = if distribution == Distribution::Variance {
+                variance
+            } else {
+                variance.sqrt()
+            };
+            return Ok(LiteralValue::NumberValue(result));
+        }
+    };
+    if values.is_empty() {
+        return Ok(LiteralValue::NullValue);
+    }
+    //interpolate between the two closest values, like PERCENTILE_CONT in SQL
+    let position = fraction * (values.len() - 1) as f64;
+    let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+    let weight = position - position.floor();
+    match exact {
+        Some(mut values) => {
+            values.sort();
+            let weight = Decimal::from_f64(weight).ok_or(ErrorKind::NumericOverflow)?;
+            let result = (values[upper] - values[lower])
+                .checked_mul(weight)
+                .and_then(|offset| values[lower].checked_add(offset))
+                .ok_or(ErrorKind::NumericOverflow)?;
+            Ok(LiteralValue::DecimalValue(result.normalize()))
+        }
+        None => {
+            let mut values = values.into_iter().map(f64::try_from).collect::<Result<Vec<_>, _>>()?;
+            values.sort_by(f64::total_cmp);
+            Ok(LiteralValue::NumberValue(
+                values[lower] + (values[upper] - values[lower]) * weight,
+            ))
+        }
+    }
+}
+
+//variance of a sample, None for less than two values
+fn sample_variance(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let count = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / count;
+    Some(values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (count - 1.0))
+}
+
+//hashable form of a value for countd, numbers of different kinds are equal if their values are
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub(crate) enum DistinctKey {
+    Exact(Decimal),
+    Float(u64),
+    String(String),
+    Boolean(bool),
+    Date(NaiveDate),
+    Timestamp(NaiveDateTime),
+    Interval(Interval),
+    Null,
+}
+
+impl From<LiteralValue> for DistinctKey {
+    fn from(value: LiteralValue) -> Self {
+        match value {
+            LiteralValue::IntegerValue(value) => DistinctKey::Exact(Decimal::from(value)),
+            LiteralValue::DecimalValue(value) => DistinctKey::Exact(value.normalize()),
+            LiteralValue::NumberValue(value) => match Decimal::from_f64(value) {
+                Some(exact) if exact.to_f64() == Some(value) => DistinctKey::Exact(exact.normalize()),
+                _ => DistinctKey::Float(value.to_bits()),
+            },
+            LiteralValue::StringValue(value) => DistinctKey::String(value),
+            LiteralValue::BooleanValue(value) => DistinctKey::Boolean(value),
+            LiteralValue::DateValue(value) => DistinctKey::Date(value),
+            LiteralValue::TimestampValue(value) => DistinctKey::Timestamp(value),
+            LiteralValue::IntervalValue(value) => DistinctKey::Interval(value),
+            LiteralValue::NullValue => DistinctKey::Null,
+        }
+    }
+}
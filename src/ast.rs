@@ -1,133 +1,620 @@
-#[derive(Debug, PartialEq)]
+//! Abstract syntax tree of analytical expressions.
+//!
+//! Trees are produced by [`convert_to_ast`](crate::convert_to_ast) and can also be built
+//! directly with the constructor functions at the bottom of this module, which leave the
+//! source spans empty.
+//!
+//! ```
+//! use analytical_expression_parser::ast::{field_ref, func, lit_num, Expression};
+//!
+//! let expression = func("+", vec![field_ref("table.column"), lit_num(42.0)]);
+//! if let Expression::Function { function_name, params, .. } = &expression {
+//!     assert_eq!(function_name, "+");
+//!     assert_eq!(params[0], field_ref("table.column"));
+//! }
+//! ```
+
+use std::fmt;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use crate::error::ValueTypeError;
+
+/// A node of the expression tree produced by [`convert_to_ast`](crate::convert_to_ast).
+///
+/// Operators are represented as `Function` nodes named after the operator as written in the
+/// source (`+`, `>=`, `and`, `NOT`, ...), function calls keep the name of the called function.
+/// Trees are dropped without recursion, so they can be nested to any depth.
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
     Literal {
         value: LiteralValue,
+        span: Span,
     },
+    /// Reference to a field, `field_id` is the dotted name as written, e.g. `table.column`.
     FieldReference {
         field_id: String,
+        span: Span,
     },
     Function {
         function_name: String,
         params: Vec<Expression>,
+        span: Span,
     },
     IfExpression {
         condition: Box<Expression>,
         result: Box<Expression>,
         else_result: Box<Expression>,
+        span: Span,
     },
     CaseExpression {
         cases: Vec<CaseBranch>,
-        else_result: Box<Expression>,
+        /// `None` when the source has no ELSE branch.
+        else_result: Option<Box<Expression>>,
+        span: Span,
     },
+    /// An expression followed by `[where ...]` and/or `[group by ...]` clauses.
     ModifierExpression {
         expression: Box<Expression>,
         where_modifier: Option<WhereModifier>,
         group_by_modifier: Option<GroupByModifier>,
+        span: Span,
     },
 }
 
-#[derive(Debug, PartialEq)]
+impl Expression {
+    /// Location of the expression in the source it was parsed from.
+    pub fn span(&self) -> Span {
+        match self {
+            Expression::Literal { span, .. }
+            | Expression::FieldReference { span, .. }
+            | Expression::Function { span, .. }
+            | Expression::IfExpression { span, .. }
+            | Expression::CaseExpression { span, .. }
+            | Expression::ModifierExpression { span, .. } => *span,
+        }
+    }
+
+    /// Compares two trees without their spans, e.g. a parsed tree with one built by hand.
+    pub fn eq_ignoring_spans(&self, other: &Expression) -> bool {
+        let (mut left, mut right) = (self.clone(), other.clone());
+        left.clear_spans();
+        right.clear_spans();
+        left == right
+    }
+
+    fn clear_spans(&mut self) {
+        match self {
+            Expression::Literal { span, .. } | Expression::FieldReference { span, .. } => *span = Span::default(),
+            Expression::Function { params, span, .. } => {
+                params.iter_mut().for_each(Expression::clear_spans);
+                *span = Span::default();
+            }
+            Expression::IfExpression {
+                condition,
+                result,
+                else_result,
+                span,
+            } => {
+                condition.clear_spans();
+                result.clear_spans();
+                else_result.clear_spans();
+                *span = Span::default();
+            }
+            Expression::CaseExpression {
+                cases,
+                else_result,
+                span,
+            } => {
+                for case in cases {
+                    case.condition.clear_spans();
+                    case.result.clear_spans();
+                    case.span = Span::default();
+                }
+                if let Some(else_result) = else_result {
+                    else_result.clear_spans();
+                }
+                *span = Span::default();
+            }
+            Expression::ModifierExpression {
+                expression,
+                where_modifier,
+                group_by_modifier,
+                span,
+            } => {
+                expression.clear_spans();
+                if let Some(where_modifier) = where_modifier {
+                    match &mut where_modifier.filter_context {
+                        Some(FilterContext::AllowedFilters { allowed_filters: fields })
+                        | Some(FilterContext::IgnoredFilters { ignored_filters: fields }) => {
+                            fields.iter_mut().for_each(Expression::clear_spans)
+                        }
+                        Some(FilterContext::AllFiltersIgnored()) | None => {}
+                    }
+                    where_modifier.additional_filters.iter_mut().for_each(Expression::clear_spans);
+                    where_modifier.span = Span::default();
+                }
+                if let Some(group_by_modifier) = group_by_modifier {
+                    if let GroupByContext::IncludedGroups { groups } = &mut group_by_modifier.group_context {
+                        for group in groups {
+                            match group {
+                                GroupReference::FieldGroup { field } => field.clear_spans(),
+                                GroupReference::QueryGroup { span, .. } => *span = Span::default(),
+                            }
+                        }
+                    }
+                    group_by_modifier.span = Span::default();
+                }
+                *span = Span::default();
+            }
+        }
+    }
+
+    /// Direct sub-expressions, including CASE branches, modifier filters and GROUP BY fields.
+    pub fn children(&self) -> Vec<&Expression> {
+        match self {
+            Expression::Literal { .. } | Expression::FieldReference { .. } => vec![],
+            Expression::Function { params, .. } => params.iter().collect(),
+            Expression::IfExpression {
+                condition,
+                result,
+                else_result,
+                ..
+            } => vec![condition, result, else_result],
+            Expression::CaseExpression {
+                cases, else_result, ..
+            } => cases
+                .iter()
+                .flat_map(|case| [&case.condition, &case.result])
+                .chain(else_result.as_deref())
+                .collect(),
+            Expression::ModifierExpression {
+                expression,
+                where_modifier,
+                group_by_modifier,
+                ..
+            } => {
+                let mut children: Vec<&Expression> = vec![expression];
+                if let Some(where_modifier) = where_modifier {
+                    match &where_modifier.filter_context {
+                        Some(FilterContext::AllowedFilters { allowed_filters: fields })
+                        | Some(FilterContext::IgnoredFilters { ignored_filters: fields }) => {
+                            children.extend(fields)
+                        }
+                        Some(FilterContext::AllFiltersIgnored()) | None => {}
+                    }
+                    children.extend(&where_modifier.additional_filters);
+                }
+                if let Some(GroupByModifier {
+                    group_context: GroupByContext::IncludedGroups { groups },
+                    ..
+                }) = group_by_modifier
+                {
+                    children.extend(groups.iter().filter_map(|group| match group {
+                        GroupReference::FieldGroup { field } => Some(field),
+                        GroupReference::QueryGroup { .. } => None,
+                    }));
+                }
+                children
+            }
+        }
+    }
+
+    //moves the sub-expressions out of the expression, which is left without any
+    fn take_children(&mut self, taken: &mut Vec<Expression>) {
+        let mut take = |expression: &mut Expression| taken.push(std::mem::replace(expression, lit_null()));
+        match self {
+            Expression::Literal { .. } | Expression::FieldReference { .. } => {}
+            Expression::Function { params, .. } => params.drain(..).for_each(|param| taken.push(param)),
+            Expression::IfExpression {
+                condition,
+                result,
+                else_result,
+                ..
+            } => {
+                take(condition);
+                take(result);
+                take(else_result);
+            }
+            Expression::CaseExpression {
+                cases, else_result, ..
+            } => {
+                for case in cases.drain(..) {
+                    taken.extend([case.condition, case.result]);
+                }
+                taken.extend(else_result.take().map(|else_result| *else_result));
+            }
+            Expression::ModifierExpression {
+                expression,
+                where_modifier,
+                group_by_modifier,
+                ..
+            } => {
+                take(expression);
+                if let Some(where_modifier) = where_modifier.take() {
+                    if let Some(
+                        FilterContext::AllowedFilters { allowed_filters: fields }
+                        | FilterContext::IgnoredFilters { ignored_filters: fields },
+                    ) = where_modifier.filter_context
+                    {
+                        taken.extend(fields);
+                    }
+                    taken.extend(where_modifier.additional_filters);
+                }
+                if let Some(GroupByModifier {
+                    group_context: GroupByContext::IncludedGroups { groups },
+                    ..
+                }) = group_by_modifier.take()
+                {
+                    taken.extend(groups.into_iter().filter_map(|group| match group {
+                        GroupReference::FieldGroup { field } => Some(field),
+                        GroupReference::QueryGroup { .. } => None,
+                    }));
+                }
+            }
+        }
+    }
+}
+
+//dropping the sub-expressions one after another, instead of recursively, keeps the stack shallow
+//for trees of any depth
+impl Drop for Expression {
+    fn drop(&mut self) {
+        let mut taken = Vec::new();
+        self.take_children(&mut taken);
+        while let Some(mut expression) = taken.pop() {
+            expression.take_children(&mut taken);
+        }
+    }
+}
+
+/// One `WHEN condition THEN result` branch of a CASE expression.
+#[derive(Debug, Clone, PartialEq)]
 pub struct CaseBranch {
     pub condition: Expression,
     pub result: Expression,
+    pub span: Span,
 }
 
-#[derive(Debug, PartialEq)]
+/// The `[where ...]` clause: which dashboard filters apply, plus extra filter expressions.
+#[derive(Debug, Clone, PartialEq)]
 pub struct WhereModifier {
+    /// `None` when the clause only lists filter expressions, so dashboard filters apply as usual.
     pub filter_context: Option<FilterContext>,
     pub additional_filters: Vec<Expression>,
+    pub span: Span,
 }
 
-#[derive(Debug, PartialEq)]
+/// Selects the dashboard filters that apply to a modified expression.
+#[derive(Debug, Clone, PartialEq)]
 pub enum FilterContext {
+    /// `allow filters on a, b`: only filters on the listed fields apply.
     AllowedFilters {
         allowed_filters: Vec<Expression>,
     },
+    /// `ignore all filters`: no dashboard filter applies.
     AllFiltersIgnored(),
+    /// `ignore filters on a, b`: filters on the listed fields do not apply.
     IgnoredFilters {
         ignored_filters: Vec<Expression>,
     },
 }
 
-#[derive(Debug, PartialEq)]
+/// The `[group by ...]` clause, setting the level of detail of a modified expression.
+#[derive(Debug, Clone, PartialEq)]
 pub struct GroupByModifier {
-    group_context: GroupByContext,
+    pub group_context: GroupByContext,
+    pub span: Span,
 }
 
-#[derive(Debug, PartialEq)]
+impl GroupByModifier {
+    pub fn new(group_context: GroupByContext, span: Span) -> Self {
+        GroupByModifier { group_context, span }
+    }
+
+    pub fn group_context(&self) -> &GroupByContext {
+        &self.group_context
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum GroupByContext {
+    /// `group by all groups`: computed over all rows, ignoring the query groups.
     AllGroups(),
     IncludedGroups {
         groups: Vec<GroupReference>,
     },
 }
 
-#[derive(Debug, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum GroupReference {
+    /// `group(N)`: the N-th grouping of the query, counting from 1.
     QueryGroup {
         index: usize,
+        span: Span,
     },
     FieldGroup {
         field: Expression,
     },
 }
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, PartialEq, Clone)]
 pub enum LiteralValue {
     StringValue(String),
+    /// Floating point number, produced by literals with an exponent such as `1e6`.
     NumberValue(f64),
+    /// Whole number, produced by literals such as `42`.
+    IntegerValue(i64),
+    /// Exact fixed-point number, produced by literals such as `0.1`.
+    DecimalValue(Decimal),
     BooleanValue(bool),
+    /// Calendar date, produced by literals such as `#2024-01-31#` or `date '2024-01-31'`.
+    DateValue(NaiveDate),
+    /// Date and time of day without a time zone, produced by literals such as
+    /// `#2024-01-31 12:30:00#` or `timestamp '2024-01-31 12:30:00'`.
+    TimestampValue(NaiveDateTime),
+    /// Produced by literals such as `interval '1 month 7 days'`.
+    IntervalValue(Interval),
+    /// Missing or unknown data, propagated with SQL semantics.
+    NullValue,
 }
 
-impl Into<f64> for LiteralValue {
-    fn into(self) -> f64 {
-        match self {
-            LiteralValue::NumberValue(value) => value,
-            _ => panic!("Cannot convert non-number literal to number"),
+/// Length of time added to or subtracted from dates and timestamps.
+///
+/// Months and days are kept apart from the time of day, as their length depends on the
+/// date they are applied to: `#2024-01-31# + interval '1 month'` is `#2024-02-29#`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    pub microseconds: i64,
+}
+
+impl Interval {
+    pub fn new(months: i32, days: i32, microseconds: i64) -> Self {
+        Interval {
+            months,
+            days,
+            microseconds,
         }
     }
 }
 
-impl Into<String> for LiteralValue {
-    fn into(self) -> String {
+/// Type of a [`LiteralValue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    String,
+    Number,
+    Integer,
+    Decimal,
+    Boolean,
+    Date,
+    Timestamp,
+    Interval,
+    Null,
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            LiteralValue::StringValue(value) => value,
-            _ => panic!("Cannot convert non-string literal to string"),
+            ValueType::String => write!(f, "string"),
+            ValueType::Number => write!(f, "number"),
+            ValueType::Integer => write!(f, "integer"),
+            ValueType::Decimal => write!(f, "decimal"),
+            ValueType::Boolean => write!(f, "boolean"),
+            ValueType::Date => write!(f, "date"),
+            ValueType::Timestamp => write!(f, "timestamp"),
+            ValueType::Interval => write!(f, "interval"),
+            ValueType::Null => write!(f, "null"),
         }
     }
 }
 
-impl Into<bool> for LiteralValue {
-    fn into(self) -> bool {
+impl LiteralValue {
+    pub fn value_type(&self) -> ValueType {
         match self {
-            LiteralValue::BooleanValue(value) => value,
-            _ => panic!("Cannot convert non-boolean literal to boolean"),
+            LiteralValue::StringValue(_) => ValueType::String,
+            LiteralValue::NumberValue(_) => ValueType::Number,
+            LiteralValue::IntegerValue(_) => ValueType::Integer,
+            LiteralValue::DecimalValue(_) => ValueType::Decimal,
+            LiteralValue::BooleanValue(_) => ValueType::Boolean,
+            LiteralValue::DateValue(_) => ValueType::Date,
+            LiteralValue::TimestampValue(_) => ValueType::Timestamp,
+            LiteralValue::IntervalValue(_) => ValueType::Interval,
+            LiteralValue::NullValue => ValueType::Null,
+        }
+    }
+}
+
+/// Location of an AST node in the source text: byte offsets plus 1-based line and column
+/// of both ends. `end` is exclusive.
+///
+/// Trees parsed from differently formatted sources, or built with the helper functions below,
+/// only differ in their spans, see [`Expression::eq_ignoring_spans`] to compare them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Span {
+    /// Span covering everything from the start of `self` to the end of `other`.
+    pub fn to(&self, other: Span) -> Span {
+        Span {
+            start: self.start,
+            start_line: self.start_line,
+            start_column: self.start_column,
+            end: other.end,
+            end_line: other.end_line,
+            end_column: other.end_column,
+        }
+    }
+}
+
+impl From<pest::Span<'_>> for Span {
+    fn from(span: pest::Span<'_>) -> Self {
+        let (start_line, start_column) = span.start_pos().line_col();
+        let (end_line, end_column) = span.end_pos().line_col();
+        Span {
+            start: span.start(),
+            end: span.end(),
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        }
+    }
+}
+
+/// Accepts every numeric value, integers and decimals may lose precision.
+impl TryFrom<LiteralValue> for f64 {
+    type Error = ValueTypeError;
+
+    fn try_from(value: LiteralValue) -> Result<Self, Self::Error> {
+        match value {
+            LiteralValue::NumberValue(value) => Ok(value),
+            LiteralValue::IntegerValue(value) => Ok(value as f64),
+            LiteralValue::DecimalValue(value) => value
+                .to_f64()
+                .ok_or_else(|| ValueTypeError::new(ValueType::Number, &LiteralValue::DecimalValue(value))),
+            other => Err(ValueTypeError::new(ValueType::Number, &other)),
         }
     }
 }
 
-//short funcs for tests
+impl TryFrom<LiteralValue> for i64 {
+    type Error = ValueTypeError;
+
+    fn try_from(value: LiteralValue) -> Result<Self, Self::Error> {
+        match value {
+            LiteralValue::IntegerValue(value) => Ok(value),
+            other => Err(ValueTypeError::new(ValueType::Integer, &other)),
+        }
+    }
+}
+
+/// Accepts integers and decimals, which convert without loss.
+impl TryFrom<LiteralValue> for Decimal {
+    type Error = ValueTypeError;
+
+    fn try_from(value: LiteralValue) -> Result<Self, Self::Error> {
+        match value {
+            LiteralValue::DecimalValue(value) => Ok(value),
+            LiteralValue::IntegerValue(value) => Ok(Decimal::from(value)),
+            other => Err(ValueTypeError::new(ValueType::Decimal, &other)),
+        }
+    }
+}
+
+impl TryFrom<LiteralValue> for String {
+    type Error = ValueTypeError;
+
+    fn try_from(value: LiteralValue) -> Result<Self, Self::Error> {
+        match value {
+            LiteralValue::StringValue(value) => Ok(value),
+            other => Err(ValueTypeError::new(ValueType::String, &other)),
+        }
+    }
+}
+
+impl TryFrom<LiteralValue> for bool {
+    type Error = ValueTypeError;
+
+    fn try_from(value: LiteralValue) -> Result<Self, Self::Error> {
+        match value {
+            LiteralValue::BooleanValue(value) => Ok(value),
+            other => Err(ValueTypeError::new(ValueType::Boolean, &other)),
+        }
+    }
+}
+
+impl TryFrom<LiteralValue> for NaiveDate {
+    type Error = ValueTypeError;
+
+    fn try_from(value: LiteralValue) -> Result<Self, Self::Error> {
+        match value {
+            LiteralValue::DateValue(value) => Ok(value),
+            other => Err(ValueTypeError::new(ValueType::Date, &other)),
+        }
+    }
+}
+
+/// Accepts dates as well, which become midnight of that day.
+impl TryFrom<LiteralValue> for NaiveDateTime {
+    type Error = ValueTypeError;
+
+    fn try_from(value: LiteralValue) -> Result<Self, Self::Error> {
+        match value {
+            LiteralValue::TimestampValue(value) => Ok(value),
+            LiteralValue::DateValue(value) => Ok(value.and_time(NaiveTime::MIN)),
+            other => Err(ValueTypeError::new(ValueType::Timestamp, &other)),
+        }
+    }
+}
+
+//short constructor funcs, used by tests and by code building trees by hand
+pub fn lit(value: LiteralValue) -> Expression {
+    Expression::Literal {
+        value,
+        span: Span::default(),
+    }
+}
+
 pub fn lit_str(value: &str) -> Expression {
     Expression::Literal {
         value: LiteralValue::StringValue(value.to_string()),
+        span: Span::default(),
     }
 }
 
 pub fn lit_num(value: f64) -> Expression {
     Expression::Literal {
         value: LiteralValue::NumberValue(value),
+        span: Span::default(),
+    }
+}
+
+pub fn lit_int(value: i64) -> Expression {
+    Expression::Literal {
+        value: LiteralValue::IntegerValue(value),
+        span: Span::default(),
+    }
+}
+
+pub fn lit_dec(value: Decimal) -> Expression {
+    Expression::Literal {
+        value: LiteralValue::DecimalValue(value),
+        span: Span::default(),
     }
 }
 
 pub fn lit_bool(value: bool) -> Expression {
     Expression::Literal {
         value: LiteralValue::BooleanValue(value),
+        span: Span::default(),
+    }
+}
+
+pub fn lit_null() -> Expression {
+    Expression::Literal {
+        value: LiteralValue::NullValue,
+        span: Span::default(),
     }
 }
 
 pub fn field_ref(field_id: &str) -> Expression {
     Expression::FieldReference {
         field_id: field_id.to_string(),
+        span: Span::default(),
     }
 }
 
@@ -135,6 +622,7 @@ pub fn func(function_name: &str, params: Vec<Expression>) -> Expression {
     Expression::Function {
         function_name: function_name.to_string(),
         params,
+        span: Span::default(),
     }
 }
 
@@ -143,22 +631,53 @@ pub fn if_expr(condition: Expression, result: Expression, else_result: Expressio
         condition: Box::new(condition),
         result: Box::new(result),
         else_result: Box::new(else_result),
+        span: Span::default(),
     }
 }
 
 pub fn case_expr(cases: Vec<CaseBranch>, else_result: Expression) -> Expression {
     Expression::CaseExpression {
         cases,
-        else_result: Box::new(else_result),
+        else_result: Some(Box::new(else_result)),
+        span: Span::default(),
+    }
+}
+
+pub fn case_expr_no_else(cases: Vec<CaseBranch>) -> Expression {
+    Expression::CaseExpression {
+        cases,
+        else_result: None,
+        span: Span::default(),
     }
 }
 
 pub fn case_branch(condition: Expression, result: Expression) -> CaseBranch {
-    CaseBranch { condition, result }
+    CaseBranch {
+        condition,
+        result,
+        span: Span::default(),
+    }
+}
+
+pub fn modifier_expr(
+    expression: Expression,
+    where_modifier: Option<WhereModifier>,
+    group_by_modifier: Option<GroupByModifier>,
+) -> Expression {
+    Expression::ModifierExpression {
+        expression: Box::new(expression),
+        where_modifier,
+        group_by_modifier,
+        span: Span::default(),
+    }
 }
 
 pub fn where_modifier(filter_context: Option<FilterContext>, additional_filters: Vec<Expression>) -> WhereModifier {
-    WhereModifier { filter_context, additional_filters }
+    WhereModifier {
+        filter_context,
+        additional_filters,
+        span: Span::default(),
+    }
 }
 
 pub fn allowed_filters(allowed_filters: Vec<Expression>) -> FilterContext {
@@ -174,11 +693,14 @@ pub fn ignore_all_filters() -> FilterContext {
 }
 
 pub fn group_by_modifier(group_context: GroupByContext) -> GroupByModifier {
-    GroupByModifier { group_context }
+    GroupByModifier::new(group_context, Span::default())
 }
 
 pub fn query_group(index: usize) -> GroupReference {
-    GroupReference::QueryGroup { index }
+    GroupReference::QueryGroup {
+        index,
+        span: Span::default(),
+    }
 }
 
 pub fn field_group(field: Expression) -> GroupReference {
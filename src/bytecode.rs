@@ -0,0 +1,578 @@
+//! Compilation of expressions to a flat program for a stack machine.
+//!
+//! A [`Program`] is a vector of [`Instruction`]s, with jumps for the branches of IF and CASE and
+//! for AND and OR, which do not evaluate operands once the result is known. Neither compiling
+//! nor evaluating a program recurses, so deeply nested expressions do not overflow the stack;
+//! [`evaluate`](crate::evaluate) runs the parts of a tree nested too deep for its recursion as a
+//! program.
+//! Field references are resolved to the index of their column, and the spans needed for error
+//! messages are kept apart from the instructions. [`Program::to_bytes`] encodes a program, so it
+//! can be compiled once and evaluated elsewhere after [`Program::from_bytes`] decodes it.
+//!
+//! ```
+//! use analytical_expression_parser::ast::LiteralValue;
+//! use analytical_expression_parser::bytecode::{Instruction, Program};
+//! use analytical_expression_parser::parse;
+//!
+//! let expression = parse("if paid then amount else 0").unwrap();
+//! let program = Program::compile(&expression, &["amount", "paid"]);
+//! assert_eq!(program.instructions()[1], Instruction::JumpUnlessTrue { target: 4, span: 0 });
+//!
+//! let row = [LiteralValue::IntegerValue(25), LiteralValue::BooleanValue(true)];
+//! assert_eq!(program.evaluate(&row).unwrap(), LiteralValue::IntegerValue(25));
+//! ```
+
+use std::borrow::Cow;
+
+use crate::aggregate;
+use crate::ast::{Expression, LiteralValue, Span};
+use crate::error::{Error, ErrorKind, ValueTypeError};
+use crate::eval::{arity_mismatch, call_with_arguments, condition_value, eval_binary_operator};
+use crate::functions::{Conditional, FunctionRegistry, ParamType, Signature};
+
+mod encoding;
+
+pub use encoding::DecodeError;
+
+/// Arithmetic and comparison operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Operator {
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        Some(match symbol {
+            "+" => Operator::Add,
+            "-" => Operator::Subtract,
+            "*" => Operator::Multiply,
+            "/" => Operator::Divide,
+            "=" => Operator::Equal,
+            "!=" => Operator::NotEqual,
+            "<" => Operator::Less,
+            "<=" => Operator::LessOrEqual,
+            ">" => Operator::Greater,
+            ">=" => Operator::GreaterOrEqual,
+            _ => return None,
+        })
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+            Operator::Equal => "=",
+            Operator::NotEqual => "!=",
+            Operator::Less => "<",
+            Operator::LessOrEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterOrEqual => ">=",
+        }
+    }
+}
+
+/// One instruction of a [`Program`], working on a stack of values.
+///
+/// `span` fields are indices into the spans of the program, which locate errors in the source.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instruction {
+    Push(LiteralValue),
+    /// Pushes the value of the column with the index `slot` in the row.
+    Load {
+        slot: usize,
+        span: usize,
+    },
+    /// Pops two operands and pushes the result of the operator. `spans` is the index of the spans
+    /// of the left operand, the right operand and the operator, in this order.
+    Operator {
+        operator: Operator,
+        spans: usize,
+    },
+    /// Negates the condition on top of the stack.
+    Not {
+        span: usize,
+    },
+    /// Replaces the value on top of the stack by whether it is NULL, or not NULL when `negated`.
+    IsNull {
+        negated: bool,
+    },
+    /// Fails when the argument on top of the stack does not have the type of the parameter.
+    CheckArgument {
+        param_type: ParamType,
+        span: usize,
+    },
+    /// Pops `arg_count` arguments and pushes the result of the function with the index `function`
+    /// in the functions of the program.
+    Call {
+        function: usize,
+        arg_count: usize,
+        span: usize,
+    },
+    /// Continues with the instruction at the index.
+    Jump(usize),
+    /// Continues with the instruction at the index unless the value on top of the stack is NULL,
+    /// which is popped.
+    JumpUnlessNull(usize),
+    /// Pops a condition and continues at `target` unless it is true.
+    JumpUnlessTrue {
+        target: usize,
+        span: usize,
+    },
+    /// Pops an operand of AND (`value` false) or OR (`value` true) with the result so far below
+    /// it. An operand equal to `value` decides the result and continues at `target`, a NULL
+    /// operand makes the result NULL unless a later operand decides it.
+    ShortCircuit {
+        value: bool,
+        target: usize,
+        span: usize,
+    },
+    /// Fails with the error with the index in the errors of the program.
+    Fail(usize),
+}
+
+/// An expression compiled to instructions, for rows with the values of a list of columns.
+///
+/// The results are the same as the ones of [`evaluate`](crate::evaluate), including the errors:
+/// unknown fields and functions are reported when evaluating a row that needs them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    instructions: Vec<Instruction>,
+    spans: Vec<Span>,
+    columns: Vec<String>,
+    functions: Vec<String>,
+    errors: Vec<Error>,
+}
+
+impl Program {
+    /// Compiles an expression with the built-in functions.
+    pub fn compile<S: AsRef<str>>(expression: &Expression, columns: &[S]) -> Self {
+        compile_with(expression, columns, FunctionRegistry::builtins())
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Names of the functions called by the program.
+    pub fn functions(&self) -> &[String] {
+        &self.functions
+    }
+
+    /// Evaluates the program with the built-in functions on a row with the values of the columns
+    /// it was compiled for, in the same order. Fields of columns missing from the row are unknown.
+    pub fn evaluate(&self, row: &[LiteralValue]) -> Result<LiteralValue, Error> {
+        self.run(row, FunctionRegistry::builtins())
+    }
+
+    pub(crate) fn run(&self, row: &[LiteralValue], functions: &FunctionRegistry) -> Result<LiteralValue, Error> {
+        self.run_with(|slot| row.get(slot).map(Cow::Borrowed), functions)
+    }
+
+    //`load` gives the value of the column with an index, `None` for unknown fields
+    pub(crate) fn run_with<'r>(
+        &self,
+        load: impl Fn(usize) -> Option<Cow<'r, LiteralValue>>,
+        functions: &FunctionRegistry,
+    ) -> Result<LiteralValue, Error> {
+        let mut stack: Vec<LiteralValue> = Vec::new();
+        let mut pc = 0;
+        while let Some(instruction) = self.instructions.get(pc) {
+            pc += 1;
+            match instruction {
+                Instruction::Push(value) => stack.push(value.clone()),
+                Instruction::Load { slot, span } => match load(*slot) {
+                    Some(value) => stack.push(value.into_owned()),
+                    None => {
+                        return Err(Error::new(
+                            ErrorKind::UnknownField {
+                                field_id: self.columns[*slot].clone(),
+                            },
+                            self.spans[*span],
+                        ))
+                    }
+                },
+                Instruction::Operator { operator, spans } => {
+                    let right = pop(&mut stack);
+                    let left = pop(&mut stack);
+                    let operand_spans = [self.spans[*spans], self.spans[spans + 1]];
+                    let result =
+                        eval_binary_operator(operator.symbol(), &left, &right, operand_spans, self.spans[spans + 2])?;
+                    stack.push(result);
+                }
+                Instruction::Not { span } => {
+                    let result = match condition_value(&pop(&mut stack), self.spans[*span])? {
+                        Some(value) => LiteralValue::BooleanValue(!value),
+                        None => LiteralValue::NullValue,
+                    };
+                    stack.push(result);
+                }
+                Instruction::IsNull { negated } => {
+                    let is_null = pop(&mut stack) == LiteralValue::NullValue;
+                    stack.push(LiteralValue::BooleanValue(is_null != *negated));
+                }
+                Instruction::CheckArgument { param_type, span } => {
+                    let value = stack.last().expect("arguments are pushed before they are checked");
+                    if !param_type.accepts(value.value_type()) {
+                        return Err(ValueTypeError::new(param_type.expected_type(), value).at(self.spans[*span]));
+                    }
+                }
+                Instruction::Call {
+                    function,
+                    arg_count,
+                    span,
+                } => {
+                    let function_name = &self.functions[*function];
+                    let Some(function) = functions.get(function_name) else {
+                        return Err(Error::new(
+                            ErrorKind::UnknownFunction {
+                                function_name: function_name.clone(),
+                            },
+                            self.spans[*span],
+                        ));
+                    };
+                    //the arguments are passed from the stack and replaced by the result
+                    let args_start = stack.len() - arg_count;
+                    let result = call_with_arguments(function, &stack[args_start..], self.spans[*span])?;
+                    stack.truncate(args_start);
+                    stack.push(result);
+                }
+                Instruction::Jump(target) => pc = *target,
+                Instruction::JumpUnlessNull(target) => {
+                    if stack.last() == Some(&LiteralValue::NullValue) {
+                        stack.pop();
+                    } else {
+                        pc = *target;
+                    }
+                }
+                Instruction::JumpUnlessTrue { target, span } => {
+                    if condition_value(&pop(&mut stack), self.spans[*span])? != Some(true) {
+                        pc = *target;
+                    }
+                }
+                Instruction::ShortCircuit { value, target, span } => {
+                    let operand = condition_value(&pop(&mut stack), self.spans[*span])?;
+                    let result = stack
+                        .last_mut()
+                        .expect("the result of AND and OR is pushed before the operands");
+                    match operand {
+                        Some(operand) if operand == *value => {
+                            *result = LiteralValue::BooleanValue(*value);
+                            pc = *target;
+                        }
+                        Some(_) => {}
+                        None => *result = LiteralValue::NullValue,
+                    }
+                }
+                Instruction::Fail(error) => return Err(self.errors[*error].clone()),
+            }
+        }
+        Ok(pop(&mut stack))
+    }
+}
+
+fn pop(stack: &mut Vec<LiteralValue>) -> LiteralValue {
+    stack.pop().expect("instructions pop only the values pushed for them")
+}
+
+pub(crate) fn compile_with<S: AsRef<str>>(
+    expression: &Expression,
+    columns: &[S],
+    functions: &FunctionRegistry,
+) -> Program {
+    let mut compiler = Compiler {
+        functions,
+        program: Program {
+            instructions: Vec::new(),
+            spans: Vec::new(),
+            columns: columns.iter().map(|column| column.as_ref().to_string()).collect(),
+            functions: Vec::new(),
+            errors: Vec::new(),
+        },
+        labels: Vec::new(),
+    };
+    //the steps of a node are pushed in reverse order, so they are taken in the order they are executed
+    let mut steps = vec![Step::Visit(expression)];
+    while let Some(step) = steps.pop() {
+        match step {
+            Step::Visit(expression) => steps.extend(compiler.visit(expression).into_iter().rev()),
+            Step::Emit(instruction) => compiler.program.instructions.push(instruction),
+            Step::Label(label) => compiler.labels[label] = compiler.program.instructions.len(),
+        }
+    }
+    //jumps are emitted with the label of their target
+    let labels = compiler.labels;
+    for instruction in &mut compiler.program.instructions {
+        match instruction {
+            Instruction::Jump(target)
+            | Instruction::JumpUnlessNull(target)
+            | Instruction::JumpUnlessTrue { target, .. }
+            | Instruction::ShortCircuit { target, .. } => *target = labels[*target],
+            _ => {}
+        }
+    }
+    compiler.program
+}
+
+enum Step<'e> {
+    Visit(&'e Expression),
+    Emit(Instruction),
+    //the position of the next instruction is the target of the jumps to the label
+    Label(usize),
+}
+
+struct Compiler<'f> {
+    functions: &'f FunctionRegistry,
+    program: Program,
+    labels: Vec<usize>,
+}
+
+impl Compiler<'_> {
+    //the steps compiling the expression, which do what `eval::eval_node` does for the same kind of node
+    fn visit<'e>(&mut self, expression: &'e Expression) -> Vec<Step<'e>> {
+        match expression {
+            Expression::Literal { value, .. } => vec![Step::Emit(Instruction::Push(value.clone()))],
+            Expression::FieldReference { field_id, span } => {
+                match self.program.columns.iter().position(|column| column == field_id) {
+                    Some(slot) => vec![Step::Emit(Instruction::Load {
+                        slot,
+                        span: self.span(*span),
+                    })],
+                    None => self.fail(
+                        ErrorKind::UnknownField {
+                            field_id: field_id.clone(),
+                        },
+                        *span,
+                    ),
+                }
+            }
+            Expression::Function {
+                function_name,
+                params,
+                span,
+            } => self.function(function_name, params, *span),
+            Expression::IfExpression {
+                condition,
+                result,
+                else_result,
+                ..
+            } => {
+                let (else_label, end_label) = (self.label(), self.label());
+                vec![
+                    Step::Visit(condition),
+                    Step::Emit(Instruction::JumpUnlessTrue {
+                        target: else_label,
+                        span: self.span(condition.span()),
+                    }),
+                    Step::Visit(result),
+                    Step::Emit(Instruction::Jump(end_label)),
+                    Step::Label(else_label),
+                    Step::Visit(else_result),
+                    Step::Label(end_label),
+                ]
+            }
+            Expression::CaseExpression { cases, else_result, .. } => {
+                let end_label = self.label();
+                let mut steps = Vec::new();
+                for case in cases {
+                    let next_label = self.label();
+                    steps.extend([
+                        Step::Visit(&case.condition),
+                        Step::Emit(Instruction::JumpUnlessTrue {
+                            target: next_label,
+                            span: self.span(case.condition.span()),
+                        }),
+                        Step::Visit(&case.result),
+                        Step::Emit(Instruction::Jump(end_label)),
+                        Step::Label(next_label),
+                    ]);
+                }
+                steps.push(match else_result {
+                    Some(else_result) => Step::Visit(else_result),
+                    None => Step::Emit(Instruction::Push(LiteralValue::NullValue)),
+                });
+                steps.push(Step::Label(end_label));
+                steps
+            }
+            Expression::ModifierExpression { span, .. } => self.fail(ErrorKind::UnsupportedModifier, *span),
+        }
+    }
+
+    fn function<'e>(&mut self, function_name: &str, params: &'e [Expression], span: Span) -> Vec<Step<'e>> {
+        if function_name.eq_ignore_ascii_case("and") || function_name.eq_ignore_ascii_case("or") {
+            let short_circuit_value = function_name.eq_ignore_ascii_case("or");
+            let end_label = self.label();
+            let mut steps = vec![Step::Emit(Instruction::Push(LiteralValue::BooleanValue(
+                !short_circuit_value,
+            )))];
+            for param in params {
+                steps.push(Step::Visit(param));
+                steps.push(Step::Emit(Instruction::ShortCircuit {
+                    value: short_circuit_value,
+                    target: end_label,
+                    span: self.span(param.span()),
+                }));
+            }
+            steps.push(Step::Label(end_label));
+            return steps;
+        }
+
+        if let Some(operator) = Operator::from_symbol(function_name) {
+            let [left, right] = params else {
+                return self.fail_with(arity_mismatch(function_name, 2, params.len(), span));
+            };
+            let spans = self.program.spans.len();
+            self.program.spans.extend([left.span(), right.span(), span]);
+            return vec![
+                Step::Visit(left),
+                Step::Visit(right),
+                Step::Emit(Instruction::Operator { operator, spans }),
+            ];
+        }
+        match function_name {
+            not if not.eq_ignore_ascii_case("not") => match params {
+                [operand] => vec![
+                    Step::Visit(operand),
+                    Step::Emit(Instruction::Not {
+                        span: self.span(operand.span()),
+                    }),
+                ],
+                _ => self.fail_with(arity_mismatch(function_name, 1, params.len(), span)),
+            },
+            "is null" | "is not null" => match params {
+                [operand] => vec![
+                    Step::Visit(operand),
+                    Step::Emit(Instruction::IsNull {
+                        negated: function_name == "is not null",
+                    }),
+                ],
+                _ => self.fail_with(arity_mismatch(function_name, 1, params.len(), span)),
+            },
+            _ => {
+                let Some(function) = self.functions.get(function_name) else {
+                    let kind = if aggregate::is_aggregate(function_name) {
+                        ErrorKind::UnexpectedAggregate {
+                            function_name: function_name.to_string(),
+                        }
+                    } else {
+                        ErrorKind::UnknownFunction {
+                            function_name: function_name.to_string(),
+                        }
+                    };
+                    return self.fail(kind, span);
+                };
+                let signature = function.signature();
+                if let Err(error) = signature.check_arity(function_name, params.len(), span) {
+                    return self.fail_with(error);
+                }
+                if let Some(conditional) = function.conditional() {
+                    return self.conditional(conditional, signature, params);
+                }
+                let mut steps = Vec::new();
+                for (index, param) in params.iter().enumerate() {
+                    steps.extend(self.argument(signature, index, param));
+                }
+                let function = match self.program.functions.iter().position(|name| name == function_name) {
+                    Some(function) => function,
+                    None => {
+                        self.program.functions.push(function_name.to_string());
+                        self.program.functions.len() - 1
+                    }
+                };
+                steps.push(Step::Emit(Instruction::Call {
+                    function,
+                    arg_count: params.len(),
+                    span: self.span(span),
+                }));
+                steps
+            }
+        }
+    }
+
+    //the arguments are evaluated only when they are needed, like in `eval::eval_conditional`
+    fn conditional<'e>(
+        &mut self,
+        conditional: Conditional,
+        signature: &Signature,
+        params: &'e [Expression],
+    ) -> Vec<Step<'e>> {
+        let end_label = self.label();
+        let mut steps = Vec::new();
+        match conditional {
+            Conditional::Iif => {
+                let (known_label, else_label) = (self.label(), self.label());
+                steps.extend(self.argument(signature, 0, &params[0]));
+                steps.push(Step::Emit(Instruction::JumpUnlessNull(known_label)));
+                match params.get(3) {
+                    Some(unknown) => steps.extend(self.argument(signature, 3, unknown)),
+                    None => steps.push(Step::Emit(Instruction::Push(LiteralValue::NullValue))),
+                }
+                steps.push(Step::Emit(Instruction::Jump(end_label)));
+                steps.push(Step::Label(known_label));
+                steps.push(Step::Emit(Instruction::JumpUnlessTrue {
+                    target: else_label,
+                    span: self.span(params[0].span()),
+                }));
+                steps.extend(self.argument(signature, 1, &params[1]));
+                steps.push(Step::Emit(Instruction::Jump(end_label)));
+                steps.push(Step::Label(else_label));
+                steps.extend(self.argument(signature, 2, &params[2]));
+            }
+            Conditional::FirstNonNull => {
+                for (index, param) in params.iter().enumerate() {
+                    steps.extend(self.argument(signature, index, param));
+                    if index + 1 < params.len() {
+                        steps.push(Step::Emit(Instruction::JumpUnlessNull(end_label)));
+                    }
+                }
+            }
+        }
+        steps.push(Step::Label(end_label));
+        steps
+    }
+
+    fn argument<'e>(&mut self, signature: &Signature, index: usize, param: &'e Expression) -> Vec<Step<'e>> {
+        let mut steps = vec![Step::Visit(param)];
+        if let Some(param_type) = signature.param_type(index) {
+            steps.push(Step::Emit(Instruction::CheckArgument {
+                param_type,
+                span: self.span(param.span()),
+            }));
+        }
+        steps
+    }
+
+    fn span(&mut self, span: Span) -> usize {
+        self.program.spans.push(span);
+        self.program.spans.len() - 1
+    }
+
+    fn label(&mut self) -> usize {
+        self.labels.push(0);
+        self.labels.len() - 1
+    }
+
+    fn fail<'e>(&mut self, kind: ErrorKind, span: Span) -> Vec<Step<'e>> {
+        self.fail_with(Error::new(kind, span))
+    }
+
+    //errors that the evaluator reports when it reaches the node are reported by the instruction
+    fn fail_with<'e>(&mut self, error: Error) -> Vec<Step<'e>> {
+        self.program.errors.push(error);
+        vec![Step::Emit(Instruction::Fail(self.program.errors.len() - 1))]
+    }
+}
@@ -0,0 +1,643 @@
+//! Binary encoding of programs, so they can be compiled once and stored or sent elsewhere.
+//!
+//! The encoding starts with a magic number and a version, followed by the columns, functions,
+//! spans, errors and instructions of the program. Unsigned integers are LEB128 varints, signed
+//! ones are zigzag encoded first, floats are their little-endian bits and strings are their
+//! length followed by their UTF-8 bytes.
+
+use std::fmt;
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use rust_decimal::Decimal;
+
+use super::{Instruction, Operator, Program};
+use crate::ast::{Interval, LiteralValue, Span, ValueType};
+use crate::error::{Error, ErrorKind};
+use crate::functions::ParamType;
+
+const MAGIC: &[u8; 4] = b"AEPB";
+const VERSION: u8 = 1;
+
+//the position of a value in these lists is its tag in the encoding
+const OPERATORS: [Operator; 10] = [
+    Operator::Add,
+    Operator::Subtract,
+    Operator::Multiply,
+    Operator::Divide,
+    Operator::Equal,
+    Operator::NotEqual,
+    Operator::Less,
+    Operator::LessOrEqual,
+    Operator::Greater,
+    Operator::GreaterOrEqual,
+];
+const VALUE_TYPES: [ValueType; 9] = [
+    ValueType::String,
+    ValueType::Number,
+    ValueType::Integer,
+    ValueType::Decimal,
+    ValueType::Boolean,
+    ValueType::Date,
+    ValueType::Timestamp,
+    ValueType::Interval,
+    ValueType::Null,
+];
+
+/// Returned by [`Program::from_bytes`] for bytes that do not encode a valid program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The bytes end early, or have an unexpected value at the offset.
+    Malformed { offset: usize },
+    /// The instructions refer to columns, functions, spans, errors or instructions the program
+    /// does not have, or do not leave exactly one value on the stack.
+    InvalidProgram,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Malformed { offset } => write!(f, "malformed program at byte {}", offset),
+            DecodeError::InvalidProgram => write!(f, "invalid program"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl Program {
+    /// Encodes the program, [`Program::from_bytes`] decodes it again.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer(MAGIC.to_vec());
+        writer.byte(VERSION);
+        writer.usize(self.columns.len());
+        for column in &self.columns {
+            writer.string(column);
+        }
+        writer.usize(self.functions.len());
+        for function in &self.functions {
+            writer.string(function);
+        }
+        writer.usize(self.spans.len());
+        for span in &self.spans {
+            writer.span(*span);
+        }
+        writer.usize(self.errors.len());
+        for error in &self.errors {
+            writer.error(error);
+        }
+        writer.usize(self.instructions.len());
+        for instruction in &self.instructions {
+            writer.instruction(instruction);
+        }
+        writer.0
+    }
+
+    /// Decodes a program encoded by [`Program::to_bytes`].
+    ///
+    /// The program is checked before it is returned, so evaluating it cannot panic even when the
+    /// bytes were not produced by [`Program::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(DecodeError::Malformed { offset: 0 });
+        }
+        if reader.byte()? != VERSION {
+            return Err(DecodeError::Malformed { offset: MAGIC.len() });
+        }
+        let program = Program {
+            columns: reader.list(Reader::string)?,
+            functions: reader.list(Reader::string)?,
+            spans: reader.list(Reader::span)?,
+            errors: reader.list(Reader::error)?,
+            instructions: reader.list(Reader::instruction)?,
+        };
+        if reader.offset != bytes.len() {
+            return Err(DecodeError::Malformed { offset: reader.offset });
+        }
+        if !program.is_valid() {
+            return Err(DecodeError::InvalidProgram);
+        }
+        Ok(program)
+    }
+
+    //compiled programs only jump forward, so the height of the stack before each instruction is
+    //known once the instructions before it are checked
+    fn is_valid(&self) -> bool {
+        let count = self.instructions.len();
+        let mut heights: Vec<Option<usize>> = vec![None; count + 1];
+        heights[0] = Some(0);
+        let span = |span: usize| span < self.spans.len();
+        for (pc, instruction) in self.instructions.iter().enumerate() {
+            let Some(height) = heights[pc] else {
+                continue;
+            };
+            let jump = |target: usize| target > pc && target <= count;
+            //the values popped and pushed, and the target of a jump with the values popped when it is taken
+            let (pops, pushes, jump_to) = match instruction {
+                Instruction::Push(_) => (0, 1, None),
+                Instruction::Load { slot, span: index } if *slot < self.columns.len() && span(*index) => (0, 1, None),
+                Instruction::Operator { spans, .. } if spans.checked_add(2).is_some_and(span) => (2, 1, None),
+                Instruction::Not { span: index } if span(*index) => (1, 1, None),
+                Instruction::IsNull { .. } => (1, 1, None),
+                Instruction::CheckArgument { span: index, .. } if span(*index) => (1, 1, None),
+                Instruction::Call {
+                    function,
+                    arg_count,
+                    span: index,
+                } if *function < self.functions.len() && span(*index) => (*arg_count, 1, None),
+                Instruction::Jump(target) if jump(*target) => {
+                    if !reach(&mut heights, *target, height) {
+                        return false;
+                    }
+                    continue;
+                }
+                Instruction::JumpUnlessNull(target) if jump(*target) => (1, 0, Some((*target, 0))),
+                Instruction::JumpUnlessTrue { target, span: index } if jump(*target) && span(*index) => {
+                    (1, 0, Some((*target, 1)))
+                }
+                //the result of AND and OR stays below the operand
+                Instruction::ShortCircuit {
+                    target, span: index, ..
+                } if jump(*target) && span(*index) && height > 1 => (1, 0, Some((*target, 1))),
+                Instruction::Fail(error) if *error < self.errors.len() => continue,
+                _ => return false,
+            };
+            if pops > height {
+                return false;
+            }
+            if let Some((target, jump_pops)) = jump_to {
+                if !reach(&mut heights, target, height - jump_pops) {
+                    return false;
+                }
+            }
+            if !reach(&mut heights, pc + 1, height - pops + pushes) {
+                return false;
+            }
+        }
+        heights[count].is_none_or(|height| height == 1)
+    }
+}
+
+//records the height of the stack before the instruction at the target, which has to match the
+//height of the other paths to it
+fn reach(heights: &mut [Option<usize>], target: usize, height: usize) -> bool {
+    match heights[target] {
+        Some(known) => known == height,
+        None => {
+            heights[target] = Some(height);
+            true
+        }
+    }
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn byte(&mut self, byte: u8) {
+        self.0.push(byte);
+    }
+
+    fn u64(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.byte(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.byte(value as u8);
+    }
+
+    fn usize(&mut self, value: usize) {
+        self.u64(value as u64);
+    }
+
+    fn i64(&mut self, value: i64) {
+        self.u64(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.byte(value as u8);
+    }
+
+    fn string(&mut self, value: &str) {
+        self.usize(value.len());
+        self.0.extend_from_slice(value.as_bytes());
+    }
+
+    fn span(&mut self, span: Span) {
+        for value in [
+            span.start,
+            span.end,
+            span.start_line,
+            span.start_column,
+            span.end_line,
+            span.end_column,
+        ] {
+            self.usize(value);
+        }
+    }
+
+    fn value_type(&mut self, value_type: ValueType) {
+        let tag = VALUE_TYPES
+            .iter()
+            .position(|known| *known == value_type)
+            .expect("all value types have a tag");
+        self.byte(tag as u8);
+    }
+
+    fn date(&mut self, date: NaiveDate) {
+        self.i64(date.num_days_from_ce().into());
+    }
+
+    fn literal(&mut self, value: &LiteralValue) {
+        match value {
+            LiteralValue::StringValue(value) => {
+                self.byte(0);
+                self.string(value);
+            }
+            LiteralValue::NumberValue(value) => {
+                self.byte(1);
+                self.0.extend_from_slice(&value.to_le_bytes());
+            }
+            LiteralValue::IntegerValue(value) => {
+                self.byte(2);
+                self.i64(*value);
+            }
+            LiteralValue::DecimalValue(value) => {
+                self.byte(3);
+                self.0.extend_from_slice(&value.mantissa().to_le_bytes());
+                self.u64(value.scale().into());
+            }
+            LiteralValue::BooleanValue(value) => {
+                self.byte(4);
+                self.bool(*value);
+            }
+            LiteralValue::DateValue(value) => {
+                self.byte(5);
+                self.date(*value);
+            }
+            LiteralValue::TimestampValue(value) => {
+                self.byte(6);
+                self.date(value.date());
+                self.u64(value.num_seconds_from_midnight().into());
+                self.u64(value.nanosecond().into());
+            }
+            LiteralValue::IntervalValue(value) => {
+                self.byte(7);
+                self.i64(value.months.into());
+                self.i64(value.days.into());
+                self.i64(value.microseconds);
+            }
+            LiteralValue::NullValue => self.byte(8),
+        }
+    }
+
+    //programs only hold the errors their compiler reports, without a source line
+    fn error(&mut self, error: &Error) {
+        match error.kind() {
+            ErrorKind::UnknownField { field_id } => {
+                self.byte(0);
+                self.string(field_id);
+            }
+            ErrorKind::UnknownFunction { function_name } => {
+                self.byte(1);
+                self.string(function_name);
+            }
+            ErrorKind::UnexpectedAggregate { function_name } => {
+                self.byte(2);
+                self.string(function_name);
+            }
+            ErrorKind::ArityMismatch {
+                function_name,
+                expected_min,
+                expected_max,
+                actual,
+            } => {
+                self.byte(3);
+                self.string(function_name);
+                self.usize(*expected_min);
+                match expected_max {
+                    Some(expected_max) => {
+                        self.bool(true);
+                        self.usize(*expected_max);
+                    }
+                    None => self.bool(false),
+                }
+                self.usize(*actual);
+            }
+            ErrorKind::UnsupportedModifier => self.byte(4),
+            kind => unreachable!("programs are not compiled with `{}` errors", kind),
+        }
+        self.span(error.span());
+    }
+
+    fn instruction(&mut self, instruction: &Instruction) {
+        match instruction {
+            Instruction::Push(value) => {
+                self.byte(0);
+                self.literal(value);
+            }
+            Instruction::Load { slot, span } => {
+                self.byte(1);
+                self.usize(*slot);
+                self.usize(*span);
+            }
+            Instruction::Operator { operator, spans } => {
+                self.byte(2);
+                let tag = OPERATORS
+                    .iter()
+                    .position(|known| known == operator)
+                    .expect("all operators have a tag");
+                self.byte(tag as u8);
+                self.usize(*spans);
+            }
+            Instruction::Not { span } => {
+                self.byte(3);
+                self.usize(*span);
+            }
+            Instruction::IsNull { negated } => {
+                self.byte(4);
+                self.bool(*negated);
+            }
+            Instruction::CheckArgument { param_type, span } => {
+                self.byte(5);
+                match param_type {
+                    ParamType::Any => self.byte(0),
+                    ParamType::Numeric => self.byte(1),
+                    ParamType::DateTime => self.byte(2),
+                    ParamType::Exact(value_type) => {
+                        self.byte(3);
+                        self.value_type(*value_type);
+                    }
+                }
+                self.usize(*span);
+            }
+            Instruction::Call {
+                function,
+                arg_count,
+                span,
+            } => {
+                self.byte(6);
+                self.usize(*function);
+                self.usize(*arg_count);
+                self.usize(*span);
+            }
+            Instruction::Jump(target) => {
+                self.byte(7);
+                self.usize(*target);
+            }
+            Instruction::JumpUnlessNull(target) => {
+                self.byte(8);
+                self.usize(*target);
+            }
+            Instruction::JumpUnlessTrue { target, span } => {
+                self.byte(9);
+                self.usize(*target);
+                self.usize(*span);
+            }
+            Instruction::ShortCircuit { value, target, span } => {
+                self.byte(10);
+                self.bool(*value);
+                self.usize(*target);
+                self.usize(*span);
+            }
+            Instruction::Fail(error) => {
+                self.byte(11);
+                self.usize(*error);
+            }
+        }
+    }
+}
+
+struct Reader<'b> {
+    bytes: &'b [u8],
+    offset: usize,
+}
+
+impl<'b> Reader<'b> {
+    fn malformed<T>(&self, start: usize) -> Result<T, DecodeError> {
+        Err(DecodeError::Malformed { offset: start })
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'b [u8], DecodeError> {
+        match self.bytes.get(self.offset..).and_then(|rest| rest.get(..count)) {
+            Some(bytes) => {
+                self.offset += count;
+                Ok(bytes)
+            }
+            None => self.malformed(self.bytes.len()),
+        }
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        Ok(self.take(N)?.try_into().expect("N bytes are taken"))
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u64(&mut self) -> Result<u64, DecodeError> {
+        let start = self.offset;
+        let mut value = 0_u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            let bits = u64::from(byte & 0x7f);
+            if bits << shift >> shift != bits {
+                break;
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        self.malformed(start)
+    }
+
+    fn usize(&mut self) -> Result<usize, DecodeError> {
+        let start = self.offset;
+        let value = self.u64()?;
+        usize::try_from(value).or_else(|_| self.malformed(start))
+    }
+
+    fn i64(&mut self) -> Result<i64, DecodeError> {
+        let value = self.u64()?;
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    fn i32(&mut self) -> Result<i32, DecodeError> {
+        let start = self.offset;
+        let value = self.i64()?;
+        i32::try_from(value).or_else(|_| self.malformed(start))
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        let start = self.offset;
+        let value = self.u64()?;
+        u32::try_from(value).or_else(|_| self.malformed(start))
+    }
+
+    fn bool(&mut self) -> Result<bool, DecodeError> {
+        let start = self.offset;
+        match self.byte()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => self.malformed(start),
+        }
+    }
+
+    fn list<T>(&mut self, mut item: impl FnMut(&mut Self) -> Result<T, DecodeError>) -> Result<Vec<T>, DecodeError> {
+        let count = self.usize()?;
+        //every item takes at least one byte, which bounds the memory allocated for a bad count
+        let mut items = Vec::with_capacity(count.min(self.bytes.len() - self.offset));
+        for _ in 0..count {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    fn string(&mut self) -> Result<String, DecodeError> {
+        let start = self.offset;
+        let length = self.usize()?;
+        let bytes = self.take(length)?;
+        String::from_utf8(bytes.to_vec()).or_else(|_| self.malformed(start))
+    }
+
+    fn span(&mut self) -> Result<Span, DecodeError> {
+        Ok(Span {
+            start: self.usize()?,
+            end: self.usize()?,
+            start_line: self.usize()?,
+            start_column: self.usize()?,
+            end_line: self.usize()?,
+            end_column: self.usize()?,
+        })
+    }
+
+    fn value_type(&mut self) -> Result<ValueType, DecodeError> {
+        let start = self.offset;
+        let tag = self.byte()?;
+        VALUE_TYPES
+            .get(usize::from(tag))
+            .copied()
+            .map_or_else(|| self.malformed(start), Ok)
+    }
+
+    fn date(&mut self) -> Result<NaiveDate, DecodeError> {
+        let start = self.offset;
+        let days = self.i32()?;
+        NaiveDate::from_num_days_from_ce_opt(days).map_or_else(|| self.malformed(start), Ok)
+    }
+
+    fn literal(&mut self) -> Result<LiteralValue, DecodeError> {
+        let start = self.offset;
+        Ok(match self.byte()? {
+            0 => LiteralValue::StringValue(self.string()?),
+            1 => LiteralValue::NumberValue(f64::from_le_bytes(self.array()?)),
+            2 => LiteralValue::IntegerValue(self.i64()?),
+            3 => {
+                let mantissa = i128::from_le_bytes(self.array()?);
+                let scale = self.u32()?;
+                match Decimal::try_from_i128_with_scale(mantissa, scale) {
+                    Ok(value) => LiteralValue::DecimalValue(value),
+                    Err(_) => return self.malformed(start),
+                }
+            }
+            4 => LiteralValue::BooleanValue(self.bool()?),
+            5 => LiteralValue::DateValue(self.date()?),
+            6 => {
+                let date = self.date()?;
+                let seconds = self.u32()?;
+                let nanoseconds = self.u32()?;
+                match NaiveTime::from_num_seconds_from_midnight_opt(seconds, nanoseconds) {
+                    Some(time) => LiteralValue::TimestampValue(NaiveDateTime::new(date, time)),
+                    None => return self.malformed(start),
+                }
+            }
+            7 => LiteralValue::IntervalValue(Interval {
+                months: self.i32()?,
+                days: self.i32()?,
+                microseconds: self.i64()?,
+            }),
+            8 => LiteralValue::NullValue,
+            _ => return self.malformed(start),
+        })
+    }
+
+    fn error(&mut self) -> Result<Error, DecodeError> {
+        let start = self.offset;
+        let kind = match self.byte()? {
+            0 => ErrorKind::UnknownField {
+                field_id: self.string()?,
+            },
+            1 => ErrorKind::UnknownFunction {
+                function_name: self.string()?,
+            },
+            2 => ErrorKind::UnexpectedAggregate {
+                function_name: self.string()?,
+            },
+            3 => ErrorKind::ArityMismatch {
+                function_name: self.string()?,
+                expected_min: self.usize()?,
+                expected_max: if self.bool()? { Some(self.usize()?) } else { None },
+                actual: self.usize()?,
+            },
+            4 => ErrorKind::UnsupportedModifier,
+            _ => return self.malformed(start),
+        };
+        Ok(Error::new(kind, self.span()?))
+    }
+
+    fn instruction(&mut self) -> Result<Instruction, DecodeError> {
+        let start = self.offset;
+        Ok(match self.byte()? {
+            0 => Instruction::Push(self.literal()?),
+            1 => Instruction::Load {
+                slot: self.usize()?,
+                span: self.usize()?,
+            },
+            2 => {
+                let tag_offset = self.offset;
+                let Some(operator) = OPERATORS.get(usize::from(self.byte()?)).copied() else {
+                    return self.malformed(tag_offset);
+                };
+                Instruction::Operator {
+                    operator,
+                    spans: self.usize()?,
+                }
+            }
+            3 => Instruction::Not { span: self.usize()? },
+            4 => Instruction::IsNull { negated: self.bool()? },
+            5 => {
+                let tag_offset = self.offset;
+                let param_type = match self.byte()? {
+                    0 => ParamType::Any,
+                    1 => ParamType::Numeric,
+                    2 => ParamType::DateTime,
+                    3 => ParamType::Exact(self.value_type()?),
+                    _ => return self.malformed(tag_offset),
+                };
+                Instruction::CheckArgument {
+                    param_type,
+                    span: self.usize()?,
+                }
+            }
+            6 => Instruction::Call {
+                function: self.usize()?,
+                arg_count: self.usize()?,
+                span: self.usize()?,
+            },
+            7 => Instruction::Jump(self.usize()?),
+            8 => Instruction::JumpUnlessNull(self.usize()?),
+            9 => Instruction::JumpUnlessTrue {
+                target: self.usize()?,
+                span: self.usize()?,
+            },
+            10 => Instruction::ShortCircuit {
+                value: self.bool()?,
+                target: self.usize()?,
+                span: self.usize()?,
+            },
+            11 => Instruction::Fail(self.usize()?),
+            _ => return self.malformed(start),
+        })
+    }
+}
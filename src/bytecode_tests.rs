@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use crate::ast::{field_ref, func, if_expr, lit_int, lit_null, LiteralValue};
+use crate::bytecode::{DecodeError, Instruction, Operator, Program};
+use crate::{evaluate, parse, ErrorKind, Evaluator, FunctionRegistry, ParamType, Signature};
+
+const COLUMNS: [&str; 4] = ["qty", "price", "region", "paid"];
+
+#[test]
+fn test_program_instructions() {
+    let program = Program::compile(&parse("qty > 1 or not paid").unwrap(), &COLUMNS);
+    assert_eq!(
+        program.instructions(),
+        &[
+            Instruction::Push(LiteralValue::BooleanValue(false)),
+            Instruction::Load { slot: 0, span: 5 },
+            Instruction::Push(LiteralValue::IntegerValue(1)),
+            Instruction::Operator {
+                operator: Operator::Greater,
+                spans: 2,
+            },
+            Instruction::ShortCircuit {
+                value: true,
+                target: 8,
+                span: 0,
+            },
+            Instruction::Load { slot: 3, span: 7 },
+            Instruction::Not { span: 6 },
+            Instruction::ShortCircuit {
+                value: true,
+                target: 8,
+                span: 1,
+            },
+        ]
+    );
+
+    let program = Program::compile(&parse("round(price, 1)").unwrap(), &COLUMNS);
+    assert_eq!(program.functions(), ["round"]);
+    assert!(matches!(
+        program.instructions()[1],
+        Instruction::CheckArgument {
+            param_type: ParamType::Numeric,
+            ..
+        }
+    ));
+
+    //unknown fields fail when they are evaluated
+    let program = Program::compile(&parse("if paid then 1 else missing").unwrap(), &COLUMNS);
+    assert!(matches!(program.instructions()[4], Instruction::Fail(0)));
+    let error = program.evaluate(&[]).unwrap_err();
+    assert_eq!(
+        error.kind(),
+        &ErrorKind::UnknownField {
+            field_id: "paid".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_program_with_custom_functions() {
+    let mut functions = FunctionRegistry::with_builtins();
+    functions.register(
+        "double",
+        Signature::new(vec![ParamType::Numeric], ParamType::Numeric),
+        |args| Ok(LiteralValue::NumberValue(f64::try_from(args[0].clone())? * 2.0)),
+    );
+    let evaluator = Evaluator::new(functions);
+    let expression = parse("double(qty) + 1").unwrap();
+    let program = evaluator.compile_program(&expression, &["qty"]);
+    let row = [LiteralValue::IntegerValue(4)];
+    assert_eq!(
+        evaluator.evaluate_program(&program, &row).unwrap(),
+        LiteralValue::NumberValue(9.0)
+    );
+    let error = program.evaluate(&row).unwrap_err();
+    assert_eq!(
+        error.kind(),
+        &ErrorKind::UnknownFunction {
+            function_name: "double".to_string()
+        }
+    );
+    let error = Program::compile(&expression, &["qty"]).evaluate(&row).unwrap_err();
+    assert_eq!(
+        error.kind(),
+        &ErrorKind::UnknownFunction {
+            function_name: "double".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_program_round_trip() {
+    let expressions = [
+        r#"if paid and qty >= 2 then upper(region) else "none""#,
+        "qty > 1 or not paid or price is null or region is not null",
+        "round(price * 1.25 - 0.5e1, 2) / -3 != 7",
+        r#"date_trunc("month", #2024-02-29 10:30:15.25#) + interval '1 year 2 days 3 hours' < #2024-03-01#"#,
+        "case when qty = 1 then null when qty <= 2 then true end",
+        r#"coalesce(region, ifnull(null, "x")) = iif(paid, "a", "b", "c")"#,
+        //the errors of the program are encoded as well
+        "iif(qty > 1, missing, unknown_function(qty)) + sum(qty) + round() + sum(qty) [where paid]",
+    ];
+    let rows = [
+        [
+            LiteralValue::IntegerValue(3),
+            LiteralValue::NumberValue(2.5),
+            LiteralValue::StringValue("EU".to_string()),
+            LiteralValue::BooleanValue(true),
+        ],
+        [
+            LiteralValue::IntegerValue(1),
+            LiteralValue::NullValue,
+            LiteralValue::NullValue,
+            LiteralValue::BooleanValue(false),
+        ],
+    ];
+    for expression in expressions {
+        let program = Program::compile(&parse(expression).unwrap(), &COLUMNS);
+        let decoded = Program::from_bytes(&program.to_bytes()).unwrap();
+        assert_eq!(decoded, program, "{}", expression);
+        for row in &rows {
+            assert_eq!(decoded.evaluate(row), program.evaluate(row), "{}", expression);
+        }
+    }
+}
+
+#[test]
+fn test_decoding_invalid_programs() {
+    let program = Program::compile(&parse("if paid then price else -1").unwrap(), &COLUMNS);
+    let bytes = program.to_bytes();
+    for length in 0..bytes.len() {
+        assert!(Program::from_bytes(&bytes[..length]).is_err(), "{}", length);
+    }
+    let mut version = bytes.clone();
+    version[4] = 0;
+    assert_eq!(Program::from_bytes(&version), Err(DecodeError::Malformed { offset: 4 }));
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        Program::from_bytes(&trailing),
+        Err(DecodeError::Malformed { offset: bytes.len() })
+    );
+
+    //programs that would make evaluation panic or loop are rejected
+    let invalid = [
+        //the last instruction, `Push(-1)`, is replaced by a jump to the end that pushes nothing
+        vec![7, 5],
+        //a jump back to the start
+        vec![7, 0],
+        //a load from a column the program does not have
+        vec![1, 4, 0],
+    ];
+    let last = bytes.len() - 3;
+    assert_eq!(bytes[last..], [0, 2, 1]);
+    for instruction in invalid {
+        let mut bytes = bytes[..last].to_vec();
+        bytes.extend(instruction);
+        assert_eq!(Program::from_bytes(&bytes), Err(DecodeError::InvalidProgram));
+    }
+}
+
+#[test]
+fn test_deeply_nested_program() {
+    //1 + (1 + (1 + ... + qty)), too deep to evaluate recursively
+    let depth = 100_000;
+    let mut expression = field_ref("qty");
+    for _ in 0..depth {
+        expression = func("+", vec![lit_int(1), expression]);
+    }
+    let program = Program::compile(&expression, &["qty"]);
+    assert_eq!(program.instructions().len(), 2 * depth + 1);
+    assert_eq!(
+        program.evaluate(&[LiteralValue::IntegerValue(5)]).unwrap(),
+        LiteralValue::IntegerValue(depth as i64 + 5)
+    );
+
+    //the evaluator of trees runs what is nested too deep for its recursion as a program
+    let row = HashMap::from([("qty".to_string(), LiteralValue::IntegerValue(5))]);
+    assert_eq!(
+        evaluate(&expression, &row).unwrap(),
+        LiteralValue::IntegerValue(depth as i64 + 5)
+    );
+    let error = Evaluator::default().evaluate(&expression, &HashMap::new()).unwrap_err();
+    assert_eq!(
+        error.kind(),
+        &ErrorKind::UnknownField {
+            field_id: "qty".to_string()
+        }
+    );
+    let nested_ifs = (0..depth).fold(field_ref("qty"), |expression, _| {
+        if_expr(func(">", vec![field_ref("qty"), lit_int(1)]), expression, lit_null())
+    });
+    assert_eq!(evaluate(&nested_ifs, &row).unwrap(), LiteralValue::IntegerValue(5));
+}
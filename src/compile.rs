@@ -0,0 +1,248 @@
+use std::fmt;
+
+use crate::aggregate;
+use crate::ast::{Expression, LiteralValue, Span};
+use crate::error::{Error, ErrorKind};
+use crate::eval::{
+    arity_mismatch, call_with_arguments, check_argument, condition_value, eval_binary_operator, eval_conditional,
+};
+use crate::functions::FunctionRegistry;
+
+//a compiled sub-expression, evaluated on the column values of a row
+type Node<'a> = Box<dyn Fn(&[LiteralValue]) -> Result<LiteralValue, Error> + Send + Sync + 'a>;
+
+/// An expression compiled to nested closures, with its field references resolved to the index
+/// of their column, so rows are evaluated without walking the tree or looking fields up by name.
+///
+/// The results are the same as the ones of [`evaluate`](crate::evaluate), including the errors:
+/// unknown fields and functions are reported when evaluating a row that needs them.
+pub struct CompiledExpression<'a> {
+    node: Node<'a>,
+}
+
+impl CompiledExpression<'_> {
+    /// Evaluates the expression on a row with the values of the columns it was compiled for, in
+    /// the same order. Fields of columns missing from the row are unknown.
+    pub fn evaluate(&self, row: &[LiteralValue]) -> Result<LiteralValue, Error> {
+        (self.node)(row)
+    }
+}
+
+impl fmt::Debug for CompiledExpression<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompiledExpression").finish_non_exhaustive()
+    }
+}
+
+/// Compiles an expression with the built-in functions for rows with the values of `columns`.
+///
+/// ```
+/// use analytical_expression_parser::ast::LiteralValue;
+/// use analytical_expression_parser::{compile, parse};
+///
+/// let expression = parse("if quantity > 10 then price * 0.9 else price").unwrap();
+/// let compiled = compile(&expression, &["price", "quantity"]);
+///
+/// let rows = [
+///     [LiteralValue::NumberValue(100.0), LiteralValue::IntegerValue(20)],
+///     [LiteralValue::NumberValue(100.0), LiteralValue::IntegerValue(5)],
+/// ];
+/// let results: Vec<_> = rows.iter().map(|row| compiled.evaluate(row).unwrap()).collect();
+/// assert_eq!(results, [LiteralValue::NumberValue(90.0), LiteralValue::NumberValue(100.0)]);
+/// ```
+pub fn compile<'a, S: AsRef<str>>(expression: &'a Expression, columns: &[S]) -> CompiledExpression<'a> {
+    compile_with(expression, columns, FunctionRegistry::builtins())
+}
+
+pub(crate) fn compile_with<'a, S: AsRef<str>>(
+    expression: &'a Expression,
+    columns: &[S],
+    functions: &'a FunctionRegistry,
+) -> CompiledExpression<'a> {
+    let compiler = Compiler {
+        columns: columns.iter().map(AsRef::as_ref).collect(),
+        functions,
+    };
+    CompiledExpression {
+        node: compiler.compile(expression),
+    }
+}
+
+struct Compiler<'a, 'c> {
+    columns: Vec<&'c str>,
+    functions: &'a FunctionRegistry,
+}
+
+impl<'a> Compiler<'a, '_> {
+    //the closures do what `eval::eval_node` does for the same kind of node
+    fn compile(&self, expression: &'a Expression) -> Node<'a> {
+        match expression {
+            Expression::Literal { value, .. } => Box::new(move |_| Ok(value.clone())),
+            Expression::FieldReference { field_id, span } => {
+                let unknown_field = move || {
+                    Error::new(
+                        ErrorKind::UnknownField {
+                            field_id: field_id.clone(),
+                        },
+                        *span,
+                    )
+                };
+                match self.columns.iter().position(|column| column == field_id) {
+                    Some(index) => Box::new(move |row| row.get(index).cloned().ok_or_else(unknown_field)),
+                    None => Box::new(move |_| Err(unknown_field())),
+                }
+            }
+            Expression::Function {
+                function_name,
+                params,
+                span,
+            } => self.function(function_name, params, *span),
+            Expression::IfExpression {
+                condition,
+                result,
+                else_result,
+                ..
+            } => {
+                let condition_span = condition.span();
+                let (condition, result, else_result) =
+                    (self.compile(condition), self.compile(result), self.compile(else_result));
+                Box::new(move |row| {
+                    if condition_value(&condition(row)?, condition_span)? == Some(true) {
+                        result(row)
+                    } else {
+                        else_result(row)
+                    }
+                })
+            }
+            Expression::CaseExpression { cases, else_result, .. } => {
+                let cases: Vec<(Node, Span, Node)> = cases
+                    .iter()
+                    .map(|case| {
+                        (
+                            self.compile(&case.condition),
+                            case.condition.span(),
+                            self.compile(&case.result),
+                        )
+                    })
+                    .collect();
+                let else_result = else_result.as_ref().map(|else_result| self.compile(else_result));
+                Box::new(move |row| {
+                    for (condition, condition_span, result) in &cases {
+                        if condition_value(&condition(row)?, *condition_span)? == Some(true) {
+                            return result(row);
+                        }
+                    }
+                    match &else_result {
+                        Some(else_result) => else_result(row),
+                        None => Ok(LiteralValue::NullValue),
+                    }
+                })
+            }
+            Expression::ModifierExpression { span, .. } => fail(Error::new(ErrorKind::UnsupportedModifier, *span)),
+        }
+    }
+
+    fn function(&self, function_name: &'a str, params: &'a [Expression], span: Span) -> Node<'a> {
+        if function_name.eq_ignore_ascii_case("and") || function_name.eq_ignore_ascii_case("or") {
+            let short_circuit_value = function_name.eq_ignore_ascii_case("or");
+            let operands: Vec<(Node, Span)> = params.iter().map(|param| (self.compile(param), param.span())).collect();
+            return Box::new(move |row| {
+                let mut null_operand = false;
+                for (operand, operand_span) in &operands {
+                    match condition_value(&operand(row)?, *operand_span)? {
+                        Some(value) if value == short_circuit_value => {
+                            return Ok(LiteralValue::BooleanValue(short_circuit_value));
+                        }
+                        Some(_) => {}
+                        None => null_operand = true,
+                    }
+                }
+                Ok(if null_operand {
+                    LiteralValue::NullValue
+                } else {
+                    LiteralValue::BooleanValue(!short_circuit_value)
+                })
+            });
+        }
+
+        match function_name {
+            "+" | "-" | "*" | "/" | "=" | "!=" | "<" | "<=" | ">" | ">=" => match params {
+                [left, right] => {
+                    let operand_spans = [left.span(), right.span()];
+                    let (left, right) = (self.compile(left), self.compile(right));
+                    Box::new(move |row| {
+                        eval_binary_operator(function_name, &left(row)?, &right(row)?, operand_spans, span)
+                    })
+                }
+                _ => fail(arity_mismatch(function_name, 2, params.len(), span)),
+            },
+            not if not.eq_ignore_ascii_case("not") => match params {
+                [operand] => {
+                    let operand_span = operand.span();
+                    let operand = self.compile(operand);
+                    Box::new(move |row| {
+                        Ok(match condition_value(&operand(row)?, operand_span)? {
+                            Some(value) => LiteralValue::BooleanValue(!value),
+                            None => LiteralValue::NullValue,
+                        })
+                    })
+                }
+                _ => fail(arity_mismatch(function_name, 1, params.len(), span)),
+            },
+            "is null" | "is not null" => match params {
+                [operand] => {
+                    let operand = self.compile(operand);
+                    let null_result = function_name == "is null";
+                    Box::new(move |row| {
+                        Ok(LiteralValue::BooleanValue(
+                            (operand(row)? == LiteralValue::NullValue) == null_result,
+                        ))
+                    })
+                }
+                _ => fail(arity_mismatch(function_name, 1, params.len(), span)),
+            },
+            _ => {
+                let Some(function) = self.functions.get(function_name) else {
+                    let kind = if aggregate::is_aggregate(function_name) {
+                        ErrorKind::UnexpectedAggregate {
+                            function_name: function_name.to_string(),
+                        }
+                    } else {
+                        ErrorKind::UnknownFunction {
+                            function_name: function_name.to_string(),
+                        }
+                    };
+                    return fail(Error::new(kind, span));
+                };
+                let signature = function.signature();
+                if let Err(error) = signature.check_arity(function_name, params.len(), span) {
+                    return fail(error);
+                }
+                let args: Vec<Node> = params.iter().map(|param| self.compile(param)).collect();
+                if let Some(conditional) = function.conditional() {
+                    return Box::new(move |row| {
+                        eval_conditional(conditional, args.len(), |index| {
+                            let value = args[index](row)?;
+                            check_argument(signature, index, &value, params[index].span())?;
+                            Ok(value)
+                        })
+                    });
+                }
+                Box::new(move |row| {
+                    let mut values = Vec::with_capacity(args.len());
+                    for (index, (arg, param)) in args.iter().zip(params).enumerate() {
+                        let value = arg(row)?;
+                        check_argument(signature, index, &value, param.span())?;
+                        values.push(value);
+                    }
+                    call_with_arguments(function, &values, span)
+                })
+            }
+        }
+    }
+}
+
+//a node failing on every row, for errors that the evaluator reports when it reaches the node
+fn fail<'a>(error: Error) -> Node<'a> {
+    Box::new(move |_| Err(error.clone()))
+}
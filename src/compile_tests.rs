@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::thread;
+
+use crate::ast::LiteralValue;
+use crate::bytecode::Program;
+use crate::{
+    compile, evaluate, parse, Decimal, ErrorKind, Evaluator, FunctionRegistry, NaiveDate, ParamType, Signature,
+};
+
+const COLUMNS: [&str; 6] = ["qty", "price", "region", "paid", "orders.date", "discount"];
+
+//rows with the values of `COLUMNS`, including NULLs and values of unexpected types
+fn rows() -> Vec<Vec<LiteralValue>> {
+    let date = |day| LiteralValue::DateValue(NaiveDate::from_ymd_opt(2024, 2, day).unwrap());
+    vec![
+        vec![
+            LiteralValue::IntegerValue(3),
+            LiteralValue::NumberValue(9.5),
+            LiteralValue::StringValue("EU".to_string()),
+            LiteralValue::BooleanValue(true),
+            date(28),
+            LiteralValue::DecimalValue(Decimal::new(15, 2)),
+        ],
+        vec![
+            LiteralValue::IntegerValue(0),
+            LiteralValue::NullValue,
+            LiteralValue::StringValue("US".to_string()),
+            LiteralValue::BooleanValue(false),
+            date(1),
+            LiteralValue::NullValue,
+        ],
+        vec![
+            LiteralValue::NullValue,
+            LiteralValue::NumberValue(-2.25),
+            LiteralValue::NullValue,
+            LiteralValue::NullValue,
+            LiteralValue::NullValue,
+            LiteralValue::IntegerValue(1),
+        ],
+        vec![
+            LiteralValue::IntegerValue(i64::MAX),
+            LiteralValue::StringValue("12".to_string()),
+            LiteralValue::StringValue("EU".to_string()),
+            LiteralValue::IntegerValue(1),
+            date(29),
+            LiteralValue::DecimalValue(Decimal::new(5, 1)),
+        ],
+    ]
+}
+
+#[test]
+fn test_compiled_results_match_evaluation() {
+    let cases = [
+        "qty + price * 2",
+        "qty * 2 - discount",
+        "price / qty",
+        "qty / 4 + 1e3",
+        r#"region = "EU" and qty > 1 or not paid"#,
+        "paid and qty > 1 and price < 10",
+        "paid or qty = 0 or price > 0",
+        "qty is null or price is not null",
+        "if paid then price else discount",
+        "if paid then price else -1",
+        r#"case when qty > 2 then "many" when qty > 0 then "some" end"#,
+        r#"case when region = "EU" then 1 when region = "US" then 2 else 0 end"#,
+        r#"concat(upper(region), "-", qty)"#,
+        "round(price * qty, 1) + abs(discount)",
+        "coalesce(price, discount, 0)",
+        "iif(qty = 0, 0, price / qty)",
+        "iif(paid, qty, missing)",
+        "iif(qty > 1, 1, unknown_function(qty), -1)",
+        "coalesce(price, 1 / 0)",
+        "ifnull(region, upper(qty))",
+        "orders.date + 1",
+        "datediff(\"day\", orders.date, date '2024-03-01')",
+        "orders.date > date '2024-02-15'",
+        "missing + 1",
+        "if qty > 100 then missing else 0",
+        "unknown_function(qty)",
+        "sum(qty)",
+        "sum(qty) [group by region]",
+        "round(qty, 1, 2)",
+        "upper(qty)",
+        "qty + region",
+    ];
+    let rows = rows();
+    for case in cases {
+        let expression = parse(case).unwrap();
+        let compiled = compile(&expression, &COLUMNS);
+        let program = Program::compile(&expression, &COLUMNS);
+        assert_eq!(Program::from_bytes(&program.to_bytes()).as_ref(), Ok(&program), "{}", case);
+        for row in &rows {
+            let context: HashMap<String, LiteralValue> = COLUMNS
+                .iter()
+                .map(|column| column.to_string())
+                .zip(row.iter().cloned())
+                .collect();
+            let expected = evaluate(&expression, &context);
+            assert_eq!(compiled.evaluate(row), expected, "{} on {:?}", case, row);
+            assert_eq!(program.evaluate(row), expected, "program of {} on {:?}", case, row);
+        }
+    }
+}
+
+#[test]
+fn test_compiled_fields_and_functions() {
+    let expression = parse("qty * price").unwrap();
+    let compiled = compile(&expression, &["price".to_string(), "qty".to_string()]);
+    let row = [LiteralValue::NumberValue(2.5), LiteralValue::IntegerValue(4)];
+    assert_eq!(compiled.evaluate(&row).unwrap(), LiteralValue::NumberValue(10.0));
+
+    //columns missing from a row are unknown fields
+    let error = compiled.evaluate(&row[..1]).unwrap_err();
+    assert_eq!(
+        error.kind(),
+        &ErrorKind::UnknownField {
+            field_id: "qty".to_string()
+        }
+    );
+
+    let mut functions = FunctionRegistry::with_builtins();
+    functions.register(
+        "double",
+        Signature::new(vec![ParamType::Numeric], ParamType::Numeric),
+        |args| Ok(LiteralValue::NumberValue(f64::try_from(args[0].clone())? * 2.0)),
+    );
+    let evaluator = Evaluator::new(functions);
+    let expression = parse("double(qty) + 1").unwrap();
+    let compiled = evaluator.compile(&expression, &["qty"]);
+    assert_eq!(
+        compiled.evaluate(&[LiteralValue::IntegerValue(4)]).unwrap(),
+        LiteralValue::NumberValue(9.0)
+    );
+    assert!(compile(&expression, &["qty"])
+        .evaluate(&[LiteralValue::IntegerValue(4)])
+        .is_err());
+
+    //compiled expressions can be shared between threads
+    let rows: Vec<Vec<LiteralValue>> = (0..4).map(|qty| vec![LiteralValue::IntegerValue(qty)]).collect();
+    let results: Vec<LiteralValue> = thread::scope(|scope| {
+        let handles: Vec<_> = rows
+            .iter()
+            .map(|row| scope.spawn(|| compiled.evaluate(row).unwrap()))
+            .collect();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    });
+    let expected: Vec<LiteralValue> = [1.0, 3.0, 5.0, 7.0]
+        .into_iter()
+        .map(LiteralValue::NumberValue)
+        .collect();
+    assert_eq!(results, expected);
+}
@@ -0,0 +1,358 @@
+use std::cmp::Ordering;
+
+use chrono::{Datelike, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike};
+use pest::iterators::Pair;
+
+use crate::ast::{Interval, LiteralValue, Span, ValueType};
+use crate::error::{Error, ErrorKind, ValueTypeError};
+use crate::functions::{FunctionRegistry, ParamType, Signature};
+use crate::Rule;
+
+const MICROSECONDS_PER_DAY: i64 = 86_400_000_000;
+
+/// Unit of interval literals and of the date functions, e.g. the `"month"` of `datepart("month", order_date)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatePart {
+    Year,
+    Quarter,
+    Month,
+    Week,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+impl DatePart {
+    //accepts singular and plural names in any case
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        let part = match name.strip_suffix('s').unwrap_or(&name) {
+            "year" => DatePart::Year,
+            "quarter" => DatePart::Quarter,
+            "month" => DatePart::Month,
+            "week" => DatePart::Week,
+            "day" => DatePart::Day,
+            "hour" => DatePart::Hour,
+            "minute" => DatePart::Minute,
+            "second" => DatePart::Second,
+            _ => return None,
+        };
+        Some(part)
+    }
+
+    //interval of `amount` units, None if it overflows
+    fn interval(self, amount: i64) -> Option<Interval> {
+        let months = |factor: i64| {
+            let months = i32::try_from(amount.checked_mul(factor)?).ok()?;
+            Some(Interval::new(months, 0, 0))
+        };
+        let days = |factor: i64| {
+            let days = i32::try_from(amount.checked_mul(factor)?).ok()?;
+            Some(Interval::new(0, days, 0))
+        };
+        let microseconds = |factor: i64| Some(Interval::new(0, 0, amount.checked_mul(factor)?));
+        match self {
+            DatePart::Year => months(12),
+            DatePart::Quarter => months(3),
+            DatePart::Month => months(1),
+            DatePart::Week => days(7),
+            DatePart::Day => days(1),
+            DatePart::Hour => microseconds(3_600_000_000),
+            DatePart::Minute => microseconds(60_000_000),
+            DatePart::Second => microseconds(1_000_000),
+        }
+    }
+}
+
+//convert a date, timestamp or interval literal, failing if the date does not exist
+//or the interval overflows, e.g. `#2024-02-30#`
+pub(crate) fn convert_temporal_literal(pair: Pair<Rule>) -> Result<LiteralValue, Error> {
+    let value_type = match pair.as_rule() {
+        Rule::date_literal => ValueType::Date,
+        Rule::timestamp_literal => ValueType::Timestamp,
+        _ => ValueType::Interval,
+    };
+    let (span, source) = (Span::from(pair.as_span()), pair.get_input());
+    temporal_value(pair).ok_or_else(|| Error::new(ErrorKind::InvalidLiteral { value_type }, span).with_source(source))
+}
+
+fn temporal_value(pair: Pair<Rule>) -> Option<LiteralValue> {
+    match pair.as_rule() {
+        Rule::date_literal => {
+            let text = pair.into_inner().next()?.as_str();
+            NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()
+                .map(LiteralValue::DateValue)
+        }
+        Rule::timestamp_literal => {
+            let text = pair.into_inner().next()?.as_str().replacen('T', " ", 1);
+            ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%d %H:%M"]
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(&text, format).ok())
+                .map(LiteralValue::TimestampValue)
+        }
+        Rule::interval_literal => pair
+            .into_inner()
+            .try_fold(Interval::default(), |interval, part| {
+                let mut part = part.into_inner();
+                let amount = part.next()?.as_str().parse::<i64>().ok()?;
+                let unit = DatePart::parse(part.next()?.as_str())?;
+                add_intervals(interval, unit.interval(amount)?)
+            })
+            .map(LiteralValue::IntervalValue),
+        _ => None,
+    }
+}
+
+fn add_intervals(left: Interval, right: Interval) -> Option<Interval> {
+    Some(Interval::new(
+        left.months.checked_add(right.months)?,
+        left.days.checked_add(right.days)?,
+        left.microseconds.checked_add(right.microseconds)?,
+    ))
+}
+
+fn multiply_interval(interval: Interval, factor: i64) -> Option<Interval> {
+    let factor32 = i32::try_from(factor).ok()?;
+    Some(Interval::new(
+        interval.months.checked_mul(factor32)?,
+        interval.days.checked_mul(factor32)?,
+        interval.microseconds.checked_mul(factor)?,
+    ))
+}
+
+fn to_timestamp(value: &LiteralValue) -> Option<NaiveDateTime> {
+    match value {
+        LiteralValue::DateValue(date) => Some(date.and_time(NaiveTime::MIN)),
+        LiteralValue::TimestampValue(timestamp) => Some(*timestamp),
+        _ => None,
+    }
+}
+
+fn shift_timestamp(timestamp: NaiveDateTime, interval: Interval) -> Option<NaiveDateTime> {
+    let timestamp = if interval.months >= 0 {
+        timestamp.checked_add_months(Months::new(interval.months.unsigned_abs()))?
+    } else {
+        timestamp.checked_sub_months(Months::new(interval.months.unsigned_abs()))?
+    };
+    timestamp
+        .checked_add_signed(TimeDelta::try_days(i64::from(interval.days))?)?
+        .checked_add_signed(TimeDelta::microseconds(interval.microseconds))
+}
+
+//dates stay dates unless the interval has a time of day part, None on overflow
+fn shift_value(value: &LiteralValue, interval: Interval) -> Option<LiteralValue> {
+    let shifted = shift_timestamp(to_timestamp(value)?, interval)?;
+    match value {
+        LiteralValue::DateValue(_) if interval.microseconds == 0 => Some(LiteralValue::DateValue(shifted.date())),
+        _ => Some(LiteralValue::TimestampValue(shifted)),
+    }
+}
+
+fn interval_between(end: NaiveDateTime, start: NaiveDateTime) -> Option<Interval> {
+    let difference = end - start;
+    let days = difference.num_days();
+    let microseconds = (difference - TimeDelta::try_days(days)?).num_microseconds()?;
+    Some(Interval::new(i32::try_from(days).ok()?, 0, microseconds))
+}
+
+fn is_temporal(value: &LiteralValue) -> bool {
+    matches!(
+        value,
+        LiteralValue::DateValue(_) | LiteralValue::TimestampValue(_) | LiteralValue::IntervalValue(_)
+    )
+}
+
+/// Evaluates `+`, `-`, `*` and `/` when one of the operands is a date, timestamp or interval,
+/// returns `None` for other operands. Neither operand may be NULL.
+pub(crate) fn eval_temporal_arithmetic(
+    function_name: &str,
+    left: &LiteralValue,
+    right: &LiteralValue,
+    operand_spans: [Span; 2],
+    span: Span,
+) -> Option<Result<LiteralValue, Error>> {
+    use LiteralValue::{DateValue, IntegerValue, IntervalValue, TimestampValue};
+
+    if !is_temporal(left) && !is_temporal(right) {
+        return None;
+    }
+    let result = match (function_name, left, right) {
+        ("+", DateValue(_) | TimestampValue(_), IntervalValue(interval)) => shift_value(left, *interval),
+        ("+", IntervalValue(interval), DateValue(_) | TimestampValue(_)) => shift_value(right, *interval),
+        ("-", DateValue(_) | TimestampValue(_), IntervalValue(interval)) => {
+            multiply_interval(*interval, -1).and_then(|interval| shift_value(left, interval))
+        }
+        //whole days can be added to dates directly
+        ("+", DateValue(_), IntegerValue(days)) => DatePart::Day
+            .interval(*days)
+            .and_then(|interval| shift_value(left, interval)),
+        ("+", IntegerValue(days), DateValue(_)) => DatePart::Day
+            .interval(*days)
+            .and_then(|interval| shift_value(right, interval)),
+        ("-", DateValue(_), IntegerValue(days)) => days
+            .checked_neg()
+            .and_then(|days| DatePart::Day.interval(days))
+            .and_then(|interval| shift_value(left, interval)),
+        ("-", DateValue(end), DateValue(start)) => Some(IntegerValue((*end - *start).num_days())),
+        ("-", DateValue(_) | TimestampValue(_), DateValue(_) | TimestampValue(_)) => {
+            interval_between(to_timestamp(left)?, to_timestamp(right)?).map(IntervalValue)
+        }
+        ("+", IntervalValue(left), IntervalValue(right)) => add_intervals(*left, *right).map(IntervalValue),
+        ("-", IntervalValue(left), IntervalValue(right)) => multiply_interval(*right, -1)
+            .and_then(|right| add_intervals(*left, right))
+            .map(IntervalValue),
+        ("*", IntervalValue(interval), IntegerValue(factor)) | ("*", IntegerValue(factor), IntervalValue(interval)) => {
+            multiply_interval(*interval, *factor).map(IntervalValue)
+        }
+        //dates are shifted by intervals, everything else expects numbers
+        ("+" | "-", DateValue(_) | TimestampValue(_), _) => {
+            return Some(Err(ValueTypeError::new(ValueType::Interval, right).at(operand_spans[1])));
+        }
+        _ if is_temporal(left) => {
+            return Some(Err(ValueTypeError::new(ValueType::Number, left).at(operand_spans[0])));
+        }
+        _ => return Some(Err(ValueTypeError::new(ValueType::Number, right).at(operand_spans[1]))),
+    };
+    Some(result.ok_or_else(|| Error::new(ErrorKind::NumericOverflow, span)))
+}
+
+/// Order of two dates, timestamps or intervals, `None` if the values are not both of these kinds.
+/// Dates are compared with timestamps as midnight of that day, intervals by their approximate
+/// length with 30 day months.
+pub(crate) fn compare_temporal(left: &LiteralValue, right: &LiteralValue) -> Option<Ordering> {
+    match (left, right) {
+        (LiteralValue::IntervalValue(left), LiteralValue::IntervalValue(right)) => {
+            Some(approximate_length(left).cmp(&approximate_length(right)))
+        }
+        _ => Some(to_timestamp(left)?.cmp(&to_timestamp(right)?)),
+    }
+}
+
+fn approximate_length(interval: &Interval) -> i128 {
+    (i128::from(interval.months) * 30 + i128::from(interval.days)) * i128::from(MICROSECONDS_PER_DAY)
+        + i128::from(interval.microseconds)
+}
+
+pub(crate) fn register_date_functions(functions: &mut FunctionRegistry) {
+    use ParamType::{DateTime, Exact};
+
+    let part = Exact(ValueType::String);
+    functions
+        .register("today", Signature::new(vec![], Exact(ValueType::Date)), |_| {
+            Ok(LiteralValue::DateValue(Local::now().date_naive()))
+        })
+        .register("now", Signature::new(vec![], Exact(ValueType::Timestamp)), |_| {
+            Ok(LiteralValue::TimestampValue(Local::now().naive_local()))
+        })
+        .register(
+            "datepart",
+            Signature::new(vec![part, DateTime], Exact(ValueType::Integer)),
+            datepart,
+        )
+        .register("date_trunc", Signature::new(vec![part, DateTime], DateTime), date_trunc)
+        .register(
+            "datediff",
+            Signature::new(vec![part, DateTime, DateTime], Exact(ValueType::Integer)),
+            datediff,
+        )
+        .register(
+            "dateadd",
+            Signature::new(vec![part, Exact(ValueType::Integer), DateTime], DateTime),
+            dateadd,
+        );
+}
+
+fn date_part_argument(function_name: &str, value: &LiteralValue) -> Result<DatePart, ErrorKind> {
+    let name = String::try_from(value.clone())?;
+    DatePart::parse(&name).ok_or_else(|| ErrorKind::InvalidArgument {
+        function_name: function_name.to_string(),
+        reason: format!("unknown date part `{}`", name),
+    })
+}
+
+fn timestamp_argument(value: &LiteralValue) -> Result<NaiveDateTime, ErrorKind> {
+    Ok(NaiveDateTime::try_from(value.clone())?)
+}
+
+//start of the unit the timestamp falls into, weeks start on Monday
+fn truncate(timestamp: NaiveDateTime, part: DatePart) -> Option<NaiveDateTime> {
+    let date = timestamp.date();
+    let first_day_of_month = |month: u32| NaiveDate::from_ymd_opt(date.year(), month, 1);
+    let start = match part {
+        DatePart::Year => first_day_of_month(1)?.and_time(NaiveTime::MIN),
+        DatePart::Quarter => first_day_of_month(date.month0() / 3 * 3 + 1)?.and_time(NaiveTime::MIN),
+        DatePart::Month => first_day_of_month(date.month())?.and_time(NaiveTime::MIN),
+        DatePart::Week => {
+            let days_since_monday = i64::from(date.weekday().num_days_from_monday());
+            date.checked_sub_signed(TimeDelta::try_days(days_since_monday)?)?
+                .and_time(NaiveTime::MIN)
+        }
+        DatePart::Day => date.and_time(NaiveTime::MIN),
+        DatePart::Hour => date.and_hms_opt(timestamp.hour(), 0, 0)?,
+        DatePart::Minute => date.and_hms_opt(timestamp.hour(), timestamp.minute(), 0)?,
+        DatePart::Second => date.and_hms_opt(timestamp.hour(), timestamp.minute(), timestamp.second())?,
+    };
+    Some(start)
+}
+
+//datepart("month", order_date) returns the number of the month
+fn datepart(args: &[LiteralValue]) -> Result<LiteralValue, ErrorKind> {
+    let part = date_part_argument("datepart", &args[0])?;
+    let timestamp = timestamp_argument(&args[1])?;
+    let value = match part {
+        DatePart::Year => timestamp.year(),
+        DatePart::Quarter => (timestamp.month0() / 3 + 1) as i32,
+        DatePart::Month => timestamp.month() as i32,
+        DatePart::Week => timestamp.iso_week().week() as i32,
+        DatePart::Day => timestamp.day() as i32,
+        DatePart::Hour => timestamp.hour() as i32,
+        DatePart::Minute => timestamp.minute() as i32,
+        DatePart::Second => timestamp.second() as i32,
+    };
+    Ok(LiteralValue::IntegerValue(i64::from(value)))
+}
+
+//date_trunc("month", order_date) returns the first day of the month, dates stay dates
+fn date_trunc(args: &[LiteralValue]) -> Result<LiteralValue, ErrorKind> {
+    let part = date_part_argument("date_trunc", &args[0])?;
+    let truncated = truncate(timestamp_argument(&args[1])?, part).ok_or(ErrorKind::NumericOverflow)?;
+    match args[1] {
+        LiteralValue::DateValue(_) => Ok(LiteralValue::DateValue(truncated.date())),
+        _ => Ok(LiteralValue::TimestampValue(truncated)),
+    }
+}
+
+//datediff("day", start, end) counts the unit boundaries crossed between start and end
+fn datediff(args: &[LiteralValue]) -> Result<LiteralValue, ErrorKind> {
+    let part = date_part_argument("datediff", &args[0])?;
+    let start = timestamp_argument(&args[1])?;
+    let end = timestamp_argument(&args[2])?;
+    let months = |timestamp: NaiveDateTime| i64::from(timestamp.year()) * 12 + i64::from(timestamp.month0());
+    let truncated_difference =
+        |part: DatePart| -> Option<TimeDelta> { Some(truncate(end, part)? - truncate(start, part)?) };
+    let difference = match part {
+        DatePart::Year => Some(i64::from(end.year()) - i64::from(start.year())),
+        DatePart::Quarter => Some(months(end).div_euclid(3) - months(start).div_euclid(3)),
+        DatePart::Month => Some(months(end) - months(start)),
+        DatePart::Week => truncated_difference(DatePart::Week).map(|difference| difference.num_weeks()),
+        DatePart::Day => Some((end.date() - start.date()).num_days()),
+        DatePart::Hour => truncated_difference(DatePart::Hour).map(|difference| difference.num_hours()),
+        DatePart::Minute => truncated_difference(DatePart::Minute).map(|difference| difference.num_minutes()),
+        DatePart::Second => truncated_difference(DatePart::Second).map(|difference| difference.num_seconds()),
+    };
+    difference
+        .map(LiteralValue::IntegerValue)
+        .ok_or(ErrorKind::NumericOverflow)
+}
+
+//dateadd("month", 2, order_date) is the same as order_date + interval '2 months'
+fn dateadd(args: &[LiteralValue]) -> Result<LiteralValue, ErrorKind> {
+    let part = date_part_argument("dateadd", &args[0])?;
+    let amount = i64::try_from(args[1].clone())?;
+    timestamp_argument(&args[2])?;
+    part.interval(amount)
+        .and_then(|interval| shift_value(&args[2], interval))
+        .ok_or(ErrorKind::NumericOverflow)
+}
@@ -0,0 +1,281 @@
+use std::fmt;
+
+use pest::error::{ErrorVariant, InputLocation};
+
+use crate::ast::{LiteralValue, Span, ValueType};
+use crate::Rule;
+
+/// Error produced while parsing or evaluating an expression.
+///
+/// Every error carries the span of the offending part of the input. Errors returned from
+/// functions that have the source text at hand also carry the affected source line, which
+/// `Display` renders with the span underlined.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    //boxed to keep `Result<_, Error>` small on the hot evaluation path
+    inner: Box<ErrorInner>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ErrorInner {
+    kind: ErrorKind,
+    span: Span,
+    source_line: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    /// The input does not match the grammar; `expected` lists the rules that would have been accepted.
+    Parse {
+        expected: Vec<Rule>,
+    },
+    UnknownField {
+        field_id: String,
+    },
+    UnknownFunction {
+        function_name: String,
+    },
+    ArityMismatch {
+        function_name: String,
+        expected_min: usize,
+        expected_max: Option<usize>,
+        actual: usize,
+    },
+    TypeMismatch {
+        expected: ValueType,
+        actual: ValueType,
+    },
+    /// The argument has the right type but a value the function cannot handle.
+    InvalidArgument {
+        function_name: String,
+        reason: String,
+    },
+    /// A date, timestamp or interval literal matches the grammar but does not exist,
+    /// e.g. `#2024-02-30#`.
+    InvalidLiteral {
+        value_type: ValueType,
+    },
+    DivisionByZero,
+    /// The result of integer or decimal arithmetic does not fit into its type.
+    NumericOverflow,
+    /// Parentheses or modifier brackets are nested deeper than the parser allows.
+    NestingTooDeep {
+        max_depth: usize,
+    },
+    /// The parser was configured to reject WHERE and GROUP BY modifiers.
+    ModifierNotAllowed,
+    /// WHERE and GROUP BY modifiers need a row set and cannot be evaluated against a single row.
+    UnsupportedModifier,
+    /// An aggregate function is used where a single row is evaluated, e.g. inside another aggregate.
+    UnexpectedAggregate {
+        function_name: String,
+    },
+    /// A field is used outside of an aggregate in an expression evaluated over many rows.
+    FieldNotAggregated {
+        field_id: String,
+    },
+    /// `group(N)` refers to a query group that does not exist, groups are counted from 1.
+    UnknownGroup {
+        index: usize,
+    },
+    /// A GROUP BY modifier outside of an aggregate has different values within a query group.
+    AmbiguousLevelOfDetail,
+    /// A WHERE or GROUP BY modifier wraps an expression without aggregates, or with fields used
+    /// outside of aggregates, e.g. `1 [where ignore all filters]`.
+    ModifierWithoutAggregate,
+    /// The SQL dialect has no equivalent for a function or operation of the expression.
+    UnsupportedInDialect {
+        dialect: String,
+        feature: String,
+    },
+}
+
+/// Returned when a `LiteralValue` is converted into a Rust type that does not match its type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueTypeError {
+    pub expected: ValueType,
+    pub actual: ValueType,
+}
+
+impl ValueTypeError {
+    pub fn new(expected: ValueType, actual: &LiteralValue) -> Self {
+        ValueTypeError {
+            expected,
+            actual: actual.value_type(),
+        }
+    }
+
+    /// Turns the conversion failure into a type mismatch error for the node at `span`.
+    pub fn at(self, span: Span) -> Error {
+        Error::new(ErrorKind::from(self), span)
+    }
+}
+
+impl From<ValueTypeError> for ErrorKind {
+    fn from(error: ValueTypeError) -> Self {
+        ErrorKind::TypeMismatch {
+            expected: error.expected,
+            actual: error.actual,
+        }
+    }
+}
+
+impl fmt::Display for ValueTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected {} but got {}", self.expected, self.actual)
+    }
+}
+
+impl std::error::Error for ValueTypeError {}
+
+impl Error {
+    pub fn new(kind: ErrorKind, span: Span) -> Self {
+        Error {
+            inner: Box::new(ErrorInner {
+                kind,
+                span,
+                source_line: None,
+            }),
+        }
+    }
+
+    pub(crate) fn from_pest(error: pest::error::Error<Rule>, source: &str) -> Self {
+        let expected = match &error.variant {
+            ErrorVariant::ParsingError { positives, .. } => positives.clone(),
+            ErrorVariant::CustomError { .. } => vec![],
+        };
+        let (start, end) = match error.location {
+            InputLocation::Pos(pos) => (pos, pos),
+            InputLocation::Span(span) => span,
+        };
+        let span = pest::Span::new(source, start, end)
+            .map(Span::from)
+            .unwrap_or_default();
+        Error::new(ErrorKind::Parse { expected }, span).with_source(source)
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.inner.kind
+    }
+
+    pub fn span(&self) -> Span {
+        self.inner.span
+    }
+
+    /// Attaches the source line the span points to, so it is shown when the error is displayed.
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.inner.source_line = source
+            .lines()
+            .nth(self.inner.span.start_line.saturating_sub(1))
+            .map(|line| line.to_string());
+        self
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::Parse { expected } if expected.is_empty() => write!(f, "invalid expression"),
+            ErrorKind::Parse { expected } => {
+                let expected: Vec<String> =
+                    expected.iter().map(|rule| format!("{:?}", rule)).collect();
+                write!(f, "invalid expression, expected {}", expected.join(", "))
+            }
+            ErrorKind::UnknownField { field_id } => write!(f, "unknown field `{}`", field_id),
+            ErrorKind::UnknownFunction { function_name } => {
+                write!(f, "unknown function `{}`", function_name)
+            }
+            ErrorKind::ArityMismatch {
+                function_name,
+                expected_min,
+                expected_max,
+                actual,
+            } => {
+                let expected = match expected_max {
+                    Some(max) if max == expected_min => format!("{}", max),
+                    Some(max) => format!("{} to {}", expected_min, max),
+                    None => format!("at least {}", expected_min),
+                };
+                write!(
+                    f,
+                    "function `{}` expects {} argument(s), got {}",
+                    function_name, expected, actual
+                )
+            }
+            ErrorKind::TypeMismatch { expected, actual } => {
+                write!(f, "type mismatch, expected {} but got {}", expected, actual)
+            }
+            ErrorKind::InvalidArgument { function_name, reason } => {
+                write!(f, "invalid argument of function `{}`, {}", function_name, reason)
+            }
+            ErrorKind::InvalidLiteral { value_type } => write!(f, "invalid {} literal", value_type),
+            ErrorKind::DivisionByZero => write!(f, "division by zero"),
+            ErrorKind::NumericOverflow => write!(f, "numeric overflow"),
+            ErrorKind::NestingTooDeep { max_depth } => {
+                write!(f, "expression is nested deeper than {} levels", max_depth)
+            }
+            ErrorKind::ModifierNotAllowed => {
+                write!(f, "WHERE and GROUP BY modifiers are not allowed here")
+            }
+            ErrorKind::UnsupportedModifier => {
+                write!(f, "WHERE and GROUP BY modifiers cannot be evaluated on a single row")
+            }
+            ErrorKind::UnexpectedAggregate { function_name } => {
+                write!(f, "aggregate function `{}` is not allowed here", function_name)
+            }
+            ErrorKind::FieldNotAggregated { field_id } => {
+                write!(f, "field `{}` has to be used inside an aggregate function", field_id)
+            }
+            ErrorKind::UnknownGroup { index } => write!(f, "the query has no group {}", index),
+            ErrorKind::AmbiguousLevelOfDetail => {
+                write!(f, "GROUP BY modifier has more than one value per group, it has to be aggregated")
+            }
+            ErrorKind::ModifierWithoutAggregate => {
+                write!(f, "WHERE and GROUP BY modifiers can only be applied to aggregate expressions")
+            }
+            ErrorKind::UnsupportedInDialect { dialect, feature } => {
+                write!(f, "{} cannot be translated to {}", feature, dialect)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span = &self.inner.span;
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.inner.kind, span.start_line, span.start_column
+        )?;
+        if let Some(line) = &self.inner.source_line {
+            //underline the span up to the end of its first line
+            let line_number = span.start_line.to_string();
+            let gutter = " ".repeat(line_number.len());
+            let start = span.start_column.saturating_sub(1);
+            let width = if span.end_line == span.start_line {
+                span.end_column.saturating_sub(span.start_column)
+            } else {
+                line.chars().count().saturating_sub(start)
+            };
+            let padding: String = line
+                .chars()
+                .take(start)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            write!(
+                f,
+                "\n{} |\n{} | {}\n{} | {}{}",
+                gutter,
+                line_number,
+                line,
+                gutter,
+                padding,
+                "^".repeat(width.max(1))
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,687 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::BuildHasher;
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use crate::aggregate::{self, DashboardFilter};
+use crate::compile::{self, CompiledExpression};
+use crate::ast::{Expression, LiteralValue, Span, ValueType};
+use crate::bytecode::{self, Program};
+use crate::datetime;
+use crate::error::{Error, ErrorKind, ValueTypeError};
+use crate::functions::{Conditional, FunctionDef, FunctionRegistry, NullHandling, Signature};
+use crate::lod::{self, GroupValue};
+
+/// Source of field values for evaluation.
+///
+/// Field ids are passed exactly as written in the expression, including the dots of
+/// qualified references such as `table.column`.
+pub trait EvalContext {
+    /// Value of the field, or `None` when the context has no such field. Fields that exist but
+    /// have no value should resolve to `LiteralValue::NullValue`, as `None` is reported as an
+    /// unknown field.
+    fn resolve(&self, field_id: &str) -> Option<Cow<'_, LiteralValue>>;
+}
+
+impl<S: BuildHasher> EvalContext for HashMap<String, LiteralValue, S> {
+    fn resolve(&self, field_id: &str) -> Option<Cow<'_, LiteralValue>> {
+        self.get(field_id).map(Cow::Borrowed)
+    }
+}
+
+impl EvalContext for BTreeMap<String, LiteralValue> {
+    fn resolve(&self, field_id: &str) -> Option<Cow<'_, LiteralValue>> {
+        self.get(field_id).map(Cow::Borrowed)
+    }
+}
+
+//a blanket impl for references would conflict with the one for closures,
+//so references to contexts are supported one by one, e.g. for `evaluate_rows(&expr, &rows)`
+impl<S: BuildHasher> EvalContext for &HashMap<String, LiteralValue, S> {
+    fn resolve(&self, field_id: &str) -> Option<Cow<'_, LiteralValue>> {
+        (**self).resolve(field_id)
+    }
+}
+
+impl EvalContext for &BTreeMap<String, LiteralValue> {
+    fn resolve(&self, field_id: &str) -> Option<Cow<'_, LiteralValue>> {
+        (**self).resolve(field_id)
+    }
+}
+
+impl<F: Fn(&str) -> Option<LiteralValue>> EvalContext for F {
+    fn resolve(&self, field_id: &str) -> Option<Cow<'_, LiteralValue>> {
+        self(field_id).map(Cow::Owned)
+    }
+}
+
+/// Looks the field id up as a key first, then as a path through nested objects, so both
+/// `{"table.column": 1}` and `{"table": {"column": 1}}` resolve `table.column`.
+/// Only strings, numbers, booleans and nulls can be used as values.
+#[cfg(feature = "serde_json")]
+impl EvalContext for serde_json::Map<String, serde_json::Value> {
+    fn resolve(&self, field_id: &str) -> Option<Cow<'_, LiteralValue>> {
+        let value = self.get(field_id).or_else(|| {
+            let mut path = field_id.split('.');
+            let first = self.get(path.next()?)?;
+            path.try_fold(first, |value, key| value.get(key))
+        })?;
+        match value {
+            serde_json::Value::String(s) => Some(LiteralValue::StringValue(s.clone())),
+            serde_json::Value::Number(n) => n
+                .as_i64()
+                .map(LiteralValue::IntegerValue)
+                .or_else(|| n.as_f64().map(LiteralValue::NumberValue)),
+            serde_json::Value::Bool(b) => Some(LiteralValue::BooleanValue(*b)),
+            serde_json::Value::Null => Some(LiteralValue::NullValue),
+            _ => None,
+        }
+        .map(Cow::Owned)
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl EvalContext for serde_json::Value {
+    fn resolve(&self, field_id: &str) -> Option<Cow<'_, LiteralValue>> {
+        self.as_object()?.resolve(field_id)
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl EvalContext for &serde_json::Map<String, serde_json::Value> {
+    fn resolve(&self, field_id: &str) -> Option<Cow<'_, LiteralValue>> {
+        (**self).resolve(field_id)
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl EvalContext for &serde_json::Value {
+    fn resolve(&self, field_id: &str) -> Option<Cow<'_, LiteralValue>> {
+        (**self).resolve(field_id)
+    }
+}
+
+/// Evaluates an expression against the field values of a single row, with the built-in functions.
+///
+/// The expression is only borrowed, so a parsed expression can be evaluated against any
+/// number of rows. Operators compare literals and field values in place; function arguments
+/// are copied, on the stack for functions with up to four arguments.
+pub fn evaluate<C: EvalContext + ?Sized>(expression: &Expression, ctx: &C) -> Result<LiteralValue, Error> {
+    eval_ast(expression, ctx)
+}
+
+/// Evaluates one expression against every row of `rows`, lazily.
+pub fn evaluate_rows<'a, C, I>(
+    expression: &'a Expression,
+    rows: I,
+) -> impl Iterator<Item = Result<LiteralValue, Error>> + 'a
+where
+    C: EvalContext,
+    I: IntoIterator<Item = C>,
+    I::IntoIter: 'a,
+{
+    rows.into_iter().map(move |row| eval_ast(expression, &row))
+}
+
+/// Evaluates expressions with the functions of a [`FunctionRegistry`].
+#[derive(Debug, Clone)]
+pub struct Evaluator {
+    functions: FunctionRegistry,
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Evaluator::new(FunctionRegistry::with_builtins())
+    }
+}
+
+impl Evaluator {
+    pub fn new(functions: FunctionRegistry) -> Self {
+        Evaluator { functions }
+    }
+
+    pub fn functions(&self) -> &FunctionRegistry {
+        &self.functions
+    }
+
+    pub fn evaluate<C: EvalContext + ?Sized>(&self, expression: &Expression, ctx: &C) -> Result<LiteralValue, Error> {
+        eval_node(expression, ctx, &self.functions)
+    }
+
+    pub fn evaluate_rows<'a, C, I>(
+        &'a self,
+        expression: &'a Expression,
+        rows: I,
+    ) -> impl Iterator<Item = Result<LiteralValue, Error>> + 'a
+    where
+        C: EvalContext,
+        I: IntoIterator<Item = C>,
+        I::IntoIter: 'a,
+    {
+        rows.into_iter().map(move |row| self.evaluate(expression, &row))
+    }
+
+    /// Compiles an expression with the functions of this evaluator, see [`compile`](crate::compile).
+    pub fn compile<'a, S: AsRef<str>>(&'a self, expression: &'a Expression, columns: &[S]) -> CompiledExpression<'a> {
+        compile::compile_with(expression, columns, &self.functions)
+    }
+
+    /// Compiles an expression to a [`Program`] calling the functions of this evaluator.
+    pub fn compile_program<S: AsRef<str>>(&self, expression: &Expression, columns: &[S]) -> Program {
+        bytecode::compile_with(expression, columns, &self.functions)
+    }
+
+    /// Evaluates a program compiled by [`Evaluator::compile_program`] on a row, see [`Program::evaluate`].
+    pub fn evaluate_program(&self, program: &Program, row: &[LiteralValue]) -> Result<LiteralValue, Error> {
+        program.run(row, &self.functions)
+    }
+
+    /// Evaluates an expression with aggregates over all rows, see [`evaluate_aggregate`](crate::evaluate_aggregate).
+    pub fn evaluate_aggregate<C, I>(&self, expression: &Expression, rows: I) -> Result<LiteralValue, Error>
+    where
+        C: EvalContext,
+        I: IntoIterator<Item = C>,
+    {
+        aggregate::aggregate_rows(expression, rows, &[], &self.functions)
+    }
+
+    /// Evaluates an expression with aggregates over all rows and dashboard filters, see
+    /// [`evaluate_aggregate_with_filters`](crate::evaluate_aggregate_with_filters).
+    pub fn evaluate_aggregate_with_filters<C, I>(
+        &self,
+        expression: &Expression,
+        rows: I,
+        dashboard_filters: &[DashboardFilter],
+    ) -> Result<LiteralValue, Error>
+    where
+        C: EvalContext,
+        I: IntoIterator<Item = C>,
+    {
+        aggregate::aggregate_rows(expression, rows, dashboard_filters, &self.functions)
+    }
+
+    /// Evaluates an expression for every group of a grouped query, see
+    /// [`evaluate_grouped`](crate::evaluate_grouped).
+    pub fn evaluate_grouped<C, I>(
+        &self,
+        expression: &Expression,
+        rows: I,
+        query_groups: &[Expression],
+        dashboard_filters: &[DashboardFilter],
+    ) -> Result<Vec<GroupValue>, Error>
+    where
+        C: EvalContext,
+        I: IntoIterator<Item = C>,
+    {
+        let rows = rows.into_iter().collect::<Vec<_>>();
+        lod::aggregate_groups(expression, &rows, query_groups, dashboard_filters, &self.functions)
+    }
+}
+
+//nesting evaluated recursively, safe for the 2 MiB stack of spawned threads in debug builds
+const MAX_RECURSION_DEPTH: usize = 128;
+
+//function arguments collected without allocating
+const INLINE_ARGUMENTS: usize = 4;
+
+//evaluate with the built-in functions
+pub(crate) fn eval_ast<C: EvalContext + ?Sized>(ast: &Expression, ctx: &C) -> Result<LiteralValue, Error> {
+    eval_node(ast, ctx, FunctionRegistry::builtins())
+}
+
+pub(crate) fn eval_node<C: EvalContext + ?Sized>(
+    ast: &Expression,
+    ctx: &C,
+    functions: &FunctionRegistry,
+) -> Result<LiteralValue, Error> {
+    eval_nested(ast, ctx, functions, 0).map(Cow::into_owned)
+}
+
+//literals and field values are borrowed, `depth` counts the calls of this function up the stack,
+//sub-expressions nested deeper are evaluated as a `Program`, which does not recurse
+fn eval_nested<'a, C: EvalContext + ?Sized>(
+    ast: &'a Expression,
+    ctx: &'a C,
+    functions: &FunctionRegistry,
+    depth: usize,
+) -> Result<Cow<'a, LiteralValue>, Error> {
+    if depth == MAX_RECURSION_DEPTH {
+        return eval_program(ast, ctx, functions).map(Cow::Owned);
+    }
+    let depth = depth + 1;
+    match ast {
+        Expression::Literal { value, .. } => Ok(Cow::Borrowed(value)),
+        Expression::FieldReference { field_id, span } => {
+            if let Some(field_value) = ctx.resolve(field_id) {
+                Ok(field_value)
+            } else {
+                Err(Error::new(
+                    ErrorKind::UnknownField {
+                        field_id: field_id.clone(),
+                    },
+                    *span,
+                ))
+            }
+        }
+        Expression::Function {
+            function_name,
+            params,
+            span,
+        } => eval_function(function_name, params, *span, ctx, functions, depth).map(Cow::Owned),
+        Expression::IfExpression {
+            condition,
+            result,
+            else_result,
+            ..
+        } => {
+            //a NULL condition is not true, so it takes the ELSE branch
+            if condition_nested(condition, ctx, functions, depth)? == Some(true) {
+                eval_nested(result, ctx, functions, depth)
+            } else {
+                eval_nested(else_result, ctx, functions, depth)
+            }
+        }
+        Expression::CaseExpression {
+            cases, else_result, ..
+        } => {
+            for case in cases {
+                if condition_nested(&case.condition, ctx, functions, depth)? == Some(true) {
+                    return eval_nested(&case.result, ctx, functions, depth);
+                }
+            }
+            //like in SQL, a CASE without ELSE results in NULL when no branch matches
+            match else_result {
+                Some(else_result) => eval_nested(else_result, ctx, functions, depth),
+                None => Ok(Cow::Owned(LiteralValue::NullValue)),
+            }
+        }
+        Expression::ModifierExpression { span, .. } => {
+            Err(Error::new(ErrorKind::UnsupportedModifier, *span))
+        }
+    }
+}
+
+//operators and function calls produce new values
+fn eval_function<C: EvalContext + ?Sized>(
+    function_name: &str,
+    params: &[Expression],
+    span: Span,
+    ctx: &C,
+    functions: &FunctionRegistry,
+    depth: usize,
+) -> Result<LiteralValue, Error> {
+    //AND and OR short-circuit, so their operands are evaluated one by one.
+    //NULL operands make the result NULL unless another operand decides it,
+    //e.g. `false and null` is false, but `true and null` is null
+    if function_name.eq_ignore_ascii_case("and") || function_name.eq_ignore_ascii_case("or") {
+        let short_circuit_value = function_name.eq_ignore_ascii_case("or");
+        let mut null_operand = false;
+        for param in params {
+            match condition_nested(param, ctx, functions, depth)? {
+                Some(value) if value == short_circuit_value => {
+                    return Ok(LiteralValue::BooleanValue(short_circuit_value));
+                }
+                Some(_) => {}
+                None => null_operand = true,
+            }
+        }
+        return Ok(if null_operand {
+            LiteralValue::NullValue
+        } else {
+            LiteralValue::BooleanValue(!short_circuit_value)
+        });
+    }
+
+    match function_name {
+        "+" | "-" | "*" | "/" | "=" | "!=" | "<" | "<=" | ">" | ">=" => {
+            let (left, right) = binary_operands(function_name, params, span, ctx, functions, depth)?;
+            eval_binary_operator(function_name, &left, &right, [params[0].span(), params[1].span()], span)
+        }
+        not if not.eq_ignore_ascii_case("not") => match params {
+            [operand] => Ok(match condition_nested(operand, ctx, functions, depth)? {
+                Some(value) => LiteralValue::BooleanValue(!value),
+                None => LiteralValue::NullValue,
+            }),
+            _ => Err(arity_mismatch(function_name, 1, params.len(), span)),
+        },
+        "is null" | "is not null" => match params {
+            [operand] => {
+                let is_null = *eval_nested(operand, ctx, functions, depth)? == LiteralValue::NullValue;
+                Ok(LiteralValue::BooleanValue(is_null == (function_name == "is null")))
+            }
+            _ => Err(arity_mismatch(function_name, 1, params.len(), span)),
+        },
+        _ => {
+            let Some(function) = functions.get(function_name) else {
+                //aggregates need all rows and are evaluated by `evaluate_aggregate`
+                if aggregate::is_aggregate(function_name) {
+                    return Err(Error::new(
+                        ErrorKind::UnexpectedAggregate {
+                            function_name: function_name.to_string(),
+                        },
+                        span,
+                    ));
+                }
+                return Err(Error::new(
+                    ErrorKind::UnknownFunction {
+                        function_name: function_name.to_string(),
+                    },
+                    span,
+                ));
+            };
+            call_function(function, function_name, params, span, ctx, functions, depth)
+        }
+    }
+}
+
+//check the arguments against the signature, then call the function with their values
+fn call_function<C: EvalContext + ?Sized>(
+    function: &FunctionDef,
+    function_name: &str,
+    params: &[Expression],
+    span: Span,
+    ctx: &C,
+    functions: &FunctionRegistry,
+    depth: usize,
+) -> Result<LiteralValue, Error> {
+    let signature = function.signature();
+    signature.check_arity(function_name, params.len(), span)?;
+    if let Some(conditional) = function.conditional() {
+        return eval_conditional(conditional, params.len(), |index| {
+            let value = eval_nested(&params[index], ctx, functions, depth)?;
+            check_argument(signature, index, &value, params[index].span())?;
+            Ok(value.into_owned())
+        });
+    }
+    //functions take a slice of values, which is kept on the stack unless there are more arguments
+    let mut inline_args: [LiteralValue; INLINE_ARGUMENTS] = std::array::from_fn(|_| LiteralValue::NullValue);
+    let mut spilled_args = Vec::new();
+    let inline = params.len() <= INLINE_ARGUMENTS;
+    for (index, param) in params.iter().enumerate() {
+        let value = eval_nested(param, ctx, functions, depth)?;
+        check_argument(signature, index, &value, param.span())?;
+        if inline {
+            inline_args[index] = value.into_owned();
+        } else {
+            spilled_args.push(value.into_owned());
+        }
+    }
+    let args = if inline { &inline_args[..params.len()] } else { &spilled_args[..] };
+    call_with_arguments(function, args, span)
+}
+
+//evaluate a conditional function, `argument` evaluates and checks the argument with an index
+pub(crate) fn eval_conditional(
+    conditional: Conditional,
+    arg_count: usize,
+    mut argument: impl FnMut(usize) -> Result<LiteralValue, Error>,
+) -> Result<LiteralValue, Error> {
+    match conditional {
+        //a NULL condition takes the optional fourth argument
+        Conditional::Iif => match argument(0)? {
+            LiteralValue::BooleanValue(true) => argument(1),
+            LiteralValue::BooleanValue(false) => argument(2),
+            _ if arg_count > 3 => argument(3),
+            _ => Ok(LiteralValue::NullValue),
+        },
+        Conditional::FirstNonNull => {
+            for index in 0..arg_count {
+                let value = argument(index)?;
+                if value != LiteralValue::NullValue {
+                    return Ok(value);
+                }
+            }
+            Ok(LiteralValue::NullValue)
+        }
+    }
+}
+
+pub(crate) fn check_argument(
+    signature: &Signature,
+    index: usize,
+    value: &LiteralValue,
+    param_span: Span,
+) -> Result<(), Error> {
+    match signature.param_type(index) {
+        Some(param_type) if !param_type.accepts(value.value_type()) => {
+            Err(ValueTypeError::new(param_type.expected_type(), value).at(param_span))
+        }
+        _ => Ok(()),
+    }
+}
+
+//call the function with checked arguments, NULL arguments make the result NULL unless the function handles them
+pub(crate) fn call_with_arguments(function: &FunctionDef, args: &[LiteralValue], span: Span) -> Result<LiteralValue, Error> {
+    if function.signature().null_handling() == NullHandling::Propagate && args.contains(&LiteralValue::NullValue) {
+        return Ok(LiteralValue::NullValue);
+    }
+    function.call(args).map_err(|kind| Error::new(kind, span))
+}
+
+//evaluate the operands of a binary operator
+fn binary_operands<'a, C: EvalContext + ?Sized>(
+    function_name: &str,
+    params: &'a [Expression],
+    span: Span,
+    ctx: &'a C,
+    functions: &FunctionRegistry,
+    depth: usize,
+) -> Result<(Cow<'a, LiteralValue>, Cow<'a, LiteralValue>), Error> {
+    match params {
+        [left, right] => Ok((
+            eval_nested(left, ctx, functions, depth)?,
+            eval_nested(right, ctx, functions, depth)?,
+        )),
+        _ => Err(arity_mismatch(function_name, 2, params.len(), span)),
+    }
+}
+
+//apply an arithmetic or comparison operator to the values of its operands
+pub(crate) fn eval_binary_operator(
+    function_name: &str,
+    left: &LiteralValue,
+    right: &LiteralValue,
+    operand_spans: [Span; 2],
+    span: Span,
+) -> Result<LiteralValue, Error> {
+    if *left == LiteralValue::NullValue || *right == LiteralValue::NullValue {
+        return Ok(LiteralValue::NullValue);
+    }
+    if let "+" | "-" | "*" | "/" = function_name {
+        if let Some(result) = datetime::eval_temporal_arithmetic(function_name, left, right, operand_spans, span) {
+            return result;
+        }
+        let operands = numeric_operands(left, right, operand_spans)?;
+        return eval_arithmetic(function_name, operands, operand_spans, span);
+    }
+    let ordering = compare_values(left, right, operand_spans[1])?;
+    let result = match (function_name, ordering) {
+        ("=", Some(o)) => o == Ordering::Equal,
+        ("!=", Some(o)) => o != Ordering::Equal,
+        ("<", Some(o)) => o == Ordering::Less,
+        ("<=", Some(o)) => o != Ordering::Greater,
+        (">", Some(o)) => o == Ordering::Greater,
+        (">=", Some(o)) => o != Ordering::Less,
+        //NaN is not equal to anything, including itself
+        ("!=", None) => true,
+        _ => false,
+    };
+    Ok(LiteralValue::BooleanValue(result))
+}
+
+//operands of a binary numeric operator, promoted to their common type
+pub(crate) enum NumericOperands {
+    Integer(i64, i64),
+    Decimal(Decimal, Decimal),
+    Float(f64, f64),
+}
+
+//integers are promoted to decimals and both to floats, None if any value is not a number
+pub(crate) fn promote(left: &LiteralValue, right: &LiteralValue) -> Option<NumericOperands> {
+    use LiteralValue::{DecimalValue, IntegerValue, NumberValue};
+    let to_decimal = |value: &LiteralValue| match value {
+        IntegerValue(value) => Some(Decimal::from(*value)),
+        DecimalValue(value) => Some(*value),
+        _ => None,
+    };
+    let to_float = |value: &LiteralValue| match value {
+        IntegerValue(value) => Some(*value as f64),
+        DecimalValue(value) => Some(value.to_f64().unwrap_or(f64::NAN)),
+        NumberValue(value) => Some(*value),
+        _ => None,
+    };
+    match (left, right) {
+        (IntegerValue(left), IntegerValue(right)) => Some(NumericOperands::Integer(*left, *right)),
+        (NumberValue(_), _) | (_, NumberValue(_)) => Some(NumericOperands::Float(to_float(left)?, to_float(right)?)),
+        _ => Some(NumericOperands::Decimal(to_decimal(left)?, to_decimal(right)?)),
+    }
+}
+
+//check that a binary arithmetic operator got two numbers
+fn numeric_operands(
+    left: &LiteralValue,
+    right: &LiteralValue,
+    operand_spans: [Span; 2],
+) -> Result<NumericOperands, Error> {
+    if let Some(operands) = promote(left, right) {
+        return Ok(operands);
+    }
+    //report the first operand that is not a number
+    let (value, span) = match left {
+        LiteralValue::NumberValue(_) | LiteralValue::IntegerValue(_) | LiteralValue::DecimalValue(_) => {
+            (right, operand_spans[1])
+        }
+        _ => (left, operand_spans[0]),
+    };
+    Err(ValueTypeError::new(ValueType::Number, value).at(span))
+}
+
+//integer arithmetic stays exact and reports overflows, dividing integers produces a decimal
+fn eval_arithmetic(
+    function_name: &str,
+    operands: NumericOperands,
+    operand_spans: [Span; 2],
+    span: Span,
+) -> Result<LiteralValue, Error> {
+    let overflow = || Error::new(ErrorKind::NumericOverflow, span);
+    let division_by_zero = || Error::new(ErrorKind::DivisionByZero, operand_spans[1]);
+    let operands = match operands {
+        NumericOperands::Integer(left, right) if function_name == "/" => {
+            NumericOperands::Decimal(Decimal::from(left), Decimal::from(right))
+        }
+        operands => operands,
+    };
+    match operands {
+        NumericOperands::Integer(left, right) => {
+            let result = match function_name {
+                "+" => left.checked_add(right),
+                "-" => left.checked_sub(right),
+                _ => left.checked_mul(right),
+            };
+            result.map(LiteralValue::IntegerValue).ok_or_else(overflow)
+        }
+        NumericOperands::Decimal(left, right) => {
+            let result = match function_name {
+                "+" => left.checked_add(right),
+                "-" => left.checked_sub(right),
+                "*" => left.checked_mul(right),
+                _ if right.is_zero() => return Err(division_by_zero()),
+                _ => left.checked_div(right).map(|result| result.normalize()),
+            };
+            result.map(LiteralValue::DecimalValue).ok_or_else(overflow)
+        }
+        NumericOperands::Float(left, right) => {
+            let result = match function_name {
+                "+" => left + right,
+                "-" => left - right,
+                "*" => left * right,
+                _ if right == 0.0 => return Err(division_by_zero()),
+                _ => left / right,
+            };
+            Ok(LiteralValue::NumberValue(result))
+        }
+    }
+}
+
+//compare two non-NULL values of the same type, numbers of different kinds are promoted first,
+//returns None for unordered numbers (NaN)
+pub(crate) fn compare_values(left: &LiteralValue, right: &LiteralValue, right_span: Span) -> Result<Option<Ordering>, Error> {
+    if let Some(ordering) = datetime::compare_temporal(left, right) {
+        return Ok(Some(ordering));
+    }
+    if let Some(operands) = promote(left, right) {
+        return Ok(match operands {
+            NumericOperands::Integer(left, right) => Some(left.cmp(&right)),
+            NumericOperands::Decimal(left, right) => Some(left.cmp(&right)),
+            NumericOperands::Float(left, right) => left.partial_cmp(&right),
+        });
+    }
+    match (left, right) {
+        (LiteralValue::StringValue(left), LiteralValue::StringValue(right)) => Ok(Some(left.cmp(right))),
+        (LiteralValue::BooleanValue(left), LiteralValue::BooleanValue(right)) => Ok(Some(left.cmp(right))),
+        (left, right) => Err(ValueTypeError::new(left.value_type(), right).at(right_span)),
+    }
+}
+
+//evaluate a condition of IF, CASE, AND, OR, which has to produce a boolean or NULL (None)
+pub(crate) fn eval_condition<C: EvalContext + ?Sized>(
+    condition: &Expression,
+    ctx: &C,
+    functions: &FunctionRegistry,
+) -> Result<Option<bool>, Error> {
+    condition_nested(condition, ctx, functions, 0)
+}
+
+fn condition_nested<C: EvalContext + ?Sized>(
+    condition: &Expression,
+    ctx: &C,
+    functions: &FunctionRegistry,
+    depth: usize,
+) -> Result<Option<bool>, Error> {
+    condition_value(&*eval_nested(condition, ctx, functions, depth)?, condition.span())
+}
+
+//compile the expression for its fields, whose values come from the context
+fn eval_program<C: EvalContext + ?Sized>(
+    ast: &Expression,
+    ctx: &C,
+    functions: &FunctionRegistry,
+) -> Result<LiteralValue, Error> {
+    let mut columns: Vec<&str> = Vec::new();
+    let mut known = HashSet::new();
+    let mut pending = vec![ast];
+    while let Some(expression) = pending.pop() {
+        if let Expression::FieldReference { field_id, .. } = expression {
+            if known.insert(field_id.as_str()) {
+                columns.push(field_id);
+            }
+        }
+        pending.extend(expression.children());
+    }
+    let program = bytecode::compile_with(ast, &columns, functions);
+    program.run_with(|slot| ctx.resolve(columns[slot]), functions)
+}
+
+//NULL conditions are `None`, other values have to be booleans
+pub(crate) fn condition_value(value: &LiteralValue, span: Span) -> Result<Option<bool>, Error> {
+    match value {
+        LiteralValue::NullValue => Ok(None),
+        LiteralValue::BooleanValue(value) => Ok(Some(*value)),
+        value => Err(ValueTypeError::new(ValueType::Boolean, value).at(span)),
+    }
+}
+
+pub(crate) fn arity_mismatch(function_name: &str, expected: usize, actual: usize, span: Span) -> Error {
+    Error::new(
+        ErrorKind::ArityMismatch {
+            function_name: function_name.to_string(),
+            expected_min: expected,
+            expected_max: Some(expected),
+            actual,
+        },
+        span,
+    )
+}
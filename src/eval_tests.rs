@@ -0,0 +1,532 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::ast::{field_ref, LiteralValue, ValueType};
+use crate::{
+    check_types, evaluate, evaluate_aggregate, evaluate_aggregate_with_filters, evaluate_grouped, evaluate_rows, parse,
+    DashboardFilter, ErrorKind, Evaluator, FunctionRegistry, NullHandling, ParamType, Schema, Signature,
+};
+
+#[test]
+fn test_hash_map_context() {
+    let ctx = HashMap::from([
+        ("field1".to_string(), LiteralValue::NumberValue(8.0)),
+        ("orders.amount".to_string(), LiteralValue::NumberValue(2.5)),
+    ]);
+
+    let expression = parse("field1 + 42").unwrap();
+    assert_eq!(evaluate(&expression, &ctx).unwrap(), LiteralValue::NumberValue(50.0));
+
+    let expression = parse("orders.amount * field1").unwrap();
+    assert_eq!(evaluate(&expression, &ctx).unwrap(), LiteralValue::NumberValue(20.0));
+
+    let error = evaluate(&parse("orders.quantity").unwrap(), &ctx).unwrap_err();
+    assert_eq!(
+        error.kind(),
+        &ErrorKind::UnknownField {
+            field_id: "orders.quantity".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_btree_map_context() {
+    let ctx = BTreeMap::from([("city".to_string(), LiteralValue::StringValue("Opelika".to_string()))]);
+    let expression = parse(r#"if city = "Opelika" then 1 else 0"#).unwrap();
+    assert_eq!(evaluate(&expression, &ctx).unwrap(), LiteralValue::IntegerValue(1));
+}
+
+#[test]
+fn test_closure_context() {
+    let ctx = |field_id: &str| match field_id {
+        "sales.amount" => Some(LiteralValue::NumberValue(100.0)),
+        "sales.discount" => Some(LiteralValue::NumberValue(0.25)),
+        _ => None,
+    };
+    let expression = parse("sales.amount * (1 - sales.discount)").unwrap();
+    assert_eq!(evaluate(&expression, &ctx).unwrap(), LiteralValue::NumberValue(75.0));
+    assert!(evaluate(&parse("sales.tax").unwrap(), &ctx).is_err());
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_json_context() {
+    let row = serde_json::json!({
+        "region": "EU",
+        "orders": { "amount": 40, "paid": true },
+        "customer.name": "ACME",
+        "tags": ["a", "b"],
+        "discount": null
+    });
+
+    let expression = parse(r#"if orders.paid and region = "EU" then orders.amount / 4 else 0"#).unwrap();
+    assert_eq!(evaluate(&expression, &row).unwrap(), LiteralValue::DecimalValue(crate::Decimal::from(10)));
+    assert_eq!(
+        evaluate(&parse("customer.name").unwrap(), &row).unwrap(),
+        LiteralValue::StringValue("ACME".to_string())
+    );
+    assert!(evaluate(&parse("tags").unwrap(), &row).is_err());
+    assert_eq!(
+        evaluate(&parse("discount is null").unwrap(), &row).unwrap(),
+        LiteralValue::BooleanValue(true)
+    );
+    assert!(evaluate(&parse("orders.missing").unwrap(), row.as_object().unwrap()).is_err());
+}
+
+#[test]
+fn test_evaluate_rows() {
+    let expression = parse("if amount > 10 then amount * 2 else 0").unwrap();
+    let rows: Vec<HashMap<String, LiteralValue>> = [5.0, 20.0, 11.0]
+        .iter()
+        .map(|amount| HashMap::from([("amount".to_string(), LiteralValue::NumberValue(*amount))]))
+        .collect();
+
+    let results: Vec<LiteralValue> = evaluate_rows(&expression, &rows).map(Result::unwrap).collect();
+    assert_eq!(
+        results,
+        vec![
+            LiteralValue::IntegerValue(0),
+            LiteralValue::NumberValue(40.0),
+            LiteralValue::NumberValue(22.0)
+        ]
+    );
+
+    //the expression is only borrowed and can be evaluated again
+    assert_eq!(evaluate(&expression, &rows[1]).unwrap(), LiteralValue::NumberValue(40.0));
+}
+
+#[test]
+fn test_function_registry() {
+    let mut functions = FunctionRegistry::new();
+    let number = ParamType::Numeric;
+    functions.register(
+        "Twice",
+        Signature::new(vec![number], number).with_optional(ParamType::Exact(ValueType::String)),
+        |args| Ok(LiteralValue::NumberValue(f64::try_from(args[0].clone())? * 2.0)),
+    );
+    functions.register(
+        "first_non_null",
+        Signature::new(vec![], ParamType::Any)
+            .with_variadic(ParamType::Any)
+            .with_null_handling(NullHandling::PassThrough),
+        |args| Ok(args.iter().find(|arg| **arg != LiteralValue::NullValue).cloned().unwrap_or(LiteralValue::NullValue)),
+    );
+    functions.alias("double", "twice").unwrap();
+    assert!(functions.alias("triple", "thrice").is_err());
+    assert!(functions.contains("TWICE"));
+    assert_eq!(functions.get("double").unwrap().name(), "Twice");
+
+    let evaluator = Evaluator::new(functions);
+    let ctx = HashMap::from([("amount".to_string(), LiteralValue::NumberValue(1.5))]);
+    let eval = |input: &str| evaluator.evaluate(&parse(input).unwrap(), &ctx);
+
+    assert_eq!(eval("twice(amount) + DOUBLE(1)").unwrap(), LiteralValue::NumberValue(5.0));
+    assert_eq!(eval("twice(null)").unwrap(), LiteralValue::NullValue);
+    assert_eq!(eval("first_non_null(null, amount, 1)").unwrap(), LiteralValue::NumberValue(1.5));
+    //arguments beyond the ones collected on the stack
+    assert_eq!(eval("first_non_null(null, null, null, null, amount)").unwrap(), LiteralValue::NumberValue(1.5));
+
+    let error = eval("twice(1, \"a\", 2)").unwrap_err();
+    assert_eq!(
+        error.kind(),
+        &ErrorKind::ArityMismatch {
+            function_name: "twice".to_string(),
+            expected_min: 1,
+            expected_max: Some(2),
+            actual: 3
+        }
+    );
+    let error = eval("twice(\"a\")").unwrap_err();
+    assert_eq!(
+        error.kind(),
+        &ErrorKind::TypeMismatch {
+            expected: ValueType::Number,
+            actual: ValueType::String
+        }
+    );
+    assert_eq!((error.span().start, error.span().end), (6, 9));
+
+    //the custom registry does not have the built-in functions
+    assert!(matches!(eval("today()").unwrap_err().kind(), ErrorKind::UnknownFunction { .. }));
+    assert!(Evaluator::default().functions().contains("today"));
+}
+
+#[test]
+fn test_function_validation() {
+    let functions = FunctionRegistry::with_builtins();
+    assert!(functions.validate(&parse("datepart(\"year\", today()) > 2000 and not x").unwrap()).is_ok());
+
+    let error = functions.validate(&parse("1 + if x then nope(1) else 0").unwrap()).unwrap_err();
+    assert_eq!(
+        error.kind(),
+        &ErrorKind::UnknownFunction {
+            function_name: "nope".to_string()
+        }
+    );
+    assert_eq!((error.span().start, error.span().end), (14, 21));
+
+    let error = functions.validate(&parse("sum(x) [where dateadd(\"day\", 1) > x]").unwrap()).unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::ArityMismatch { function_name, .. } if function_name == "dateadd"));
+
+    let error = functions.validate(&parse("percentile(x)").unwrap()).unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::ArityMismatch { expected_min: 2, .. }));
+}
+
+#[test]
+fn test_modifier_validation() {
+    let schema = Schema::new()
+        .with_field("region", ValueType::String)
+        .with_field("qty", ValueType::Integer)
+        .with_field("price", ValueType::Decimal);
+    let validate = |input: &str| schema.validate_modifiers(&parse(input).unwrap(), 2);
+
+    assert!(validate("sum(qty)").is_ok());
+    assert!(validate("sum(qty) [where ignore filters on region and price > 1]").is_ok());
+    assert!(validate("(sum(qty) - count(price)) [where allow region]").is_ok());
+    assert!(validate("sum(qty) [where ignore all filters] [group by region, group(2)]").is_ok());
+    assert!(validate("avg(sum(qty) [group by region])").is_ok());
+
+    let errors = validate("1 [where ignore all filters]").unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind(), &ErrorKind::ModifierWithoutAggregate);
+    assert_eq!((errors[0].span().start, errors[0].span().end), (0, 28));
+    let errors = validate("(sum(qty) + qty) [where ignore all filters]").unwrap_err();
+    assert_eq!(errors[0].kind(), &ErrorKind::ModifierWithoutAggregate);
+    assert!(validate("qty > 1 and region = \"EU\" [where region = \"EU\"]").is_err());
+
+    //all problems are reported, with their locations
+    let errors = validate("sum(qty) [where allow profit, region] + max(price) [group by group(3), store]").unwrap_err();
+    let kinds = errors.iter().map(|error| error.kind().clone()).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            ErrorKind::UnknownField {
+                field_id: "profit".to_string()
+            },
+            ErrorKind::UnknownGroup { index: 3 },
+            ErrorKind::UnknownField {
+                field_id: "store".to_string()
+            },
+        ]
+    );
+    assert_eq!((errors[0].span().start, errors[0].span().end), (22, 28));
+    assert_eq!((errors[1].span().start, errors[1].span().end), (61, 69));
+}
+
+#[test]
+fn test_type_checking() {
+    let schema = Schema::new()
+        .with_field("region", ValueType::String)
+        .with_field("qty", ValueType::Integer)
+        .with_field("price", ValueType::Decimal)
+        .with_field("orders.date", ValueType::Date)
+        .with_field("paid", ValueType::Boolean);
+    let functions = FunctionRegistry::with_builtins();
+    let value_type = |input: &str| {
+        let expression = parse(input).unwrap();
+        let typed = check_types(&expression, &schema, &functions).unwrap();
+        typed.value_type
+    };
+    let errors = |input: &str| {
+        let expression = parse(input).unwrap();
+        check_types(&expression, &schema, &functions)
+            .unwrap_err()
+            .into_iter()
+            .map(|error| (error.kind().clone(), error.span().start, error.span().end))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(value_type("qty * price"), Some(ValueType::Decimal));
+    assert_eq!(value_type("qty / 2"), Some(ValueType::Decimal));
+    assert_eq!(value_type("qty * 1e0 > 3 and not paid"), Some(ValueType::Boolean));
+    assert_eq!(value_type("orders.date + 1"), Some(ValueType::Date));
+    assert_eq!(value_type("now() - orders.date"), Some(ValueType::Interval));
+    assert_eq!(value_type("date_trunc(\"month\", orders.date)"), Some(ValueType::Date));
+    assert_eq!(value_type("if paid then qty else price"), Some(ValueType::Decimal));
+    assert_eq!(value_type("case when paid then region end"), Some(ValueType::String));
+    assert_eq!(value_type("coalesce(null, qty, 2.5)"), Some(ValueType::Decimal));
+    assert_eq!(value_type("coalesce(qty, \"none\")"), None);
+    assert_eq!(value_type("pow(qty, 2)"), Some(ValueType::Integer));
+    assert_eq!(value_type("len(null)"), Some(ValueType::Integer));
+    assert_eq!(value_type("avg(qty) [where region = \"EU\"]"), Some(ValueType::Decimal));
+    assert_eq!(value_type("countd(region) / sum(price)"), Some(ValueType::Decimal));
+
+    //every node is annotated, in the order of the children of the expression
+    let expression = parse("upper(region) = \"EU\"").unwrap();
+    let typed = check_types(&expression, &schema, &functions).unwrap();
+    assert_eq!(typed.children[0].value_type, Some(ValueType::String));
+    assert_eq!(typed.children[0].children[0].value_type, Some(ValueType::String));
+    assert!(typed.children[0].children[0].expression.eq_ignoring_spans(&field_ref("region")));
+
+    let mismatch = |expected, actual| ErrorKind::TypeMismatch { expected, actual };
+    assert_eq!(errors("qty + \"abc\""), vec![(mismatch(ValueType::Number, ValueType::String), 6, 11)]);
+    assert_eq!(
+        errors("if 3 then 1 else 0"),
+        vec![(mismatch(ValueType::Boolean, ValueType::Integer), 3, 4)]
+    );
+    //results of IF and CASE that have no common type fail before evaluation
+    assert_eq!(
+        errors("(if paid then 1 else \"a\") + 1"),
+        vec![(mismatch(ValueType::Integer, ValueType::String), 21, 24)]
+    );
+    assert_eq!(
+        errors("case when paid then 1 when paid then \"a\" end * 2"),
+        vec![(mismatch(ValueType::Integer, ValueType::String), 37, 40)]
+    );
+    assert_eq!(
+        errors("case when paid then null when qty > 1 then orders.date else qty end"),
+        vec![(mismatch(ValueType::Date, ValueType::Integer), 60, 63)]
+    );
+    assert_eq!(
+        errors("orders.date + region"),
+        vec![(mismatch(ValueType::Interval, ValueType::String), 14, 20)]
+    );
+    //all errors are reported, errors are not repeated for the expressions around them
+    assert_eq!(
+        errors("left(qty, 2) = 1 or profit > nope(1) or sum(paid) [where region]"),
+        vec![
+            (mismatch(ValueType::String, ValueType::Integer), 5, 8),
+            (
+                ErrorKind::UnknownField {
+                    field_id: "profit".to_string()
+                },
+                20,
+                26
+            ),
+            (
+                ErrorKind::UnknownFunction {
+                    function_name: "nope".to_string()
+                },
+                29,
+                36
+            ),
+            (mismatch(ValueType::Number, ValueType::Boolean), 44, 48),
+            (mismatch(ValueType::Boolean, ValueType::String), 57, 63),
+        ]
+    );
+}
+
+fn order_rows() -> Vec<HashMap<String, LiteralValue>> {
+    let orders = [
+        ("EU", "1", 10, Some("2.5")),
+        ("EU", "2", 3, Some("4")),
+        ("US", "3", 1, Some("10")),
+        ("US", "3", 5, None),
+        ("EU", "4", 2, Some("1.5")),
+    ];
+    orders
+        .iter()
+        .map(|(region, order_id, qty, price)| {
+            let price = match price {
+                Some(price) => LiteralValue::DecimalValue(price.parse().unwrap()),
+                None => LiteralValue::NullValue,
+            };
+            HashMap::from([
+                ("region".to_string(), LiteralValue::StringValue(region.to_string())),
+                ("order_id".to_string(), LiteralValue::StringValue(order_id.to_string())),
+                ("qty".to_string(), LiteralValue::IntegerValue(*qty)),
+                ("price".to_string(), price),
+            ])
+        })
+        .collect()
+}
+
+#[test]
+fn test_aggregate_evaluation() {
+    let rows = order_rows();
+    let eval = |input: &str| evaluate_aggregate(&parse(input).unwrap(), &rows);
+    let decimal = |value: &str| LiteralValue::DecimalValue(value.parse().unwrap());
+
+    assert_eq!(eval("sum(qty)").unwrap(), LiteralValue::IntegerValue(21));
+    assert_eq!(eval("sum(price * qty)").unwrap(), decimal("50"));
+    assert_eq!(eval("SUM(qty) / count(qty)").unwrap(), decimal("4.2"));
+    assert_eq!(eval("avg(qty)").unwrap(), decimal("4.2"));
+    assert_eq!(eval("avg(price)").unwrap(), decimal("4.5"));
+    assert_eq!(eval("min(price)").unwrap(), decimal("1.5"));
+    assert_eq!(eval("max(region)").unwrap(), LiteralValue::StringValue("US".to_string()));
+    assert_eq!(eval("count(price)").unwrap(), LiteralValue::IntegerValue(4));
+    assert_eq!(eval("countd(order_id)").unwrap(), LiteralValue::IntegerValue(4));
+    assert_eq!(eval("countd(qty * 1.0 - qty)").unwrap(), LiteralValue::IntegerValue(1));
+    assert_eq!(eval("median(qty)").unwrap(), decimal("3"));
+    assert_eq!(eval("median(price)").unwrap(), decimal("3.25"));
+    assert_eq!(eval("percentile(qty, 0.25)").unwrap(), decimal("2"));
+    assert_eq!(eval("percentile(qty * 1.0e0, 1)").unwrap(), LiteralValue::NumberValue(10.0));
+    let variance = f64::try_from(eval("variance(qty)").unwrap()).unwrap();
+    assert!((variance - 12.7).abs() < 1e-9);
+    let stddev = f64::try_from(eval("stddev(qty)").unwrap()).unwrap();
+    assert!((stddev - 12.7f64.sqrt()).abs() < 1e-9);
+
+    //WHERE modifiers filter the rows of the aggregates they wrap
+    assert_eq!(eval(r#"count(order_id) [where region = "EU"]"#).unwrap(), LiteralValue::IntegerValue(3));
+    assert_eq!(
+        eval(r#"(sum(qty) - sum(price)) [where region = "US" and price is not null]"#).unwrap(),
+        decimal("-9")
+    );
+    assert_eq!(
+        eval(r#"sum(qty) [where region = "EU"] / sum(qty)"#).unwrap(),
+        LiteralValue::DecimalValue(crate::Decimal::from(15) / crate::Decimal::from(21))
+    );
+    assert_eq!(eval(r#"sum(qty) [where region = "ASIA"]"#).unwrap(), LiteralValue::NullValue);
+    assert_eq!(eval(r#"count(qty) [where region = "ASIA"]"#).unwrap(), LiteralValue::IntegerValue(0));
+    assert_eq!(eval("stddev(qty) [where qty > 5]").unwrap(), LiteralValue::NullValue);
+    assert_eq!(
+        eval(r#"if sum(qty) > 20 then "big" else "small""#).unwrap(),
+        LiteralValue::StringValue("big".to_string())
+    );
+
+    let error = eval("qty + sum(qty)").unwrap_err();
+    assert_eq!(
+        error.kind(),
+        &ErrorKind::FieldNotAggregated {
+            field_id: "qty".to_string()
+        }
+    );
+    assert_eq!((error.span().start, error.span().end), (0, 3));
+    let error = eval("sum(avg(qty))").unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::UnexpectedAggregate { function_name } if function_name == "avg"));
+    assert!(matches!(eval("sum(region)").unwrap_err().kind(), ErrorKind::TypeMismatch { .. }));
+    assert!(matches!(eval("percentile(qty, 2)").unwrap_err().kind(), ErrorKind::InvalidArgument { .. }));
+    assert_eq!(eval("sum(qty) [group by region]").unwrap_err().kind(), &ErrorKind::AmbiguousLevelOfDetail);
+
+    //aggregates cannot be evaluated on a single row
+    let error = evaluate(&parse("sum(qty)").unwrap(), &rows[0]).unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::UnexpectedAggregate { .. }));
+
+    let mut functions = FunctionRegistry::with_builtins();
+    functions.register("double", Signature::new(vec![ParamType::Numeric], ParamType::Numeric), |args| {
+        Ok(LiteralValue::IntegerValue(i64::try_from(args[0].clone()).unwrap() * 2))
+    });
+    let evaluator = Evaluator::new(functions);
+    let result = evaluator.evaluate_aggregate(&parse("double(sum(double(qty)))").unwrap(), &rows);
+    assert_eq!(result.unwrap(), LiteralValue::IntegerValue(84));
+}
+
+#[test]
+fn test_dashboard_filters() {
+    let rows = order_rows();
+    let filters = [
+        DashboardFilter::new("region", parse(r#"region = "EU""#).unwrap()),
+        DashboardFilter::new("qty", parse("qty > 2").unwrap()),
+    ];
+    let eval = |input: &str| evaluate_aggregate_with_filters(&parse(input).unwrap(), &rows, &filters).unwrap();
+    let count = |value| LiteralValue::IntegerValue(value);
+
+    assert_eq!(eval("count(qty)"), count(2));
+    assert_eq!(eval("count(qty) [where ignore all filters]"), count(5));
+    assert_eq!(eval("count(qty) [where ignore filters on region]"), count(3));
+    assert_eq!(eval("count(qty) [where ignore region, qty]"), count(5));
+    assert_eq!(eval("count(qty) [where allow filters on qty]"), count(3));
+    assert_eq!(eval("count(qty) [where allow region]"), count(3));
+    assert_eq!(eval("count(qty) [where allow order_id]"), count(5));
+    //filter expressions are combined with the dashboard filters that still apply
+    assert_eq!(eval("count(qty) [where allow region and price > 3]"), count(1));
+    assert_eq!(eval("count(qty) [where ignore all filters and price > 3]"), count(2));
+    //nested modifiers narrow the filters of the outer ones
+    assert_eq!(
+        eval("(count(qty) [where ignore filters on qty] - count(qty)) [where allow filters on region, qty]"),
+        count(1)
+    );
+    assert_eq!(
+        eval("(count(qty) [where allow filters on region]) [where ignore filters on region]"),
+        count(5)
+    );
+    assert_eq!(
+        eval("count(qty) [where qty < 10] [where ignore all filters and price is not null]"),
+        count(3)
+    );
+    assert_eq!(
+        Evaluator::default()
+            .evaluate_aggregate_with_filters(&parse("sum(qty)").unwrap(), &rows, &filters)
+            .unwrap(),
+        count(13)
+    );
+    assert_eq!(evaluate_aggregate(&parse("count(qty)").unwrap(), &rows).unwrap(), count(5));
+}
+
+#[test]
+fn test_level_of_detail() {
+    let rows = order_rows();
+    let by_region = [parse("region").unwrap()];
+    let grouped = |input: &str, query_groups: &[crate::ast::Expression], filters: &[DashboardFilter]| {
+        evaluate_grouped(&parse(input).unwrap(), &rows, query_groups, filters).map(|groups| {
+            groups
+                .into_iter()
+                .map(|group| (group.keys, group.value))
+                .collect::<Vec<_>>()
+        })
+    };
+    let region = |name: &str| vec![LiteralValue::StringValue(name.to_string())];
+    let integer = |value| LiteralValue::IntegerValue(value);
+    let decimal = |value: &str| LiteralValue::DecimalValue(value.parse().unwrap());
+
+    assert_eq!(
+        grouped("sum(qty)", &by_region, &[]).unwrap(),
+        vec![(region("EU"), integer(15)), (region("US"), integer(6))]
+    );
+    //coarser levels are joined back to every query group
+    assert_eq!(
+        grouped("sum(qty) / sum(qty) [group by all groups]", &by_region, &[]).unwrap(),
+        vec![
+            (region("EU"), LiteralValue::DecimalValue(crate::Decimal::from(15) / crate::Decimal::from(21))),
+            (region("US"), LiteralValue::DecimalValue(crate::Decimal::from(6) / crate::Decimal::from(21)))
+        ]
+    );
+    let by_order = [parse("region").unwrap(), parse("order_id").unwrap()];
+    let groups = grouped("sum(qty) [group by group(1)] - sum(qty) [group by region, order_id]", &by_order, &[]);
+    let values = groups.unwrap().into_iter().map(|(_, value)| value).collect::<Vec<_>>();
+    assert_eq!(values, vec![integer(5), integer(12), integer(0), integer(13)]);
+
+    //finer levels are aggregated per row, orders have one value each
+    assert_eq!(
+        grouped("avg(sum(qty) [group by order_id])", &by_region, &[]).unwrap(),
+        vec![(region("EU"), integer(5)), (region("US"), integer(6))]
+            .into_iter()
+            .map(|(keys, value)| (keys, LiteralValue::DecimalValue(crate::Decimal::try_from(value).unwrap())))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        evaluate_aggregate(&parse("max(count(qty) [group by order_id])").unwrap(), &rows).unwrap(),
+        integer(2)
+    );
+    assert_eq!(
+        evaluate_aggregate(&parse("sum(qty) [group by all groups] + 1").unwrap(), &rows).unwrap(),
+        integer(22)
+    );
+    //nested levels use the values of the inner ones
+    assert_eq!(
+        evaluate_aggregate(
+            &parse("min(max(sum(qty) [group by order_id]) [group by region])").unwrap(),
+            &rows
+        )
+        .unwrap(),
+        integer(6)
+    );
+
+    //dashboard filters remove query groups, modifiers can still ignore them
+    let filters = [DashboardFilter::new("qty", parse("qty > 4").unwrap())];
+    assert_eq!(
+        grouped("count(qty) [group by region] [where ignore all filters]", &by_region, &filters).unwrap(),
+        vec![(region("EU"), integer(3)), (region("US"), integer(2))]
+    );
+    assert_eq!(
+        grouped("count(qty)", &[parse("order_id").unwrap()], &filters).unwrap(),
+        vec![
+            (vec![LiteralValue::StringValue("1".to_string())], integer(1)),
+            (vec![LiteralValue::StringValue("3".to_string())], integer(1))
+        ]
+    );
+    assert_eq!(
+        grouped("median(price) [group by region] [where price > 2]", &by_region, &[]).unwrap(),
+        vec![(region("EU"), decimal("3.25")), (region("US"), decimal("10"))]
+    );
+
+    let error = grouped("sum(qty) [group by order_id]", &by_region, &[]).unwrap_err();
+    assert_eq!(error.kind(), &ErrorKind::AmbiguousLevelOfDetail);
+    assert_eq!((error.span().start, error.span().end), (0, 28));
+    let error = grouped("sum(qty) [group by group(2)]", &by_region, &[]).unwrap_err();
+    assert_eq!(error.kind(), &ErrorKind::UnknownGroup { index: 2 });
+    assert_eq!((error.span().start, error.span().end), (19, 27));
+    let error = Evaluator::default()
+        .evaluate_grouped(&parse("sum(qty) + qty").unwrap(), &rows, &by_region, &[])
+        .unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::FieldNotAggregated { .. }));
+}
@@ -1,38 +1,183 @@
+//the tests use 3.14 as a plain literal, not as an approximation of PI
+#![allow(clippy::approx_constant)]
+
+use std::thread;
+
 use pest::Parser;
 
-use crate::{convert_to_ast, ExpressionParser, Rule};
-use crate::ast::{field_ref, func, lit_num};
+use crate::{parse, ExpressionParser, ErrorKind, Parser as ExpressionParserBuilder, Rule};
+use crate::ast::{Expression, Interval, LiteralValue};
+use crate::{Decimal, NaiveDate};
+use crate::ast::{
+    all_groups, allowed_filters, case_branch, case_expr, case_expr_no_else, field_group, field_ref, func,
+    group_by_modifier, if_expr, ignore_all_filters, ignored_filters, included_groups, lit, lit_dec, lit_int, lit_null, lit_str, modifier_expr,
+    query_group, where_modifier,
+};
 
 macro_rules! ast_test {
     ($name:ident, $expr:expr, $expected:expr) => {
         #[test]
         fn $name() {
-            let mut result = ExpressionParser::parse(Rule::expression_input, $expr).unwrap();
-            let ast = convert_to_ast(result.next().unwrap());
-            assert_eq!(ast, $expected, "Expression '{}' translated into incorrect AST", $expr);
+            let ast = parse($expr).unwrap();
+            let expected = $expected;
+            assert!(ast.eq_ignoring_spans(&expected), "Expression '{}' translated into incorrect AST: {:?}", $expr, ast);
         }
     };
 }
 
 ast_test!(test_addition, 
     "field1 + 43", 
-    func("+", vec![field_ref("field1"), lit_num(43_f64)]));
-ast_test!(test_multiplication, "field1.field2 * 3.14", func("*", vec![field_ref("field1.field2"), lit_num(3.14_f64)]));
+    func("+", vec![field_ref("field1"), lit_int(43)]));
+ast_test!(test_multiplication, "field1.field2 * 3.14", func("*", vec![field_ref("field1.field2"), lit_dec(Decimal::new(314, 2))]));
 ast_test!(test_complex_expression, "(field1 + field2) * (field3 / field4)", func("*", vec![func("+", vec![field_ref("field1"), field_ref("field2")]), func("/", vec![field_ref("field3"), field_ref("field4")])])); 
+ast_test!(test_case_with_else,
+    r#"case when city = "Opelika" then "Op" when city = "Phoenix" then "Ph" else city end"#,
+    case_expr(
+        vec![
+            case_branch(func("=", vec![field_ref("city"), lit_str("Opelika")]), lit_str("Op")),
+            case_branch(func("=", vec![field_ref("city"), lit_str("Phoenix")]), lit_str("Ph")),
+        ],
+        field_ref("city")));
+ast_test!(test_case_without_else,
+    "CASE WHEN streamtype = 1 THEN upper(field1) END",
+    case_expr_no_else(vec![
+        case_branch(func("=", vec![field_ref("streamtype"), lit_int(1)]), func("upper", vec![field_ref("field1")])),
+    ]));
+ast_test!(test_nested_case,
+    "case when a > 1 then case when b then 1 else 2 end else 3 end",
+    case_expr(
+        vec![case_branch(
+            func(">", vec![field_ref("a"), lit_int(1)]),
+            case_expr(vec![case_branch(field_ref("b"), lit_int(1))], lit_int(2)),
+        )],
+        lit_int(3)));
+ast_test!(test_where_modifier,
+    r#"sum(sales) [where city = "Opelika"]"#,
+    modifier_expr(
+        func("sum", vec![field_ref("sales")]),
+        Some(where_modifier(None, vec![func("=", vec![field_ref("city"), lit_str("Opelika")])])),
+        None));
+ast_test!(test_where_modifier_with_filter_context,
+    "(sum(sales) - sum(planned_sales)) [where allow filters on region, city and year = 2020]",
+    modifier_expr(
+        func("-", vec![func("sum", vec![field_ref("sales")]), func("sum", vec![field_ref("planned_sales")])]),
+        Some(where_modifier(
+            Some(allowed_filters(vec![field_ref("region"), field_ref("city")])),
+            vec![func("=", vec![field_ref("year"), lit_int(2020)])])),
+        None));
+ast_test!(test_where_modifier_ignoring_filters,
+    "sum(sales) [where ignore region] / sum(sales) [where ignore all filters]",
+    func("/", vec![
+        modifier_expr(
+            func("sum", vec![field_ref("sales")]),
+            Some(where_modifier(Some(ignored_filters(vec![field_ref("region")])), vec![])),
+            None),
+        modifier_expr(
+            func("sum", vec![field_ref("sales")]),
+            Some(where_modifier(Some(ignore_all_filters()), vec![])),
+            None),
+    ]));
+ast_test!(test_group_by_modifier,
+    "sum(sales) [group by region, group(1)]",
+    modifier_expr(
+        func("sum", vec![field_ref("sales")]),
+        None,
+        Some(group_by_modifier(included_groups(vec![field_group(field_ref("region")), query_group(1)])))));
+ast_test!(test_group_by_all_groups,
+    "sum(sales) [group by all groups] + 1",
+    func("+", vec![
+        modifier_expr(func("sum", vec![field_ref("sales")]), None, Some(group_by_modifier(all_groups()))),
+        lit_int(1),
+    ]));
+ast_test!(test_where_and_group_by_modifiers,
+    "sum(sales) [where ignore all filters] [group by region]",
+    modifier_expr(
+        func("sum", vec![field_ref("sales")]),
+        Some(where_modifier(Some(ignore_all_filters()), vec![])),
+        Some(group_by_modifier(included_groups(vec![field_group(field_ref("region"))])))));
+ast_test!(test_modifier_in_function_argument,
+    "max(sum(sales) [group by region], 0)",
+    func("max", vec![
+        modifier_expr(
+            func("sum", vec![field_ref("sales")]),
+            None,
+            Some(group_by_modifier(included_groups(vec![field_group(field_ref("region"))])))),
+        lit_int(0),
+    ]));
+ast_test!(test_top_level_where_modifier,
+    "sum(sales) - sum(planned_sales) [where ignore all filters]",
+    func("-", vec![
+        func("sum", vec![field_ref("sales")]),
+        modifier_expr(
+            func("sum", vec![field_ref("planned_sales")]),
+            Some(where_modifier(Some(ignore_all_filters()), vec![])),
+            None),
+    ]));
+ast_test!(test_top_level_where_after_parentheses,
+    "(sum(sales) - sum(planned_sales)) [where region = \"EU\"]",
+    modifier_expr(
+        func("-", vec![func("sum", vec![field_ref("sales")]), func("sum", vec![field_ref("planned_sales")])]),
+        Some(where_modifier(None, vec![func("=", vec![field_ref("region"), lit_str("EU")])])),
+        None));
+
+#[test]
+fn test_parser_configuration() {
+    let nested = "((((field1))))";
+    assert!(parse(nested).is_ok());
+    let error = ExpressionParserBuilder::new().max_nesting_depth(3).parse(nested).unwrap_err();
+    assert_eq!(error.kind(), &ErrorKind::NestingTooDeep { max_depth: 3 });
+    assert_eq!(error.span().start, 3);
+    //brackets inside string literals do not count
+    assert!(ExpressionParserBuilder::new().max_nesting_depth(1).parse("f(\"((\\\"((\")").is_ok());
+
+    let max_default_nesting = format!("{}1{}", "1 + (".repeat(64), ")".repeat(64));
+    assert!(crate::eval_expression(&max_default_nesting).is_ok());
+    let deeply_nested = format!("{}1{}", "(".repeat(10_000), ")".repeat(10_000));
+    assert!(matches!(parse(&deeply_nested).unwrap_err().kind(), ErrorKind::NestingTooDeep { .. }));
+    //without the limit, the stack guard of pest rejects the input before the tree is built from it
+    let unlimited = ExpressionParserBuilder::new().max_nesting_depth(usize::MAX);
+    let error = thread::Builder::new()
+        .stack_size(2 << 20)
+        .spawn(move || unlimited.parse(&deeply_nested).unwrap_err())
+        .unwrap()
+        .join()
+        .unwrap();
+    assert!(matches!(error.kind(), ErrorKind::Parse { .. }));
+
+    //IF and CASE expressions nest without brackets
+    let nested_ifs = format!("{}1{}", "if a then ".repeat(1_000), " else 0".repeat(1_000));
+    assert!(matches!(parse(&nested_ifs).unwrap_err().kind(), ErrorKind::NestingTooDeep { .. }));
+    let nested_cases = format!("{}1{}", "case when ".repeat(1_000), " then 1 end".repeat(1_000));
+    assert!(matches!(parse(&nested_cases).unwrap_err().kind(), ErrorKind::NestingTooDeep { .. }));
+    let shallow = ExpressionParserBuilder::new().max_nesting_depth(2);
+    let error = shallow.parse("if a then if b then if c then 1 else 2 else 3 else 4").unwrap_err();
+    assert_eq!(error.kind(), &ErrorKind::NestingTooDeep { max_depth: 2 });
+    assert_eq!((error.span().start, error.span().end), (20, 22));
+    assert!(shallow.parse("if a then if b then 1 else 2 else if c then 3 else 4").is_ok());
+    assert!(shallow.parse("f(if a then 1 else 2, if b then 3 else 4) + case when x then 1 end").is_ok());
+    assert!(shallow.parse("if end_date.if > 1 then \"if if\" else 0").is_ok());
+
+    let no_modifiers = ExpressionParserBuilder::new().allow_modifiers(false);
+    assert!(no_modifiers.parse("if a > 1 then upper(b) else c").is_ok());
+    let error = no_modifiers.parse("1 + max(sum(sales) [group by region], 0)").unwrap_err();
+    assert_eq!(error.kind(), &ErrorKind::ModifierNotAllowed);
+    assert_eq!((error.span().start, error.span().end), (8, 36));
+
+    assert!(matches!(parse("field1 +").unwrap_err().kind(), ErrorKind::Parse { .. }));
+}
 
 #[test]
 fn test_valid_arithmetic_expressions() {
     let expressions = vec![
-        ("field1 + 42", func("+", vec![field_ref("field1"), lit_num(42_f64)])),
+        ("field1 + 42", func("+", vec![field_ref("field1"), lit_int(42)])),
         // "field1.field2 * 3.14",
         // "(field1 + field2) * (field3 / field4)",
         // "field1 - field2.field3 + 42 * 7.5",
     ];
 
     for expr in expressions {
-        let mut result = ExpressionParser::parse(Rule::expression_input, expr.0).unwrap();
-        let ast = convert_to_ast(result.next().unwrap());
-        assert_eq!(ast, expr.1, "Expression '{}' translated into incorrect AST", expr.0);
+        let ast = parse(expr.0).unwrap();
+        assert!(ast.eq_ignoring_spans(&expr.1), "Expression '{}' translated into incorrect AST", expr.0);
     }
 }
 
@@ -300,6 +445,56 @@ fn test_boolean_literals() {
     }
 }
 
+ast_test!(
+    test_null_literal,
+    "if NULL = null then null else 1",
+    if_expr(func("=", vec![lit_null(), lit_null()]), lit_null(), lit_int(1))
+);
+
+ast_test!(
+    test_date_literals,
+    "date '2024-01-31' + interval '1 month' > #2024-02-01 12:30#",
+    func(
+        ">",
+        vec![
+            func(
+                "+",
+                vec![
+                    lit(LiteralValue::DateValue(NaiveDate::from_ymd_opt(2024, 1, 31).unwrap())),
+                    lit(LiteralValue::IntervalValue(Interval::new(1, 0, 0)))
+                ]
+            ),
+            lit(LiteralValue::TimestampValue(
+                NaiveDate::from_ymd_opt(2024, 2, 1).unwrap().and_hms_opt(12, 30, 0).unwrap()
+            ))
+        ]
+    )
+);
+
+ast_test!(
+    test_date_named_field,
+    "date + dates",
+    func("+", vec![field_ref("date"), field_ref("dates")])
+);
+
+ast_test!(
+    test_is_null,
+    "field1 is null",
+    func("is null", vec![field_ref("field1")])
+);
+
+ast_test!(
+    test_is_not_null_of_sum,
+    "field1 + 1 IS  NOT NULL and nullable_field",
+    func(
+        "and",
+        vec![
+            func("is not null", vec![func("+", vec![field_ref("field1"), lit_int(1)])]),
+            field_ref("nullable_field")
+        ]
+    )
+);
+
 #[test]
 fn test_case_expressions() {
     let valid_expressions = vec![
@@ -453,3 +648,39 @@ fn test_expressions_with_partition_and_where() {
         );
     }
 }
+
+#[test]
+fn test_source_spans() {
+    let input = "field1 +\n  foo(2) [where x = 1]";
+    let ast = parse(input).unwrap();
+
+    let span = ast.span();
+    assert_eq!((span.start, span.end), (0, input.len()));
+    assert_eq!((span.start_line, span.start_column, span.end_line, span.end_column), (1, 1, 2, 23));
+
+    if let Expression::Function { params, .. } = &ast {
+        assert_eq!(&input[params[0].span().start..params[0].span().end], "field1");
+        assert_eq!(&input[params[1].span().start..params[1].span().end], "foo(2) [where x = 1]");
+        if let Expression::ModifierExpression { expression, where_modifier, .. } = &params[1] {
+            let function_span = expression.span();
+            assert_eq!((function_span.start_line, function_span.start_column), (2, 3));
+            assert_eq!(&input[function_span.start..function_span.end], "foo(2)");
+            let where_span = where_modifier.as_ref().unwrap().span;
+            assert_eq!(&input[where_span.start..where_span.end], "[where x = 1]");
+        } else {
+            panic!("Expected a modifier expression, got {:?}", params[1]);
+        }
+    } else {
+        panic!("Expected a function, got {:?}", ast);
+    }
+}
+
+#[test]
+fn test_spans_take_part_in_equality() {
+    let compact = parse("field1+foo(2)").unwrap();
+    let spaced = parse("field1 +  foo( 2 )").unwrap();
+    assert_ne!(compact, spaced);
+    assert_ne!(compact.span(), spaced.span());
+    assert!(compact.eq_ignoring_spans(&spaced));
+    assert!(!compact.eq_ignoring_spans(&parse("field1 + foo(3)").unwrap()));
+}
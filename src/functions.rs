@@ -0,0 +1,355 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+use crate::aggregate;
+use crate::ast::{Expression, LiteralValue, Span, ValueType};
+use crate::datetime;
+use crate::error::{Error, ErrorKind};
+use crate::stdlib;
+
+/// Type accepted for a function argument or produced by a function.
+///
+/// NULL is accepted for every argument type, what happens to it is decided by the
+/// [`NullHandling`] of the signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamType {
+    Any,
+    /// Integer, decimal or floating point number.
+    Numeric,
+    /// Date or timestamp.
+    DateTime,
+    Exact(ValueType),
+}
+
+impl ParamType {
+    pub fn accepts(&self, value_type: ValueType) -> bool {
+        match self {
+            _ if value_type == ValueType::Null => true,
+            ParamType::Any => true,
+            ParamType::Numeric => matches!(value_type, ValueType::Number | ValueType::Integer | ValueType::Decimal),
+            ParamType::DateTime => matches!(value_type, ValueType::Date | ValueType::Timestamp),
+            ParamType::Exact(expected) => *expected == value_type,
+        }
+    }
+
+    //type reported as expected when an argument is not accepted
+    pub(crate) fn expected_type(&self) -> ValueType {
+        match self {
+            ParamType::Any | ParamType::Numeric => ValueType::Number,
+            ParamType::DateTime => ValueType::Timestamp,
+            ParamType::Exact(expected) => *expected,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NullHandling {
+    /// A NULL argument makes the result NULL without calling the function, like SQL operators.
+    #[default]
+    Propagate,
+    /// The function gets NULL arguments as they are, e.g. `coalesce`.
+    PassThrough,
+}
+
+/// Number and types of the arguments of a function and the type of its result.
+///
+/// ```
+/// use analytical_expression_parser::ast::ValueType;
+/// use analytical_expression_parser::{ParamType, Signature};
+///
+/// //round(number [, digits])
+/// let signature = Signature::new(vec![ParamType::Numeric], ParamType::Numeric)
+///     .with_optional(ParamType::Exact(ValueType::Integer));
+/// assert_eq!((signature.min_args(), signature.max_args()), (1, Some(2)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature {
+    params: Vec<ParamType>,
+    required: usize,
+    variadic: Option<ParamType>,
+    return_type: ParamType,
+    null_handling: NullHandling,
+}
+
+impl Signature {
+    /// Signature with the given required arguments.
+    pub fn new(params: Vec<ParamType>, return_type: ParamType) -> Self {
+        Signature {
+            required: params.len(),
+            params,
+            variadic: None,
+            return_type,
+            null_handling: NullHandling::default(),
+        }
+    }
+
+    /// Adds an optional argument after the ones already declared.
+    pub fn with_optional(mut self, param: ParamType) -> Self {
+        self.params.push(param);
+        self
+    }
+
+    /// Accepts any number of additional arguments of the given type after the declared ones.
+    pub fn with_variadic(mut self, param: ParamType) -> Self {
+        self.variadic = Some(param);
+        self
+    }
+
+    pub fn with_null_handling(mut self, null_handling: NullHandling) -> Self {
+        self.null_handling = null_handling;
+        self
+    }
+
+    pub fn min_args(&self) -> usize {
+        self.required
+    }
+
+    /// Maximum number of arguments, `None` for variadic functions.
+    pub fn max_args(&self) -> Option<usize> {
+        match self.variadic {
+            Some(_) => None,
+            None => Some(self.params.len()),
+        }
+    }
+
+    /// Type of the argument at `index`, `None` if the function does not take that many arguments.
+    pub fn param_type(&self, index: usize) -> Option<ParamType> {
+        self.params.get(index).copied().or(self.variadic)
+    }
+
+    pub fn return_type(&self) -> ParamType {
+        self.return_type
+    }
+
+    pub fn null_handling(&self) -> NullHandling {
+        self.null_handling
+    }
+
+    pub(crate) fn check_arity(&self, function_name: &str, actual: usize, span: Span) -> Result<(), Error> {
+        let too_many = self.max_args().is_some_and(|max| actual > max);
+        if actual < self.min_args() || too_many {
+            return Err(Error::new(
+                ErrorKind::ArityMismatch {
+                    function_name: function_name.to_string(),
+                    expected_min: self.min_args(),
+                    expected_max: self.max_args(),
+                    actual,
+                },
+                span,
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Implementation of a scalar function. Arguments are already checked against the signature,
+/// errors are reported at the function call.
+pub type ScalarFunction = dyn Fn(&[LiteralValue]) -> Result<LiteralValue, ErrorKind> + Send + Sync;
+
+/// A function registered in a [`FunctionRegistry`].
+pub struct FunctionDef {
+    name: String,
+    signature: Signature,
+    implementation: Box<ScalarFunction>,
+    conditional: Option<Conditional>,
+}
+
+impl FunctionDef {
+    /// Name the function was registered with, aliases resolve to the same definition.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    pub fn call(&self, args: &[LiteralValue]) -> Result<LiteralValue, ErrorKind> {
+        (self.implementation)(args)
+    }
+
+    pub(crate) fn conditional(&self) -> Option<Conditional> {
+        self.conditional
+    }
+}
+
+//built-in functions evaluating their arguments only when they are needed, like IF does with its
+//branches, so that `iif(qty = 0, 0, sales / qty)` does not divide by zero
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Conditional {
+    //iif(condition, then, else [, unknown])
+    Iif,
+    //coalesce and ifnull, the first argument that is not NULL
+    FirstNonNull,
+}
+
+impl fmt::Debug for FunctionDef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FunctionDef")
+            .field("name", &self.name)
+            .field("signature", &self.signature)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Named scalar functions callable from expressions, looked up case-insensitively.
+///
+/// ```
+/// use analytical_expression_parser::ast::{LiteralValue, ValueType};
+/// use analytical_expression_parser::{parse, Evaluator, FunctionRegistry, ParamType, Signature};
+/// use std::collections::HashMap;
+///
+/// let mut functions = FunctionRegistry::with_builtins();
+/// let signature = Signature::new(vec![ParamType::Exact(ValueType::String)], ParamType::Exact(ValueType::String));
+/// functions.register("shout", signature, |args| match &args[0] {
+///     LiteralValue::StringValue(s) => Ok(LiteralValue::StringValue(format!("{}!", s))),
+///     _ => unreachable!(),
+/// });
+/// functions.alias("yell", "shout").unwrap();
+///
+/// let evaluator = Evaluator::new(functions);
+/// let result = evaluator.evaluate(&parse("YELL(\"hi\")").unwrap(), &HashMap::new()).unwrap();
+/// assert_eq!(result, LiteralValue::StringValue("hi!".to_string()));
+/// ```
+#[derive(Clone, Default)]
+pub struct FunctionRegistry {
+    //keyed by lowercase name, aliases share the definition
+    functions: HashMap<String, Arc<FunctionDef>>,
+}
+
+impl FunctionRegistry {
+    /// Registry without any functions, operators are always available.
+    pub fn new() -> Self {
+        FunctionRegistry::default()
+    }
+
+    /// Registry with the functions built into the library.
+    pub fn with_builtins() -> Self {
+        FunctionRegistry::builtins().clone()
+    }
+
+    pub(crate) fn builtins() -> &'static FunctionRegistry {
+        static BUILTINS: OnceLock<FunctionRegistry> = OnceLock::new();
+        BUILTINS.get_or_init(|| {
+            let mut functions = FunctionRegistry::new();
+            stdlib::register_standard_functions(&mut functions);
+            datetime::register_date_functions(&mut functions);
+            functions
+        })
+    }
+
+    /// Registers a function, replacing any function or alias with the same name.
+    pub fn register<F>(&mut self, name: &str, signature: Signature, implementation: F) -> &mut Self
+    where
+        F: Fn(&[LiteralValue]) -> Result<LiteralValue, ErrorKind> + Send + Sync + 'static,
+    {
+        let function = FunctionDef {
+            name: name.to_string(),
+            signature,
+            implementation: Box::new(implementation),
+            conditional: None,
+        };
+        self.functions.insert(name.to_ascii_lowercase(), Arc::new(function));
+        self
+    }
+
+    //the implementation gets all the arguments when the function is called directly
+    pub(crate) fn register_conditional<F>(
+        &mut self,
+        name: &str,
+        signature: Signature,
+        conditional: Conditional,
+        implementation: F,
+    ) -> &mut Self
+    where
+        F: Fn(&[LiteralValue]) -> Result<LiteralValue, ErrorKind> + Send + Sync + 'static,
+    {
+        let function = FunctionDef {
+            name: name.to_string(),
+            signature,
+            implementation: Box::new(implementation),
+            conditional: Some(conditional),
+        };
+        self.functions.insert(name.to_ascii_lowercase(), Arc::new(function));
+        self
+    }
+
+    /// Makes a registered function callable under another name as well.
+    pub fn alias(&mut self, alias: &str, name: &str) -> Result<&mut Self, ErrorKind> {
+        let function =
+            self.functions
+                .get(&name.to_ascii_lowercase())
+                .cloned()
+                .ok_or_else(|| ErrorKind::UnknownFunction {
+                    function_name: name.to_string(),
+                })?;
+        self.functions.insert(alias.to_ascii_lowercase(), function);
+        Ok(self)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&FunctionDef> {
+        self.functions.get(&name.to_ascii_lowercase()).map(Arc::as_ref)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Checks that every function called in the expression is registered or an aggregate and gets
+    /// a valid number of arguments.
+    pub fn validate(&self, expression: &Expression) -> Result<(), Error> {
+        if let Expression::Function {
+            function_name,
+            params,
+            span,
+        } = expression
+        {
+            if let Some(expected) = aggregate::aggregate_arity(function_name) {
+                if params.len() != expected {
+                    return Err(Error::new(
+                        ErrorKind::ArityMismatch {
+                            function_name: function_name.clone(),
+                            expected_min: expected,
+                            expected_max: Some(expected),
+                            actual: params.len(),
+                        },
+                        *span,
+                    ));
+                }
+            } else if !is_operator(function_name) {
+                let function = self.get(function_name).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::UnknownFunction {
+                            function_name: function_name.clone(),
+                        },
+                        *span,
+                    )
+                })?;
+                function.signature.check_arity(function_name, params.len(), *span)?;
+            }
+        }
+        expression
+            .children()
+            .into_iter()
+            .try_for_each(|child| self.validate(child))
+    }
+}
+
+impl fmt::Debug for FunctionRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<&String> = self.functions.keys().collect();
+        names.sort();
+        f.debug_struct("FunctionRegistry").field("functions", &names).finish()
+    }
+}
+
+/// Operators are represented as `Function` nodes but are built into the evaluator.
+pub(crate) fn is_operator(function_name: &str) -> bool {
+    matches!(
+        function_name,
+        "+" | "-" | "*" | "/" | "=" | "!=" | "<" | "<=" | ">" | ">=" | "is null" | "is not null"
+    ) || ["and", "or", "not"]
+        .iter()
+        .any(|operator| function_name.eq_ignore_ascii_case(operator))
+}
@@ -1,178 +1,345 @@
-use pest::iterators::Pair;
-use pest::Parser;
+use pest::iterators::{Pair, Pairs};
 use pest_derive::Parser;
-use std::collections::HashMap;
+use std::iter::Peekable;
+
+pub use chrono::{NaiveDate, NaiveDateTime};
+pub use rust_decimal::Decimal;
 
 use crate::ast::*;
+use crate::bytecode::Program;
+pub use crate::aggregate::{evaluate_aggregate, evaluate_aggregate_with_filters, DashboardFilter};
+pub use crate::compile::{compile, CompiledExpression};
+pub use crate::error::{Error, ErrorKind, ValueTypeError};
+pub use crate::eval::{evaluate, evaluate_rows, EvalContext, Evaluator};
+pub use crate::functions::{FunctionDef, FunctionRegistry, NullHandling, ParamType, ScalarFunction, Signature};
+pub use crate::lod::{evaluate_grouped, GroupValue};
+pub use crate::parser::{parse, ParseError, Parser};
+pub use crate::schema::Schema;
+pub use crate::typecheck::{check_types, TypedExpression};
 
-mod ast;
+mod aggregate;
+pub mod ast;
+pub mod bytecode;
+mod compile;
+mod datetime;
+mod error;
+mod eval;
+mod functions;
+mod lod;
+mod parser;
+mod schema;
+pub mod sql;
+mod stdlib;
+mod typecheck;
+pub mod wasm;
 
 #[derive(Parser)]
 #[grammar = "expression.pest"]
 pub struct ExpressionParser;
 
-pub fn eval_expression(input: &str) -> Result<f64, String> {
-    let parsed = ExpressionParser::parse(Rule::expression_input, input)
-        .map_err(|e| format!("Parsing error: {:?}", e))?;
-
-    let expr = parsed.into_iter().next().unwrap();
-    let ast = dbg!(convert_to_ast(expr));
-    let numeric_expression = eval_ast(ast, &HashMap::new())?;
-    Ok(numeric_expression.into())
+pub fn eval_expression(input: &str) -> Result<f64, Error> {
+    let ast = parse(input)?;
+    let span = ast.span();
+    let program = Program::compile(&ast, &[] as &[&str]);
+    let value = program.evaluate(&[]).map_err(|e| e.with_source(input))?;
+    f64::try_from(value).map_err(|e| e.at(span).with_source(input))
 }
 
-pub fn convert_to_ast(expr: Pair<Rule>) -> Expression {
+/// Converts a pair of the grammar to its AST. Fails with `ErrorKind::InvalidLiteral` on date,
+/// timestamp and interval literals that the grammar accepts but do not exist, e.g. `#2024-02-30#`.
+pub fn convert_to_ast(expr: Pair<Rule>) -> Result<Expression, Error> {
+    //skip precedence levels that only wrap a single operand without recursing,
+    //which keeps the stack shallow for deeply nested expressions
+    let mut expr = expr;
+    while let Rule::or_operand
+    | Rule::and_operand
+    | Rule::not_operand
+    | Rule::comp_operand
+    | Rule::add_operand
+    | Rule::mul_operand
+    | Rule::filter_expr = expr.as_rule()
+    {
+        let mut children = expr.clone().into_inner();
+        match (children.next(), children.next()) {
+            (Some(only_child), None) => expr = only_child,
+            _ => break,
+        }
+    }
+
     match expr.as_rule() {
         Rule::or_operand | Rule::and_operand => {
+            let span = Span::from(expr.as_span());
             let mut child_pairs = expr.into_inner();
             let first_operand = child_pairs.next().unwrap();
             let operator = child_pairs.next();
             // if there is more than one child node, then this is a full expression with an operator
             if let Some(rule) = operator {
-                //collect all child nodes that are not or_operand or and_operand
-                let mut params: Vec<Expression> = vec![convert_to_ast(first_operand)];
-                let mut where_modifier: Option<WhereModifier> = None;
-                while let Some(child) = child_pairs.next() {
-                    let child_rule = child.as_rule();
-                    match child_rule {
-                        Rule::where_clause => {
-                            where_modifier = convert_to_where_modifier(child);
-                        }
-                        c if c != Rule::or_operand && c != Rule::and_operand => {
-                            params.push(convert_to_ast(child));
-                        }
-                        _ => {}
+                //collect all operands, skipping the operators between them
+                let mut params: Vec<Expression> = vec![convert_to_ast(first_operand)?];
+                for child in child_pairs {
+                    match child.as_rule() {
+                        Rule::or_op | Rule::and_op => {}
+                        _ => params.push(convert_to_ast(child)?),
                     }
                 }
 
-                let func = Expression::Function {
+                Ok(Expression::Function {
                     function_name: rule.as_str().to_string(),
                     params,
-                };
-
-                if let Some(_) = where_modifier {
-                    Expression::ModifierExpression {
-                        expression: Box::new(func),
-                        where_modifier,
-                        group_by_modifier: None,
-                    }
-                } else {
-                    func
-                }
+                    span,
+                })
             } else {
                 //otherwise, just return the first operand
                 convert_to_ast(first_operand)
             }
         }
         Rule::comp_operand | Rule::add_operand | Rule::mul_operand => {
-            let mut child_pairs = expr.into_inner();
-            let first_operand = child_pairs.next().unwrap();
-            let operator = child_pairs.next();
-            // if there is more than one child node, then this is a full expression with an operator
-            if let Some(op_rule) = operator {
-                //build an expression tree from all operands in a left-associative way
-                //build first function node
-                let mut left = Expression::Function {
-                    function_name: op_rule.as_str().to_string(),
-                    params: vec![
-                        convert_to_ast(first_operand),
-                        convert_to_ast(child_pairs.next().unwrap()),
-                    ],
-                };
-                //append all other operands to the tree
-                while let Some(op_rule) = child_pairs.next() {
-                    left = Expression::Function {
-                        function_name: op_rule.as_str().to_string(),
-                        params: vec![left, convert_to_ast(child_pairs.next().unwrap())],
-                    };
+            let mut child_pairs = expr.into_inner().peekable();
+            //build an expression tree from all operands in a left-associative way
+            let mut left = convert_operand(&mut child_pairs)?;
+            while let Some(op_rule) = child_pairs.next() {
+                match op_rule.as_rule() {
+                    //IS [NOT] NULL is a postfix operator ending the comparison chain
+                    Rule::is_null_op | Rule::is_not_null_op => {
+                        let function_name = if let Rule::is_null_op = op_rule.as_rule() {
+                            "is null"
+                        } else {
+                            "is not null"
+                        };
+                        left = Expression::Function {
+                            function_name: function_name.to_string(),
+                            span: left.span().to(Span::from(op_rule.as_span())),
+                            params: vec![left],
+                        };
+                    }
+                    _ => {
+                        let right = convert_operand(&mut child_pairs)?;
+                        left = Expression::Function {
+                            function_name: op_rule.as_str().to_string(),
+                            span: left.span().to(right.span()),
+                            params: vec![left, right],
+                        };
+                    }
                 }
-
-                left
-            } else {
-                convert_to_ast(first_operand)
             }
+            Ok(left)
         }
         Rule::not_operand => {
-            let mut child_pairs = expr.into_inner();
-            let first_node = child_pairs.next().unwrap();
-            if let Rule::not_op = first_node.as_rule() {
-                let second_node = child_pairs.next().unwrap();
+            let mut child_pairs = expr.into_inner().peekable();
+            if let Some(Rule::not_op) = child_pairs.peek().map(|p| p.as_rule()) {
+                let not_node = child_pairs.next().unwrap();
+                let operand = convert_to_ast(child_pairs.next().unwrap())?;
                 let func = Expression::Function {
-                    function_name: first_node.as_str().to_string(),
-                    params: vec![convert_to_ast(second_node)],
+                    function_name: not_node.as_str().to_string(),
+                    span: Span::from(not_node.as_span()).to(operand.span()),
+                    params: vec![operand],
                 };
-
-                if let Some(where_clause) = child_pairs.next() {
-                    let where_modifier = convert_to_where_modifier(where_clause);
-                    Expression::ModifierExpression {
-                        expression: Box::new(func),
-                        where_modifier,
-                        group_by_modifier: None,
-                    }
-                } else {
-                    func
-                }
+                attach_modifiers(func, &mut child_pairs)
             } else {
-                convert_to_ast(first_node)
+                convert_operand(&mut child_pairs)
             }
         }
         Rule::function_call => {
+            let span = Span::from(expr.as_span());
             let mut child_pairs = expr.into_inner();
             let function = child_pairs.next().unwrap();
-            let params = child_pairs.next().unwrap();
-            Expression::Function {
-                function_name: function.as_str().to_string(),
-                params: params.into_inner().map(convert_to_ast).collect(),
+            let mut arguments = child_pairs.next().unwrap().into_inner().peekable();
+            let mut params: Vec<Expression> = vec![];
+            while arguments.peek().is_some() {
+                params.push(convert_operand(&mut arguments)?);
             }
+            Ok(Expression::Function {
+                function_name: function.as_str().to_string(),
+                params,
+                span,
+            })
         }
         Rule::if_expr => {
-            let mut children = expr.into_inner();
-            Expression::IfExpression {
-                condition: Box::new(convert_to_ast(children.next().unwrap())),
-                result: Box::new(convert_to_ast(children.next().unwrap())),
-                else_result: Box::new(convert_to_ast(children.next().unwrap())),
+            let span = Span::from(expr.as_span());
+            let mut children = expr.into_inner().peekable();
+            Ok(Expression::IfExpression {
+                condition: Box::new(convert_operand(&mut children)?),
+                result: Box::new(convert_operand(&mut children)?),
+                else_result: Box::new(convert_operand(&mut children)?),
+                span,
+            })
+        }
+        Rule::case_expr => {
+            let span = Span::from(expr.as_span());
+            let mut children = expr.into_inner().peekable();
+            let mut cases: Vec<CaseBranch> = vec![];
+            let mut else_result: Option<Box<Expression>> = None;
+            while let Some(child) = children.peek() {
+                match child.as_rule() {
+                    Rule::when_expr => {
+                        let when_node = children.next().unwrap();
+                        let branch_span = Span::from(when_node.as_span());
+                        let mut when_children = when_node.into_inner().peekable();
+                        cases.push(CaseBranch {
+                            condition: convert_operand(&mut when_children)?,
+                            result: convert_operand(&mut when_children)?,
+                            span: branch_span,
+                        });
+                    }
+                    //anything after the WHEN branches is the ELSE expression
+                    _ => else_result = Some(Box::new(convert_operand(&mut children)?)),
+                }
             }
+            Ok(Expression::CaseExpression {
+                cases,
+                else_result,
+                span,
+            })
         }
-        Rule::string_literal => Expression::Literal {
-            value: LiteralValue::StringValue(expr.as_str().to_string()),
-        },
-        Rule::integer => Expression::Literal {
-            value: LiteralValue::NumberValue(expr.as_str().parse::<f64>().unwrap()),
-        },
-        Rule::float => Expression::Literal {
-            value: LiteralValue::NumberValue(expr.as_str().parse::<f64>().unwrap()),
-        },
-        Rule::boolean_literal => Expression::Literal {
-            value: LiteralValue::BooleanValue(expr.as_str().parse::<bool>().unwrap()),
-        },
-        Rule::field_reference => Expression::FieldReference {
+        Rule::filter_expr => convert_operand(&mut expr.into_inner().peekable()),
+        Rule::string_literal => Ok(Expression::Literal {
+            value: LiteralValue::StringValue(unescape_string_literal(expr.as_str())),
+            span: Span::from(expr.as_span()),
+        }),
+        Rule::integer | Rule::float => Ok(Expression::Literal {
+            value: convert_number_literal(expr.as_str()),
+            span: Span::from(expr.as_span()),
+        }),
+        //the grammar accepts dates that do not exist, such as `#2024-02-30#`
+        Rule::date_literal | Rule::timestamp_literal | Rule::interval_literal => Ok(Expression::Literal {
+            span: Span::from(expr.as_span()),
+            value: datetime::convert_temporal_literal(expr)?,
+        }),
+        Rule::null_literal => Ok(Expression::Literal {
+            value: LiteralValue::NullValue,
+            span: Span::from(expr.as_span()),
+        }),
+        Rule::boolean_literal => Ok(Expression::Literal {
+            value: LiteralValue::BooleanValue(expr.as_str().eq_ignore_ascii_case("true")),
+            span: Span::from(expr.as_span()),
+        }),
+        Rule::field_reference => Ok(Expression::FieldReference {
             field_id: expr.as_str().to_string(),
-        },
+            span: Span::from(expr.as_span()),
+        }),
         _ => unreachable!(),
     }
 }
 
-fn convert_to_group_by_modifier(child: Pair<Rule>) -> Option<GroupByModifier> {
-    // match child.as_rule() {
-    //     Rule::window_clause => {
-    //         let mut child_pairs = child.into_inner();
-    //         let first_node = child_pairs.next().unwrap();
-    //         if let Rule::window_clause = first_node.as_rule() {
-    //             let second_node = child_pairs.next().unwrap();
-    //             GroupByModifier::Window {
-    //                 expression: convert_to_ast(second_node),
-    //             }
-    //         } else {
-    //             unreachable!()
-    //         }
-    //     }
-    //     _ => unreachable!(),
-    // }
-    unimplemented!()
+//Strips the surrounding quotes and resolves the \" and \\ escape sequences
+fn unescape_string_literal(literal: &str) -> String {
+    let mut result = String::with_capacity(literal.len());
+    let mut chars = literal[1..literal.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => result.extend(chars.next()),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+//Converts a number literal to an integer, exact decimal or float, e.g. `42`, `0.1` and `1e6`
+fn convert_number_literal(text: &str) -> LiteralValue {
+    let exact = if text.contains(['e', 'E']) {
+        None
+    } else if text.contains('.') {
+        Decimal::from_str_exact(text).ok().map(LiteralValue::DecimalValue)
+    } else {
+        text.parse::<i64>()
+            .map(LiteralValue::IntegerValue)
+            .or_else(|_| Decimal::from_str_exact(text).map(LiteralValue::DecimalValue))
+            .ok()
+    };
+    exact.unwrap_or_else(|| LiteralValue::NumberValue(text.parse::<f64>().unwrap()))
+}
+
+//Converts the next operand from a list of sibling nodes, together with the WHERE and GROUP BY
+//clauses that follow it
+pub(crate) fn convert_operand(pairs: &mut Peekable<Pairs<Rule>>) -> Result<Expression, Error> {
+    let operand = convert_to_ast(pairs.next().unwrap())?;
+    attach_modifiers(operand, pairs)
+}
+
+//Wraps an expression into a ModifierExpression for every WHERE or GROUP BY clause that follows it.
+//Clauses fill the empty slots of the current modifier first, so `f(x) [where ...] [group by ...]`
+//results in a single node with both modifiers.
+fn attach_modifiers(expression: Expression, pairs: &mut Peekable<Pairs<Rule>>) -> Result<Expression, Error> {
+    let mut expression = expression;
+    while let Some(next) = pairs.peek() {
+        let (new_where, new_group_by) = match next.as_rule() {
+            Rule::where_clause => (convert_to_where_modifier(pairs.next().unwrap())?, None),
+            Rule::group_by_clause => (None, convert_to_group_by_modifier(pairs.next().unwrap())?),
+            _ => break,
+        };
+
+        let clause_span = match (&new_where, &new_group_by) {
+            (Some(w), _) => w.span,
+            (_, Some(g)) => g.span,
+            _ => unreachable!(),
+        };
+
+        match &mut expression {
+            Expression::ModifierExpression {
+                where_modifier,
+                group_by_modifier,
+                span,
+                ..
+            } if (new_where.is_none() || where_modifier.is_none())
+                && (new_group_by.is_none() || group_by_modifier.is_none()) =>
+            {
+                if where_modifier.is_none() {
+                    *where_modifier = new_where;
+                }
+                if group_by_modifier.is_none() {
+                    *group_by_modifier = new_group_by;
+                }
+                *span = span.to(clause_span);
+            }
+            _ => {
+                expression = Expression::ModifierExpression {
+                    span: expression.span().to(clause_span),
+                    expression: Box::new(expression),
+                    where_modifier: new_where,
+                    group_by_modifier: new_group_by,
+                }
+            }
+        }
+    }
+    Ok(expression)
 }
 
-fn convert_to_where_modifier(where_clause_node: Pair<Rule>) -> Option<WhereModifier> {
+fn convert_to_group_by_modifier(group_by_clause_node: Pair<Rule>) -> Result<Option<GroupByModifier>, Error> {
+    if let Rule::group_by_clause = group_by_clause_node.as_rule() {
+        let span = Span::from(group_by_clause_node.as_span());
+        let mut groups: Vec<GroupReference> = vec![];
+        let mut group_context: Option<GroupByContext> = None;
+        for node in group_by_clause_node.into_inner() {
+            match node.as_rule() {
+                Rule::all_groups => {
+                    group_context = Some(all_groups());
+                }
+                Rule::group_reference => {
+                    //an index too large for usize can never point to an existing query group,
+                    //so it is kept as an out of range value instead of failing the conversion
+                    let span = Span::from(node.as_span());
+                    let index = node.into_inner().as_str().parse::<usize>().unwrap_or(usize::MAX);
+                    groups.push(GroupReference::QueryGroup { index, span });
+                }
+                Rule::field_reference => {
+                    groups.push(field_group(convert_to_ast(node)?));
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        Ok(Some(GroupByModifier::new(
+            group_context.unwrap_or_else(|| included_groups(groups)),
+            span,
+        )))
+    } else {
+        Ok(None)
+    }
+}
+
+fn convert_to_where_modifier(where_clause_node: Pair<Rule>) -> Result<Option<WhereModifier>, Error> {
     if let Rule::where_clause = where_clause_node.as_rule() {
+        let span = Span::from(where_clause_node.as_span());
         let mut child_pairs = where_clause_node.into_inner();
         let first_node = child_pairs.next().unwrap();
         //first node can be an allowed or ignored filters list, or a filter expression
@@ -184,7 +351,7 @@ fn convert_to_where_modifier(where_clause_node: Pair<Rule>) -> Option<WhereModif
                     allowed_filters: first_node
                         .into_inner()
                         .map(|node| convert_to_ast(node))
-                        .collect(),
+                        .collect::<Result<_, _>>()?,
                 });
             }
             Rule::ignore_field_filters => {
@@ -193,174 +360,39 @@ fn convert_to_where_modifier(where_clause_node: Pair<Rule>) -> Option<WhereModif
                 if let Rule::ignore_all_filters = first_child.as_rule() {
                     filter_context = Some(FilterContext::AllFiltersIgnored());
                 } else {
-                    let mut ignored_filters = vec![convert_to_ast(first_child)];
-                    children.for_each(|f| ignored_filters.push(convert_to_ast(f)));
+                    let mut ignored_filters = vec![convert_to_ast(first_child)?];
+                    for f in children {
+                        ignored_filters.push(convert_to_ast(f)?);
+                    }
                     filter_context = Some(FilterContext::IgnoredFilters { ignored_filters });
                 }
             }
             Rule::filter_expr => {
-                additional_filters.push(convert_to_ast(first_node));
+                additional_filters.push(convert_to_ast(first_node)?);
             }
             _ => unreachable!(),
         }
 
-        while let Some(next_node) = child_pairs.next() {
-            additional_filters.push(convert_to_ast(next_node));
+        for next_node in child_pairs {
+            additional_filters.push(convert_to_ast(next_node)?);
         }
 
-        Some(WhereModifier {
+        Ok(Some(WhereModifier {
             filter_context,
             additional_filters,
-        })
+            span,
+        }))
     } else {
-        None
-    }
-}
-
-
-//Convert expression AST to WebAssembly Text format
-// fn convert_ast_to_wat(ast: Expression, ctx: &HashMap<String, LiteralValue>) -> Result<String, String> {
-//     match ast {
-//         Expression::Literal { value, .. } => match value {
-//             LiteralValue::StringValue(s) => Ok(format!("(i32.const {})", s)),
-//             LiteralValue::NumberValue(n) => Ok(format!("(i32.const {})", n)),
-//             LiteralValue::BooleanValue(b) => Ok(format!("(i32.const {})", b)),
-//         },
-//         Expression::FieldReference { field_id, .. } => {
-//             if let Some(field_value) = ctx.get(&field_id) {
-//                 convert_ast_to_wat(Expression::Literal { value: field_value.clone() }, ctx)
-//             } else {
-//                 Err(format!("Field {} not found in context", field_id))
-//             }
-//         }
-//         Expression::Function {
-//             function_name,
-//             params,
-//             ..
-//         } => {
-//             let params: Result<Vec<String>, String> =
-//                 params.into_iter().map(|p| convert_ast_to_wat(p, ctx)).collect();
-//             let params = params?;
-//             match function_name.as_str() {
-//                 "+" => {
-//                     let params: Vec<f64> = params.into_iter().map(|p| p.parse::<f64>().unwrap()).collect();
-//                     Ok(format!("(i32.add {} {})", params[0], params[1]))
-//                 }
-//                 "-" => {
-//                     let params: Vec<f64> = params.into_iter().map(|p| p.parse::<f64>().unwrap()).collect();
-//                     Ok(format!("(i32.sub {} {})", params[0], params[1]))
-//                 }
-//                 "*" => {
-//                     let params: Vec<f64> = params.into_iter().map(|p| p.parse::<f64>().unwrap()).collect();
-//                     Ok(format!("(i32.mul {} {})", params[0], params[1]))
-//                 }
-//                 "/" => {
-//                     let params: Vec<f64> = params.into_iter().map(|p| p.parse::<f64>().unwrap()).collect();
-//                     Ok(format!("(i32.div_s {} {})", params[0], params[1]))
-//                 }
-//                 "==" => {
-//                     let params: Vec<f64> = params.into_iter().map(|p| p.parse::<f64>().unwrap()).collect();
-//                     Ok(format!("(i32.eq {} {})", params[0], params[1]))
-//                 }
-//                 "!=" => {
-//                     let params: Vec<f64> = params.into_iter().map(|p| p.parse::<f64>().unwrap()).collect();
-//                     Ok(format!("(i32.ne {} {})", params[0], params[1]))
-//                 }
-//                 ">" => {
-//                     let params: Vec<f64> = params.into_iter().map(|p| p.parse::<f64>().unwrap()).collect();
-//                     Ok(format!("(i32.gt_s {} {})", params[0], params[1]))
-//                 }
-//                 "<" => {
-//                     let params: Vec<f64> = params.into_iter().map(|p| p.parse::<f64>().unwrap()).collect();
-//                     Ok(format!("(i32.lt_s {} {})", params[0], params[1]))
-//                 }
-//                 ">=" => {
-//                     let params: Vec<f64> = params.into_iter().map(|p| p.parse::<f64>().unwrap()).collect();
-//                     Ok(format!("(i32.ge_s {} {})", params[0], params[1]))
-//                 }
-//                 "<=" => {
-//                     let params: Vec<f64> = params.into_iter().map(|p| p.parse::<f64>().unwrap()).collect();
-//                     Ok(format!("(i32.le_s {} {})", params[0], params[1]))
-//                 }
-//                 _ => {}
-//             }
-//         }
-//         _ => {}
-//     }
-// }
-
-
-//eval simple arithmetic expressions
-fn eval_ast(ast: Expression, ctx: &HashMap<String, LiteralValue>) -> Result<LiteralValue, String> {
-    match ast {
-        Expression::Literal { value, .. } => Ok(value),
-        Expression::FieldReference { field_id, .. } => {
-            if let Some(field_value) = ctx.get(&field_id) {
-                Ok(field_value.clone())
-            } else {
-                Err(format!("Field {} not found in context", field_id))
-            }
-        }
-        Expression::Function {
-            function_name,
-            params,
-            ..
-        } => {
-            let params: Result<Vec<LiteralValue>, String> =
-                params.into_iter().map(|p| eval_ast(p, ctx)).collect();
-            let params = params?;
-            match function_name.as_str() {
-                "+" => {
-                    let params: Vec<f64> = params.into_iter().map(|p| p.into()).collect();
-                    Ok(LiteralValue::NumberValue(params[0] + params[1]))
-                }
-                "-" => {
-                    let params: Vec<f64> = params.into_iter().map(|p| p.into()).collect();
-                    Ok(LiteralValue::NumberValue(params[0] - params[1]))
-                }
-                "*" => {
-                    let params: Vec<f64> = params.into_iter().map(|p| p.into()).collect();
-                    Ok(LiteralValue::NumberValue(params[0] * params[1]))
-                }
-                "/" => {
-                    let params: Vec<f64> = params.into_iter().map(|p| p.into()).collect();
-                    Ok(LiteralValue::NumberValue(params[0] / params[1]))
-                }
-                //todo add comparison and boolean funcs
-                f => Err(format!("Unknown function {}", f)),
-            }
-        }
-        Expression::IfExpression {
-            condition,
-            result,
-            else_result,
-            ..
-        } => {
-            if let Ok(LiteralValue::BooleanValue(true)) = eval_ast(*condition, ctx) {
-                eval_ast(*result, ctx)
-            } else {
-                eval_ast(*else_result, ctx)
-            }
-        }
-        Expression::CaseExpression {
-            cases, else_result, ..
-        } => {
-            for case in cases {
-                if let Ok(LiteralValue::BooleanValue(true)) = eval_ast(case.condition, ctx) {
-                    return eval_ast(case.result, ctx);
-                }
-            }
-            eval_ast(*else_result, ctx)
-        }
-        Expression::ModifierExpression { .. } => {
-            unimplemented!()
-        }
+        Ok(None)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use super::*;
+    use crate::eval::eval_ast;
 
     #[test]
     fn test_arithmetic_parsing() {
@@ -434,6 +466,451 @@ mod tests {
             );
         }
     }
+
+    fn eval_with_nulls(input: &str) -> Result<LiteralValue, Error> {
+        let ctx = HashMap::from([
+            ("name".to_string(), LiteralValue::StringValue("  Opelika, AL ".to_string())),
+            ("missing".to_string(), LiteralValue::NullValue),
+        ]);
+        eval_ast(&parse(input)?, &ctx)
+    }
+
+    #[test]
+    fn test_math_functions() {
+        let test_cases = vec![
+            ("abs(-3)", LiteralValue::IntegerValue(3)),
+            ("ABS(-2.50)", LiteralValue::DecimalValue(Decimal::new(25, 1))),
+            ("round(2.345, 2)", LiteralValue::DecimalValue(Decimal::new(235, 2))),
+            ("round(2.5)", LiteralValue::DecimalValue(Decimal::from(3))),
+            ("round(1250, -2)", LiteralValue::IntegerValue(1300)),
+            ("round(1.5e0)", LiteralValue::NumberValue(2.0)),
+            ("round(1.5, -30)", LiteralValue::DecimalValue(Decimal::ZERO)),
+            ("round(15, -30)", LiteralValue::IntegerValue(0)),
+            ("round(1.25, 30)", LiteralValue::DecimalValue(Decimal::new(125, 2))),
+            ("round(15, 30)", LiteralValue::IntegerValue(15)),
+            ("round(1.5e0, -400)", LiteralValue::NumberValue(0.0)),
+            ("round(1.5e0, 400)", LiteralValue::NumberValue(1.5)),
+            ("round(1.5e300, -301)", LiteralValue::NumberValue(0.0)),
+            ("ceil(1.2)", LiteralValue::DecimalValue(Decimal::from(2))),
+            ("floor(-1.2e0)", LiteralValue::NumberValue(-2.0)),
+            ("sqrt(16)", LiteralValue::NumberValue(4.0)),
+            ("pow(2, 10)", LiteralValue::IntegerValue(1024)),
+            ("pow(4, 0.5)", LiteralValue::NumberValue(2.0)),
+            ("ln(1)", LiteralValue::NumberValue(0.0)),
+            ("log(1000)", LiteralValue::NumberValue(3.0)),
+            ("log(8, 2)", LiteralValue::NumberValue(3.0)),
+            ("exp(0)", LiteralValue::NumberValue(1.0)),
+            ("sign(-0.5)", LiteralValue::IntegerValue(-1)),
+            ("mod(-7, 3)", LiteralValue::IntegerValue(-1)),
+            ("mod(7.5, 2)", LiteralValue::DecimalValue(Decimal::new(15, 1))),
+            ("abs(missing)", LiteralValue::NullValue),
+        ];
+        for (input, expected_output) in test_cases {
+            assert_eq!(eval_with_nulls(input).unwrap(), expected_output, "Expression: {}", input);
+        }
+
+        assert_eq!(eval_with_nulls("mod(1, 0)").unwrap_err().kind(), &ErrorKind::DivisionByZero);
+        assert!(matches!(eval_with_nulls("sqrt(-1)").unwrap_err().kind(), ErrorKind::InvalidArgument { .. }));
+        assert_eq!(eval_with_nulls("pow(10, 30)").unwrap_err().kind(), &ErrorKind::NumericOverflow);
+        assert_eq!(
+            eval_with_nulls("round(79000000000000000000000000000, -28)").unwrap_err().kind(),
+            &ErrorKind::NumericOverflow
+        );
+        let error = eval_with_nulls("abs(\"a\")").unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::TypeMismatch {
+                expected: ValueType::Number,
+                actual: ValueType::String
+            }
+        );
+        assert_eq!((error.span().start, error.span().end), (4, 7));
+    }
+
+    #[test]
+    fn test_string_functions() {
+        let string = |value: &str| LiteralValue::StringValue(value.to_string());
+        let test_cases = vec![
+            ("len(\"Zürich\")", LiteralValue::IntegerValue(6)),
+            ("upper(\"abc\")", string("ABC")),
+            ("lower(\"ABC\")", string("abc")),
+            ("trim(name)", string("Opelika, AL")),
+            ("substr(\"Zürich\", 2, 3)", string("üri")),
+            ("substr(\"abc\", 2)", string("bc")),
+            ("substr(\"abc\", 10)", string("")),
+            ("left(\"abc\", 2)", string("ab")),
+            ("right(\"abc\", 5)", string("abc")),
+            ("contains(name, \"lika\")", LiteralValue::BooleanValue(true)),
+            ("startswith(name, \"Ope\")", LiteralValue::BooleanValue(false)),
+            ("replace(\"a-b-c\", \"-\", \"+\")", string("a+b+c")),
+            ("concat(\"a\", 1, missing, true, #2024-01-31#)", string("a1true2024-01-31")),
+            ("split(trim(name), \", \", 2)", string("AL")),
+            ("split(\"a/b/c\", \"/\", -1)", string("c")),
+            ("split(\"a/b/c\", \"/\", 4)", string("")),
+            ("upper(missing)", LiteralValue::NullValue),
+        ];
+        for (input, expected_output) in test_cases {
+            assert_eq!(eval_with_nulls(input).unwrap(), expected_output, "Expression: {}", input);
+        }
+
+        assert!(matches!(eval_with_nulls("substr(\"abc\", 0)").unwrap_err().kind(), ErrorKind::InvalidArgument { .. }));
+        assert!(matches!(eval_with_nulls("left(\"abc\", -1)").unwrap_err().kind(), ErrorKind::InvalidArgument { .. }));
+        assert!(matches!(eval_with_nulls("concat()").unwrap_err().kind(), ErrorKind::ArityMismatch { .. }));
+    }
+
+    #[test]
+    fn test_conditional_and_conversion_functions() {
+        let test_cases = vec![
+            ("coalesce(missing, null, 2, 3)", LiteralValue::IntegerValue(2)),
+            ("coalesce(missing)", LiteralValue::NullValue),
+            ("ifnull(missing, \"n/a\")", LiteralValue::StringValue("n/a".to_string())),
+            ("nullif(1, 1.0)", LiteralValue::NullValue),
+            ("nullif(1, 2)", LiteralValue::IntegerValue(1)),
+            ("iif(1 < 2, \"yes\", \"no\")", LiteralValue::StringValue("yes".to_string())),
+            ("iif(missing > 1, 1, 2, 3)", LiteralValue::IntegerValue(3)),
+            ("iif(missing > 1, 1, 2)", LiteralValue::NullValue),
+            //only the arguments that decide the result are evaluated
+            ("iif(true, 1, 1 / 0)", LiteralValue::IntegerValue(1)),
+            ("iif(false, 1 / 0, 2)", LiteralValue::IntegerValue(2)),
+            ("iif(missing > 1, 1 / 0, 1 / 0, 3)", LiteralValue::IntegerValue(3)),
+            ("coalesce(1, 1 / 0)", LiteralValue::IntegerValue(1)),
+            ("ifnull(2, 1 / 0)", LiteralValue::IntegerValue(2)),
+            ("zn(missing) + 1", LiteralValue::IntegerValue(1)),
+            ("zn(2.5)", LiteralValue::DecimalValue(Decimal::new(25, 1))),
+            ("str(1.50)", LiteralValue::StringValue("1.50".to_string())),
+            ("str(interval '1 month 90 seconds')", LiteralValue::StringValue("1 months 90 seconds".to_string())),
+            ("int(\" 42 \")", LiteralValue::IntegerValue(42)),
+            ("int(-2.7)", LiteralValue::IntegerValue(-2)),
+            ("int(\"4.9\")", LiteralValue::IntegerValue(4)),
+            ("int(\"abc\")", LiteralValue::NullValue),
+            ("int(true)", LiteralValue::IntegerValue(1)),
+            ("float(\"1.5\")", LiteralValue::NumberValue(1.5)),
+            ("float(2)", LiteralValue::NumberValue(2.0)),
+            ("bool(\"TRUE\")", LiteralValue::BooleanValue(true)),
+            ("bool(0)", LiteralValue::BooleanValue(false)),
+            ("bool(\"yes\")", LiteralValue::NullValue),
+        ];
+        for (input, expected_output) in test_cases {
+            assert_eq!(eval_with_nulls(input).unwrap(), expected_output, "Expression: {}", input);
+        }
+
+        assert!(matches!(eval_with_nulls("int(#2024-01-01#)").unwrap_err().kind(), ErrorKind::TypeMismatch { .. }));
+        assert!(eval_with_nulls("iif(1, 2, 3)").is_err());
+        assert_eq!(eval_with_nulls("coalesce(missing, 1 / 0)").unwrap_err().kind(), &ErrorKind::DivisionByZero);
+        assert_eq!(eval_with_nulls("iif(true, 1 / 0, 2)").unwrap_err().kind(), &ErrorKind::DivisionByZero);
+    }
+
+    #[test]
+    fn test_case_evaluation() {
+        let test_cases = vec![
+            ("case when true then 1 else 2 end", 1.0),
+            ("case when false then 1 else 2 end", 2.0),
+            ("case when false then 1 when true then 2 else 3 end", 2.0),
+            ("case when false then 1 when true then 2 end", 2.0),
+            ("10 * case when false then 1 else 2 + 3 end", 50.0),
+        ];
+
+        for (input, expected_output) in test_cases {
+            let result = eval_expression(input).unwrap();
+            assert!(
+                (result - expected_output).abs() < f64::EPSILON,
+                "Expression: {}, Result: {}, Expected: {}",
+                input,
+                result,
+                expected_output
+            );
+        }
+
+        //without a matching branch and ELSE the result is NULL, which is not a number
+        let error = eval_expression("case when false then 1 end").unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::TypeMismatch {
+                expected: ValueType::Number,
+                actual: ValueType::Null
+            }
+        );
+    }
+
+    #[test]
+    fn test_evaluation_errors() {
+        let error = eval_expression("1 + foo(2)").unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::UnknownFunction {
+                function_name: "foo".to_string()
+            }
+        );
+        assert_eq!((error.span().start, error.span().end), (4, 10));
+        assert_eq!(
+            error.to_string(),
+            "unknown function `foo` at line 1, column 5\n  |\n1 | 1 + foo(2)\n  |     ^^^^^^"
+        );
+
+        let error = eval_expression("2 * (1 / (3 - 3))").unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::DivisionByZero);
+        assert_eq!((error.span().start, error.span().end), (10, 15));
+
+        let error = eval_expression("unknown_field * 2").unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::UnknownField { field_id } if field_id == "unknown_field"));
+
+        let error = eval_expression("\"text\"").unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::TypeMismatch { .. }));
+
+        let error = eval_expression("1 +\n  * 2").unwrap_err();
+        if let ErrorKind::Parse { expected } = error.kind() {
+            assert!(!expected.is_empty());
+        } else {
+            panic!("Expected a parse error, got {:?}", error);
+        }
+        assert_eq!((error.span().start_line, error.span().start_column), (2, 3));
+        assert!(error.to_string().ends_with("2 |   * 2\n  |   ^"), "{}", error);
+    }
+
+    #[test]
+    fn test_invalid_formulas_do_not_panic() {
+        let error = eval_expression("\"a\" + 1").unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::TypeMismatch {
+                expected: ValueType::Number,
+                actual: ValueType::String
+            }
+        );
+        assert_eq!((error.span().start, error.span().end), (0, 3));
+
+        let error = eval_expression("1 * (TRUE - 2)").unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::TypeMismatch { .. }));
+
+        let error = eval_expression("sum(sales) [where region = 1]").unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::UnsupportedModifier);
+
+        let error = eval_ast(&func("+", vec![lit_num(1.0)]), &HashMap::new()).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::ArityMismatch {
+                function_name: "+".to_string(),
+                expected_min: 2,
+                expected_max: Some(2),
+                actual: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_comparison_and_boolean_evaluation() {
+        let test_cases = vec![
+            ("if 1 < 2 then 1 else 0", 1.0),
+            ("if 2 <= 2 then 1 else 0", 1.0),
+            ("if 1 > 2 then 1 else 0", 0.0),
+            ("if 3 >= 2 + 2 then 1 else 0", 0.0),
+            ("if 1 + 1 = 2 then 1 else 0", 1.0),
+            ("if 1 != 1 then 1 else 0", 0.0),
+            (r#"if "abc" < "abd" then 1 else 0"#, 1.0),
+            (r#"if "a\"b" = "a\"b" then 1 else 0"#, 1.0),
+            ("if true = false then 1 else 0", 0.0),
+            ("if false < true then 1 else 0", 1.0),
+            ("if 1 < 2 and 2 < 3 then 1 else 0", 1.0),
+            ("if 1 < 2 AND 2 > 3 then 1 else 0", 0.0),
+            ("if 1 > 2 or 2 < 3 then 1 else 0", 1.0),
+            ("if false Or false OR false then 1 else 0", 0.0),
+            ("if not 1 > 2 then 1 else 0", 1.0),
+            ("if NOT true then 1 else 0", 0.0),
+            ("case when 1 > 2 then 1 when 2 > 1 then 2 else 3 end", 2.0),
+        ];
+
+        for (input, expected_output) in test_cases {
+            let result = eval_expression(input).unwrap();
+            assert!(
+                (result - expected_output).abs() < f64::EPSILON,
+                "Expression: {}, Result: {}, Expected: {}",
+                input,
+                result,
+                expected_output
+            );
+        }
+
+        //AND and OR do not evaluate operands after the result is known
+        assert_eq!(eval_expression("if false and unknown_field then 1 else 0").unwrap(), 0.0);
+        assert_eq!(eval_expression("if true or 1 / 0 = 1 then 1 else 0").unwrap(), 1.0);
+        assert!(eval_expression("if true and unknown_field then 1 else 0").is_err());
+
+        let ctx = HashMap::from([("city".to_string(), LiteralValue::StringValue("Opelika".to_string()))]);
+        let ast = parse(r#"city = "Opelika""#).unwrap();
+        assert_eq!(eval_ast(&ast, &ctx).unwrap(), LiteralValue::BooleanValue(true));
+
+        //conditions have to be booleans and comparisons need operands of the same type
+        let error = eval_expression("if 3 then 1 else 0").unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::TypeMismatch {
+                expected: ValueType::Boolean,
+                actual: ValueType::Integer
+            }
+        );
+        let error = eval_expression(r#"if 1 = "1" then 1 else 0"#).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::TypeMismatch {
+                expected: ValueType::Integer,
+                actual: ValueType::String
+            }
+        );
+        assert!(eval_expression("if not 1 then 1 else 0").is_err());
+    }
+
+    #[test]
+    fn test_numeric_types() {
+        let test_cases = vec![
+            ("42", LiteralValue::IntegerValue(42)),
+            ("9007199254740993 + 0", LiteralValue::IntegerValue(9007199254740993)),
+            ("99999999999999999999", LiteralValue::DecimalValue(Decimal::from(99999999999999999999_u128))),
+            ("0.1 + 0.2", LiteralValue::DecimalValue(Decimal::new(3, 1))),
+            ("7 / 2", LiteralValue::DecimalValue(Decimal::new(35, 1))),
+            ("2 * 1.5 - 1", LiteralValue::DecimalValue(Decimal::from(2))),
+            ("1e3 + 1", LiteralValue::NumberValue(1001.0)),
+            ("0.5 * 1E1", LiteralValue::NumberValue(5.0)),
+            ("0.1 + 0.2 = 0.3", LiteralValue::BooleanValue(true)),
+            ("1 = 1.0", LiteralValue::BooleanValue(true)),
+            ("2 > 1.5e0", LiteralValue::BooleanValue(true)),
+        ];
+
+        for (input, expected_output) in test_cases {
+            let ast = parse(input).unwrap();
+            assert_eq!(eval_ast(&ast, &HashMap::new()).unwrap(), expected_output, "Expression: {}", input);
+        }
+
+        let error = eval_expression("9223372036854775807 + 1").unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::NumericOverflow);
+        assert_eq!((error.span().start, error.span().end), (0, 23));
+        let error = eval_expression("7 / (2 - 2)").unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::DivisionByZero);
+        assert_eq!(eval_expression("10 / 4").unwrap(), 2.5);
+    }
+
+    #[test]
+    fn test_date_evaluation() {
+        let date = |y, m, d| LiteralValue::DateValue(NaiveDate::from_ymd_opt(y, m, d).unwrap());
+        let timestamp = |y, m, d, h, min, sec| {
+            LiteralValue::TimestampValue(NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, sec).unwrap())
+        };
+        let ctx = HashMap::from([("order_date".to_string(), date(2024, 1, 31))]);
+        let test_cases = vec![
+            ("#2024-01-31#", date(2024, 1, 31)),
+            ("DATE '2024-01-31'", date(2024, 1, 31)),
+            ("timestamp '2024-01-31T08:15'", timestamp(2024, 1, 31, 8, 15, 0)),
+            ("interval '1 month 2 days'", LiteralValue::IntervalValue(Interval::new(1, 2, 0))),
+            ("order_date + interval '7 days'", date(2024, 2, 7)),
+            ("order_date + interval '1 month'", date(2024, 2, 29)),
+            ("interval '1 year' + order_date", date(2025, 1, 31)),
+            ("order_date - interval '1 hour'", timestamp(2024, 1, 30, 23, 0, 0)),
+            ("order_date + 1", date(2024, 2, 1)),
+            ("#2024-03-01# - order_date", LiteralValue::IntegerValue(30)),
+            (
+                "#2024-01-02 06:00:00# - #2024-01-01 12:00:00#",
+                LiteralValue::IntervalValue(Interval::new(0, 0, 18 * 3_600_000_000)),
+            ),
+            ("interval '1 week' * 2 - interval '1 day'", LiteralValue::IntervalValue(Interval::new(0, 13, 0))),
+            ("order_date < #2024-02-01#", LiteralValue::BooleanValue(true)),
+            ("order_date = #2024-01-31 00:00#", LiteralValue::BooleanValue(true)),
+            ("interval '1 month' > interval '4 weeks'", LiteralValue::BooleanValue(true)),
+            ("datepart(\"quarter\", order_date)", LiteralValue::IntegerValue(1)),
+            ("DATEPART(\"hour\", #2024-01-31 08:15:00#)", LiteralValue::IntegerValue(8)),
+            ("date_trunc(\"month\", order_date)", date(2024, 1, 1)),
+            ("date_trunc(\"week\", #2024-01-31 08:15:00#)", timestamp(2024, 1, 29, 0, 0, 0)),
+            ("datediff(\"month\", order_date, #2024-03-01#)", LiteralValue::IntegerValue(2)),
+            ("datediff(\"day\", #2024-01-31 23:00:00#, #2024-02-01 01:00:00#)", LiteralValue::IntegerValue(1)),
+            ("datediff(\"year\", #2024-12-31#, #2023-01-01#)", LiteralValue::IntegerValue(-1)),
+            ("dateadd(\"quarter\", 1, order_date)", date(2024, 4, 30)),
+            ("dateadd(\"minutes\", 90, order_date)", timestamp(2024, 1, 31, 1, 30, 0)),
+            ("dateadd(\"day\", 1, null)", LiteralValue::NullValue),
+            ("today() >= #2024-01-01#", LiteralValue::BooleanValue(true)),
+            ("now() > today()", LiteralValue::BooleanValue(true)),
+        ];
+
+        for (input, expected_output) in test_cases {
+            let ast = parse(input).unwrap();
+            assert_eq!(eval_ast(&ast, &ctx).unwrap(), expected_output, "Expression: {}", input);
+        }
+
+        let error = parse("#2024-02-30# + 1").unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::InvalidLiteral { value_type: ValueType::Date });
+        assert_eq!((error.span().start, error.span().end), (0, 12));
+        assert!(parse("interval '7 fortnights'").is_err());
+        let pair = <ExpressionParser as pest::Parser<Rule>>::parse(Rule::expression_input, "1 + #2024-02-30#")
+            .unwrap()
+            .next()
+            .unwrap();
+        let error = convert_to_ast(pair).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::InvalidLiteral { value_type: ValueType::Date });
+        assert!(error.to_string().contains("1 + #2024-02-30#"));
+        assert!(parse("interval '9223372036854775807 days'").is_err());
+
+        let error = eval_ast(&parse("order_date + 1.5").unwrap(), &ctx).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::TypeMismatch {
+                expected: ValueType::Interval,
+                actual: ValueType::Decimal
+            }
+        );
+        let error = eval_ast(&parse("datepart(\"era\", order_date)").unwrap(), &ctx).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidArgument { function_name, .. } if function_name == "datepart"));
+        assert!(eval_ast(&parse("dateadd(\"day\", 1)").unwrap(), &ctx).is_err());
+    }
+
+    #[test]
+    fn test_null_evaluation() {
+        let ctx = HashMap::from([
+            ("amount".to_string(), LiteralValue::NumberValue(2.0)),
+            ("discount".to_string(), LiteralValue::NullValue),
+        ]);
+        let test_cases = vec![
+            ("null", LiteralValue::NullValue),
+            ("amount + discount", LiteralValue::NullValue),
+            ("discount / 0", LiteralValue::NullValue),
+            ("discount = null", LiteralValue::NullValue),
+            ("amount > discount", LiteralValue::NullValue),
+            ("not discount", LiteralValue::NullValue),
+            ("discount is null", LiteralValue::BooleanValue(true)),
+            ("amount IS NOT NULL", LiteralValue::BooleanValue(true)),
+            ("amount * discount is not null", LiteralValue::BooleanValue(false)),
+            ("true and null", LiteralValue::NullValue),
+            ("null and false", LiteralValue::BooleanValue(false)),
+            ("null or true", LiteralValue::BooleanValue(true)),
+            ("false or null", LiteralValue::NullValue),
+            ("if discount > 0 then 1 else 2", LiteralValue::IntegerValue(2)),
+            ("case when null then 1 when amount > 1 then 2 end", LiteralValue::IntegerValue(2)),
+            ("case when amount > 5 then 1 end", LiteralValue::NullValue),
+        ];
+
+        for (input, expected_output) in test_cases {
+            let ast = parse(input).unwrap();
+            assert_eq!(eval_ast(&ast, &ctx).unwrap(), expected_output, "Expression: {}", input);
+        }
+
+        //NULL is a value, fields the context does not know are still errors
+        assert!(eval_ast(&parse("missing is null").unwrap(), &ctx).is_err());
+    }
+
+    #[test]
+    fn test_literal_value_conversions() {
+        assert_eq!(f64::try_from(LiteralValue::NumberValue(1.5)), Ok(1.5));
+        assert_eq!(bool::try_from(LiteralValue::BooleanValue(true)), Ok(true));
+        assert_eq!(
+            String::try_from(LiteralValue::StringValue("a".to_string())),
+            Ok("a".to_string())
+        );
+        assert_eq!(
+            f64::try_from(LiteralValue::BooleanValue(true)),
+            Err(ValueTypeError {
+                expected: ValueType::Number,
+                actual: ValueType::Boolean
+            })
+        );
+        assert!(bool::try_from(LiteralValue::StringValue("true".to_string())).is_err());
+        assert!(String::try_from(LiteralValue::NumberValue(1.0)).is_err());
+    }
 }
 
 #[cfg(test)]
@@ -441,3 +918,18 @@ mod expression_parsing_tests;
 
 #[cfg(test)]
 mod expression_ast_tests;
+
+#[cfg(test)]
+mod eval_tests;
+
+#[cfg(test)]
+mod compile_tests;
+
+#[cfg(test)]
+mod bytecode_tests;
+
+#[cfg(test)]
+mod sql_tests;
+
+#[cfg(test)]
+mod wasm_tests;
@@ -0,0 +1,279 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::aggregate::{aggregate_scope, map_children, DashboardFilter, DistinctKey, FilterScope};
+use crate::ast::{Expression, GroupByContext, GroupReference, LiteralValue, Span};
+use crate::error::{Error, ErrorKind};
+use crate::eval::{eval_condition, eval_node, EvalContext};
+use crate::functions::FunctionRegistry;
+
+//name of the pseudo aggregate that takes the single value of a GROUP BY modifier in a group
+pub(crate) const LEVEL_OF_DETAIL: &str = "level of detail";
+
+//computed values are resolved as fields with this prefix, which field references cannot have
+const FIELD_PREFIX: &str = "#level of detail ";
+
+/// Value of an expression for one group of a grouped query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupValue {
+    /// Values of the query groups that identify the group, in the order of the query groups.
+    pub keys: Vec<LiteralValue>,
+    pub value: LiteralValue,
+}
+
+/// Evaluates an expression for every group of a query grouped by `query_groups`, with the
+/// built-in functions.
+///
+/// GROUP BY modifiers compute the expression they wrap at their own level of detail, over all
+/// rows, and every row gets the value of its level: `[group by region]` groups by the region,
+/// `[group by group(1)]` by the first query group and `[group by all groups]` uses all rows as
+/// one group. Inside an aggregate the values are aggregated like fields, e.g.
+/// `avg(sum(sales) [group by customer])`. Outside of aggregates the value has to be the same for
+/// all rows of a query group, which is the case when the modifier groups by a subset of the
+/// query groups. Groups are returned in the order of their first row, groups without rows that
+/// pass the dashboard filters are left out.
+///
+/// ```
+/// use analytical_expression_parser::ast::LiteralValue;
+/// use analytical_expression_parser::{evaluate_grouped, parse};
+/// use std::collections::HashMap;
+///
+/// let rows: Vec<HashMap<String, LiteralValue>> = [("EU", "DE", 10), ("EU", "FR", 30), ("US", "US", 20)]
+///     .iter()
+///     .map(|(region, country, sales)| {
+///         HashMap::from([
+///             ("region".to_string(), LiteralValue::StringValue(region.to_string())),
+///             ("country".to_string(), LiteralValue::StringValue(country.to_string())),
+///             ("sales".to_string(), LiteralValue::IntegerValue(*sales)),
+///         ])
+///     })
+///     .collect();
+/// //sales of each country compared to the sales of its region
+/// let expression = parse("sum(sales) * 100 / sum(sales) [group by region]").unwrap();
+/// let groups = evaluate_grouped(&expression, &rows, &[parse("country").unwrap()], &[]).unwrap();
+/// let shares: Vec<f64> = groups.into_iter().map(|group| f64::try_from(group.value).unwrap()).collect();
+/// assert_eq!(shares, vec![25.0, 75.0, 100.0]);
+/// ```
+pub fn evaluate_grouped<C, I>(
+    expression: &Expression,
+    rows: I,
+    query_groups: &[Expression],
+    dashboard_filters: &[DashboardFilter],
+) -> Result<Vec<GroupValue>, Error>
+where
+    C: EvalContext,
+    I: IntoIterator<Item = C>,
+{
+    let rows = rows.into_iter().collect::<Vec<_>>();
+    aggregate_groups(
+        expression,
+        &rows,
+        query_groups,
+        dashboard_filters,
+        FunctionRegistry::builtins(),
+    )
+}
+
+pub(crate) fn is_level_of_detail_field(field_id: &str) -> bool {
+    field_id.starts_with(FIELD_PREFIX)
+}
+
+pub(crate) fn has_level_of_detail(expression: &Expression) -> bool {
+    match expression {
+        Expression::ModifierExpression {
+            group_by_modifier: Some(_),
+            ..
+        } => true,
+        _ => expression.children().into_iter().any(has_level_of_detail),
+    }
+}
+
+pub(crate) fn aggregate_groups<C: EvalContext>(
+    expression: &Expression,
+    rows: &[C],
+    query_groups: &[Expression],
+    dashboard_filters: &[DashboardFilter],
+    functions: &FunctionRegistry,
+) -> Result<Vec<GroupValue>, Error> {
+    let scope = FilterScope::new(dashboard_filters);
+    let mut levels = Levels {
+        rows,
+        values: vec![Vec::new(); rows.len()],
+        count: 0,
+        query_groups,
+        functions,
+    };
+    let expression = levels.resolve(expression, &scope)?;
+
+    let all_rows = (0..rows.len()).collect::<Vec<_>>();
+    let groups = if query_groups.is_empty() {
+        vec![(Vec::new(), all_rows)]
+    } else {
+        let keys = query_groups.iter().collect::<Vec<_>>();
+        let mut groups = levels.group(&all_rows, &keys)?;
+        //groups that the dashboard filters remove completely are not part of the result
+        if !dashboard_filters.is_empty() {
+            let mut visible = Vec::with_capacity(groups.len());
+            for group in groups {
+                if levels.any_visible(&group.1, dashboard_filters)? {
+                    visible.push(group);
+                }
+            }
+            groups = visible;
+        }
+        groups
+    };
+
+    groups
+        .into_iter()
+        .map(|(keys, group)| {
+            let value = aggregate_scope(
+                &expression,
+                group.iter().map(|&index| levels.row(index)),
+                &scope,
+                functions,
+            )?;
+            Ok(GroupValue { keys, value })
+        })
+        .collect()
+}
+
+//a row together with the values of the GROUP BY modifiers computed for it
+struct LevelRow<'r, C> {
+    row: &'r C,
+    values: &'r [LiteralValue],
+}
+
+impl<C: EvalContext> EvalContext for LevelRow<'_, C> {
+    fn resolve(&self, field_id: &str) -> Option<Cow<'_, LiteralValue>> {
+        match field_id.strip_prefix(FIELD_PREFIX) {
+            Some(index) => self.values.get(index.parse::<usize>().ok()?).map(Cow::Borrowed),
+            None => self.row.resolve(field_id),
+        }
+    }
+}
+
+//rows of the query, with a value per row for every GROUP BY modifier resolved so far
+struct Levels<'r, C> {
+    rows: &'r [C],
+    values: Vec<Vec<LiteralValue>>,
+    count: usize,
+    query_groups: &'r [Expression],
+    functions: &'r FunctionRegistry,
+}
+
+type Group = (Vec<LiteralValue>, Vec<usize>);
+
+impl<'r, C: EvalContext> Levels<'r, C> {
+    fn row(&self, index: usize) -> LevelRow<'_, C> {
+        LevelRow {
+            row: &self.rows[index],
+            values: &self.values[index],
+        }
+    }
+
+    //copy of the expression with GROUP BY modifiers replaced by references to their values,
+    //inner modifiers are resolved first so outer ones can use their values
+    fn resolve<'a>(&mut self, expression: &'a Expression, scope: &FilterScope<'a>) -> Result<Expression, Error> {
+        let Expression::ModifierExpression {
+            expression: modified,
+            where_modifier,
+            group_by_modifier,
+            span,
+        } = expression
+        else {
+            return map_children(expression, |child| self.resolve(child, scope));
+        };
+        let mut scope = scope.clone();
+        if let Some(where_modifier) = where_modifier {
+            scope.apply(where_modifier);
+        }
+        let modified = self.resolve(modified, &scope)?;
+        match group_by_modifier {
+            Some(group_by_modifier) => self.add_level(&modified, &group_by_modifier.group_context, &scope, *span),
+            None => Ok(Expression::ModifierExpression {
+                expression: Box::new(modified),
+                where_modifier: where_modifier.clone(),
+                group_by_modifier: None,
+                span: *span,
+            }),
+        }
+    }
+
+    //compute the expression for every group of the level and give each row the value of its group
+    fn add_level(
+        &mut self,
+        expression: &Expression,
+        group_context: &GroupByContext,
+        scope: &FilterScope,
+        span: Span,
+    ) -> Result<Expression, Error> {
+        let keys = match group_context {
+            GroupByContext::AllGroups() => Vec::new(),
+            GroupByContext::IncludedGroups { groups } => groups
+                .iter()
+                .map(|group| match group {
+                    GroupReference::QueryGroup { index, span } => index
+                        .checked_sub(1)
+                        .and_then(|index| self.query_groups.get(index))
+                        .ok_or_else(|| Error::new(ErrorKind::UnknownGroup { index: *index }, *span)),
+                    GroupReference::FieldGroup { field } => Ok(field),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+        let all_rows = (0..self.rows.len()).collect::<Vec<_>>();
+        let mut column = vec![LiteralValue::NullValue; self.rows.len()];
+        for (_, group) in self.group(&all_rows, &keys)? {
+            let rows = group.iter().map(|&index| self.row(index));
+            let value = aggregate_scope(expression, rows, scope, self.functions)?;
+            for index in group {
+                column[index] = value.clone();
+            }
+        }
+        for (values, value) in self.values.iter_mut().zip(column) {
+            values.push(value);
+        }
+        self.count += 1;
+        Ok(Expression::FieldReference {
+            field_id: format!("{}{}", FIELD_PREFIX, self.count - 1),
+            span,
+        })
+    }
+
+    //split the rows into groups with the same values of the keys, in the order of their first row
+    fn group(&self, rows: &[usize], keys: &[&Expression]) -> Result<Vec<Group>, Error> {
+        let mut positions = HashMap::new();
+        let mut groups: Vec<Group> = Vec::new();
+        for &index in rows {
+            let row = self.row(index);
+            let values = keys
+                .iter()
+                .map(|key| eval_node(key, &row, self.functions))
+                .collect::<Result<Vec<_>, _>>()?;
+            let distinct = values.iter().cloned().map(DistinctKey::from).collect::<Vec<_>>();
+            let position = *positions.entry(distinct).or_insert_with(|| {
+                groups.push((values, Vec::new()));
+                groups.len() - 1
+            });
+            groups[position].1.push(index);
+        }
+        Ok(groups)
+    }
+
+    fn any_visible(&self, rows: &[usize], dashboard_filters: &[DashboardFilter]) -> Result<bool, Error> {
+        for &index in rows {
+            let row = self.row(index);
+            let mut visible = true;
+            for filter in dashboard_filters {
+                if eval_condition(&filter.condition, &row, self.functions)? != Some(true) {
+                    visible = false;
+                    break;
+                }
+            }
+            if visible {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
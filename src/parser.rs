@@ -0,0 +1,179 @@
+use pest::Parser as _;
+
+use crate::ast::{Expression, Span};
+use crate::error::{Error, ErrorKind};
+use crate::{convert_operand, ExpressionParser, Rule};
+
+/// Errors returned by [`parse`] and [`Parser::parse`] have one of the kinds
+/// `ErrorKind::Parse`, `ErrorKind::InvalidLiteral`, `ErrorKind::NestingTooDeep` or
+/// `ErrorKind::ModifierNotAllowed`.
+pub type ParseError = Error;
+
+/// Parses an expression into its AST with the default [`Parser`] settings.
+pub fn parse(input: &str) -> Result<Expression, ParseError> {
+    Parser::default().parse(input)
+}
+
+/// Configurable expression parser.
+///
+/// ```
+/// use analytical_expression_parser::Parser;
+///
+/// let parser = Parser::new().max_nesting_depth(16).allow_modifiers(false);
+/// assert!(parser.parse("(field1 + 42) * 2").is_ok());
+/// assert!(parser.parse("sum(sales) [where region = \"EU\"]").is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Parser {
+    max_nesting_depth: usize,
+    allow_modifiers: bool,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Parser {
+            max_nesting_depth: 64,
+            allow_modifiers: true,
+        }
+    }
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Parser::default()
+    }
+
+    /// Maximum nesting of parentheses, modifier brackets and IF and CASE expressions, 64 by
+    /// default. Parsing, type checking, validation, aggregation and SQL translation recurse over
+    /// the tree, as do `Clone`, `PartialEq` and `Debug` of [`Expression`], so the limit protects
+    /// the host from stack overflows on untrusted input; the default is safe for the 2 MiB stack
+    /// of spawned threads. Inputs nested deeper than pest can parse fail with a parse error
+    /// regardless of the limit. Evaluating a row with [`evaluate`](crate::evaluate) or a
+    /// [`Program`](crate::bytecode::Program), and dropping a tree, work at any depth.
+    pub fn max_nesting_depth(mut self, max_nesting_depth: usize) -> Self {
+        self.max_nesting_depth = max_nesting_depth;
+        self
+    }
+
+    /// Whether `[where ...]` and `[group by ...]` clauses are accepted, e.g. they make no sense
+    /// in row-level calculated fields.
+    pub fn allow_modifiers(mut self, allow_modifiers: bool) -> Self {
+        self.allow_modifiers = allow_modifiers;
+        self
+    }
+
+    pub fn parse(&self, input: &str) -> Result<Expression, ParseError> {
+        self.check_nesting_depth(input)?;
+
+        let mut pairs = ExpressionParser::parse(Rule::expression_input, input)
+            .map_err(|e| Error::from_pest(e, input))?
+            .peekable();
+        //the top level expression can be followed by a WHERE clause, EOI is the last pair
+        let expression = convert_operand(&mut pairs)?;
+
+        if !self.allow_modifiers {
+            if let Some(span) = find_modifier(&expression) {
+                return Err(Error::new(ErrorKind::ModifierNotAllowed, span).with_source(input));
+            }
+        }
+        Ok(expression)
+    }
+
+    //scan the raw input, as the grammar can only be checked by the recursive parser itself
+    fn check_nesting_depth(&self, input: &str) -> Result<(), ParseError> {
+        let mut open: Vec<Nesting> = Vec::new();
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut word_start = None;
+        //a trailing space ends the last word
+        for (offset, c) in input.char_indices().chain([(input.len(), ' ')]) {
+            if !in_string && (c.is_alphanumeric() || c == '_' || c == '.') {
+                word_start.get_or_insert(offset);
+                continue;
+            }
+            if let Some(start) = word_start.take() {
+                let word = &input[start..offset];
+                let nesting = match word.to_ascii_lowercase().as_str() {
+                    "if" => Some(Nesting::If { in_else: false }),
+                    "case" => Some(Nesting::Case),
+                    "then" | "when" => {
+                        close_if_expressions(&mut open);
+                        None
+                    }
+                    "else" => {
+                        close_if_expressions(&mut open);
+                        if let Some(Nesting::If { in_else }) = open.last_mut() {
+                            *in_else = true;
+                        }
+                        None
+                    }
+                    "end" => {
+                        close_if_expressions(&mut open);
+                        if open.last() == Some(&Nesting::Case) {
+                            open.pop();
+                        }
+                        None
+                    }
+                    _ => None,
+                };
+                if let Some(nesting) = nesting {
+                    self.enter(&mut open, nesting, input, start, offset)?;
+                }
+            }
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if in_string => escaped = true,
+                '"' => in_string = !in_string,
+                '(' | '[' if !in_string => self.enter(&mut open, Nesting::Bracket, input, offset, offset + 1)?,
+                ')' | ']' if !in_string => while open.pop().is_some_and(|nesting| nesting != Nesting::Bracket) {},
+                ',' if !in_string => close_if_expressions(&mut open),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn enter(
+        &self,
+        open: &mut Vec<Nesting>,
+        nesting: Nesting,
+        input: &str,
+        start: usize,
+        end: usize,
+    ) -> Result<(), ParseError> {
+        open.push(nesting);
+        if open.len() > self.max_nesting_depth {
+            let span = pest::Span::new(input, start, end).map(Span::from).unwrap_or_default();
+            return Err(Error::new(
+                ErrorKind::NestingTooDeep {
+                    max_depth: self.max_nesting_depth,
+                },
+                span,
+            )
+            .with_source(input));
+        }
+        Ok(())
+    }
+}
+
+//constructs counted by the nesting depth, IF expressions have no closing keyword and end with
+//their ELSE branch, at the next keyword, separator or bracket of the enclosing construct
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Nesting {
+    Bracket,
+    Case,
+    If { in_else: bool },
+}
+
+fn close_if_expressions(open: &mut Vec<Nesting>) {
+    while open.last() == Some(&Nesting::If { in_else: true }) {
+        open.pop();
+    }
+}
+
+fn find_modifier(expression: &Expression) -> Option<Span> {
+    if let Expression::ModifierExpression { span, .. } = expression {
+        return Some(*span);
+    }
+    expression.children().into_iter().find_map(find_modifier)
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use crate::aggregate;
+use crate::ast::{Expression, FilterContext, GroupByContext, GroupByModifier, GroupReference, ValueType};
+use crate::error::{Error, ErrorKind};
+
+/// Fields of the data source that expressions are written against, with their types.
+///
+/// ```
+/// use analytical_expression_parser::ast::ValueType;
+/// use analytical_expression_parser::Schema;
+///
+/// let schema = Schema::new()
+///     .with_field("region", ValueType::String)
+///     .with_field("sales", ValueType::Decimal);
+/// assert_eq!(schema.field_type("sales"), Some(ValueType::Decimal));
+/// assert!(!schema.contains("profit"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    fields: HashMap<String, ValueType>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Schema::default()
+    }
+
+    /// Adds a field, replacing the type of a field with the same id.
+    pub fn with_field(mut self, field_id: &str, value_type: ValueType) -> Self {
+        self.fields.insert(field_id.to_string(), value_type);
+        self
+    }
+
+    pub fn field_type(&self, field_id: &str) -> Option<ValueType> {
+        self.fields.get(field_id).copied()
+    }
+
+    pub fn contains(&self, field_id: &str) -> bool {
+        self.fields.contains_key(field_id)
+    }
+
+    /// Checks the WHERE and GROUP BY modifiers of an expression for a query with `query_groups`
+    /// groups, and returns all problems found.
+    ///
+    /// Modifiers have to wrap aggregate expressions, which use fields only inside of aggregate
+    /// functions, `group(N)` has to refer to one of the query groups, and the fields of
+    /// `allow filters on`, `ignore filters on` and `group by` have to be part of the schema.
+    ///
+    /// ```
+    /// use analytical_expression_parser::ast::ValueType;
+    /// use analytical_expression_parser::{parse, ErrorKind, Schema};
+    ///
+    /// let schema = Schema::new().with_field("sales", ValueType::Integer);
+    /// assert!(schema.validate_modifiers(&parse("sum(sales) [where ignore all filters]").unwrap(), 0).is_ok());
+    ///
+    /// let errors = schema.validate_modifiers(&parse("1 [where ignore filters on region]").unwrap(), 0).unwrap_err();
+    /// assert_eq!(errors.len(), 2);
+    /// assert_eq!(errors[0].kind(), &ErrorKind::ModifierWithoutAggregate);
+    /// ```
+    pub fn validate_modifiers(&self, expression: &Expression, query_groups: usize) -> Result<(), Vec<Error>> {
+        let mut errors = Vec::new();
+        self.check_modifiers(expression, query_groups, &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn check_modifiers(&self, expression: &Expression, query_groups: usize, errors: &mut Vec<Error>) {
+        if let Expression::ModifierExpression {
+            expression: modified,
+            where_modifier,
+            group_by_modifier,
+            span,
+        } = expression
+        {
+            if !is_aggregate_expression(modified) {
+                errors.push(Error::new(ErrorKind::ModifierWithoutAggregate, *span));
+            }
+            match where_modifier
+                .as_ref()
+                .and_then(|where_modifier| where_modifier.filter_context.as_ref())
+            {
+                Some(FilterContext::AllowedFilters {
+                    allowed_filters: fields,
+                })
+                | Some(FilterContext::IgnoredFilters {
+                    ignored_filters: fields,
+                }) => fields.iter().for_each(|field| self.check_field(field, errors)),
+                Some(FilterContext::AllFiltersIgnored()) | None => {}
+            }
+            if let Some(GroupByModifier {
+                group_context: GroupByContext::IncludedGroups { groups },
+                ..
+            }) = group_by_modifier
+            {
+                for group in groups {
+                    match group {
+                        GroupReference::QueryGroup { index, span } if *index == 0 || *index > query_groups => {
+                            errors.push(Error::new(ErrorKind::UnknownGroup { index: *index }, *span))
+                        }
+                        GroupReference::QueryGroup { .. } => {}
+                        GroupReference::FieldGroup { field } => self.check_field(field, errors),
+                    }
+                }
+            }
+        }
+        for child in expression.children() {
+            self.check_modifiers(child, query_groups, errors);
+        }
+    }
+
+    fn check_field(&self, field: &Expression, errors: &mut Vec<Error>) {
+        if let Expression::FieldReference { field_id, span } = field {
+            if !self.contains(field_id) {
+                errors.push(Error::new(
+                    ErrorKind::UnknownField {
+                        field_id: field_id.clone(),
+                    },
+                    *span,
+                ));
+            }
+        }
+    }
+}
+
+//whether the expression has one value for a set of rows: it calls aggregates and uses fields only inside of them
+fn is_aggregate_expression(expression: &Expression) -> bool {
+    fn visit(expression: &Expression, has_aggregate: &mut bool) -> bool {
+        match expression {
+            Expression::Function { function_name, .. } if aggregate::is_aggregate(function_name) => {
+                *has_aggregate = true;
+                true
+            }
+            Expression::FieldReference { .. } => false,
+            //the fields of nested modifiers are checked with the nested modifier
+            Expression::ModifierExpression { expression, .. } => visit(expression, has_aggregate),
+            _ => expression
+                .children()
+                .into_iter()
+                .all(|child| visit(child, has_aggregate)),
+        }
+    }
+    let mut has_aggregate = false;
+    visit(expression, &mut has_aggregate) && has_aggregate
+}
@@ -0,0 +1,1187 @@
+//! Translation of expressions to SQL, so they can be evaluated by the database.
+//!
+//! The expression is type checked against a [`Schema`] first, as the SQL for an operator can
+//! depend on the types of its operands, e.g. adding days to a date or dividing integers.
+//! Dialects differ in quoting, literals, date arithmetic and the available functions; the
+//! built-in dialects are [`Ansi`], [`PostgreSql`], [`Sqlite`], [`DuckDb`], [`MySql`] and
+//! [`BigQuery`], others can be added by implementing [`Dialect`].
+//!
+//! [`to_sql`] translates expressions that are evaluated on a single row or aggregate all rows.
+//! Expressions with WHERE and GROUP BY modifiers need the filters and groups of the query they
+//! are part of, [`to_sql_query`] plans a whole query for them.
+//!
+//! ```
+//! use analytical_expression_parser::ast::ValueType;
+//! use analytical_expression_parser::sql::{to_sql, MySql, PostgreSql};
+//! use analytical_expression_parser::{parse, Schema};
+//!
+//! let schema = Schema::new()
+//!     .with_field("orders.region", ValueType::String)
+//!     .with_field("orders.amount", ValueType::Integer);
+//! let expression = parse(r#"if orders.region = "EU" then orders.amount / 2 else 0"#).unwrap();
+//! assert_eq!(
+//!     to_sql(&expression, &schema, &PostgreSql).unwrap(),
+//!     r#"CASE WHEN "orders"."region" = 'EU' THEN CAST("orders"."amount" AS NUMERIC) / 2 ELSE 0 END"#
+//! );
+//! assert_eq!(
+//!     to_sql(&expression, &schema, &MySql).unwrap(),
+//!     "CASE WHEN `orders`.`region` = 'EU' THEN `orders`.`amount` / 2 ELSE 0 END"
+//! );
+//! ```
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+
+use crate::ast::{Expression, Interval, LiteralValue, Span, ValueType};
+pub use crate::datetime::DatePart;
+use crate::error::{Error, ErrorKind};
+use crate::functions::{is_operator, FunctionRegistry};
+use crate::schema::Schema;
+use crate::stdlib::interval_to_string;
+use crate::typecheck::{check_types, TypedExpression};
+
+pub use query::to_sql_query;
+
+mod query;
+
+//precedence of the outermost operator of generated SQL, operands that bind less tightly
+//than their operator are parenthesized
+const OR: u8 = 1;
+const AND: u8 = 2;
+const NOT: u8 = 3;
+const COMPARISON: u8 = 4;
+const ADDITION: u8 = 5;
+const MULTIPLICATION: u8 = 6;
+const ATOM: u8 = 7;
+
+/// Translates an expression with the built-in functions to SQL of the given dialect.
+///
+/// Fails with the first type error of the expression, or when the dialect has no equivalent
+/// for a function or operation used in it.
+pub fn to_sql(expression: &Expression, schema: &Schema, dialect: &dyn Dialect) -> Result<String, Error> {
+    let typed = check_types(expression, schema, FunctionRegistry::builtins()).map_err(first_error)?;
+    let translator = Translator { dialect, columns: None };
+    Ok(translator.translate(&typed)?.text)
+}
+
+/// Differences between SQL dialects. The provided methods produce ANSI SQL.
+pub trait Dialect {
+    /// Name of the dialect, used in error messages.
+    fn name(&self) -> &str;
+
+    /// Quotes one part of a dotted field id, e.g. the `table` of `table.column`.
+    fn quote_identifier(&self, identifier: &str) -> String {
+        format!("\"{}\"", identifier.replace('"', "\"\""))
+    }
+
+    fn string_literal(&self, value: &str) -> String {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+
+    fn date_literal(&self, date: NaiveDate) -> String {
+        format!("DATE '{}'", date.format("%Y-%m-%d"))
+    }
+
+    fn timestamp_literal(&self, timestamp: NaiveDateTime) -> String {
+        format!("TIMESTAMP '{}'", timestamp.format("%Y-%m-%d %H:%M:%S%.f"))
+    }
+
+    /// Interval literal, `None` if the dialect cannot represent the interval. ANSI intervals
+    /// have a single unit, so months, days and time cannot be mixed.
+    fn interval_literal(&self, interval: Interval) -> Option<String> {
+        match (interval.months, interval.days, interval.microseconds) {
+            (months, 0, 0) => Some(format!("INTERVAL '{}' MONTH", months)),
+            (0, days, 0) => Some(format!("INTERVAL '{}' DAY", days)),
+            (0, 0, microseconds) => Some(format!("INTERVAL '{}' SECOND", seconds(microseconds))),
+            _ => None,
+        }
+    }
+
+    /// Name of the type for `CAST`, `None` if the dialect has no such type.
+    fn type_name(&self, value_type: ValueType) -> Option<&str> {
+        match value_type {
+            ValueType::String => Some("VARCHAR"),
+            ValueType::Integer => Some("BIGINT"),
+            ValueType::Decimal => Some("DECIMAL"),
+            ValueType::Number => Some("DOUBLE PRECISION"),
+            ValueType::Boolean => Some("BOOLEAN"),
+            ValueType::Date => Some("DATE"),
+            ValueType::Timestamp => Some("TIMESTAMP"),
+            ValueType::Interval | ValueType::Null => None,
+        }
+    }
+
+    /// Concatenates strings that are not NULL.
+    fn concat(&self, parts: &[String]) -> String {
+        format!("({})", parts.join(" || "))
+    }
+
+    /// Comparison that is true when both values are NULL, used to join on group keys.
+    fn null_safe_equal(&self, left: &str, right: &str) -> String {
+        format!("{} IS NOT DISTINCT FROM {}", left, right)
+    }
+
+    /// Whether dividing integers with `/` truncates the result, integers are then cast to
+    /// decimals first to keep the exact result of the expression.
+    fn truncates_integer_division(&self) -> bool {
+        true
+    }
+
+    /// Whether intervals can be used as values, with `+`, `-` and `*` working on them as on
+    /// dates and timestamps. Otherwise only interval literals can be added to dates.
+    fn supports_interval_arithmetic(&self) -> bool {
+        true
+    }
+
+    /// Adds `amount` units of `part` to a date or timestamp, `None` if the dialect cannot.
+    fn add_to_date(&self, value: &str, _value_type: ValueType, part: DatePart, amount: &str) -> Option<String> {
+        let (factor, unit) = interval_unit(part);
+        Some(format!(
+            "({} + {} * INTERVAL '1' {})",
+            value,
+            scaled(amount, factor),
+            unit
+        ))
+    }
+
+    /// Translation of a built-in function, `None` if the dialect has no equivalent.
+    ///
+    /// The result is used as an operand of other expressions, so it has to be a single function
+    /// call, literal or parenthesized expression.
+    fn function(&self, function: &SqlFunction) -> Option<String> {
+        standard_function(self, function)
+    }
+}
+
+/// A call of a built-in function, with the arguments translated to SQL.
+#[derive(Debug, Clone)]
+pub struct SqlFunction {
+    name: String,
+    args: Vec<Sql>,
+    arg_types: Vec<Option<ValueType>>,
+    date_part: Option<DatePart>,
+}
+
+impl SqlFunction {
+    /// Lowercase name of the function.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn arg_count(&self) -> usize {
+        self.args.len()
+    }
+
+    /// SQL of an argument, to be used where any expression is allowed, e.g. as a function argument.
+    pub fn arg(&self, index: usize) -> &str {
+        &self.args[index].text
+    }
+
+    /// SQL of an argument, parenthesized unless it is a column, literal or function call.
+    pub fn operand(&self, index: usize) -> Cow<'_, str> {
+        self.args[index].operand(ATOM)
+    }
+
+    /// SQL of all arguments, separated by commas.
+    pub fn args(&self) -> String {
+        self.args
+            .iter()
+            .map(|arg| arg.text.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Type of an argument, `None` if it is not known before evaluation.
+    pub fn arg_type(&self, index: usize) -> Option<ValueType> {
+        self.arg_types.get(index).copied().flatten()
+    }
+
+    /// Unit of the date functions, which is given as a string literal first argument.
+    pub fn date_part(&self) -> Option<DatePart> {
+        self.date_part
+    }
+}
+
+/// ANSI translations of the built-in functions, for dialects to fall back to.
+pub fn standard_function<D: Dialect + ?Sized>(dialect: &D, function: &SqlFunction) -> Option<String> {
+    let arg = |index| function.arg(index);
+    let operand = |index| function.operand(index);
+    let sql = match (function.name(), function.arg_count()) {
+        ("abs" | "ceil" | "floor" | "sqrt" | "exp" | "ln" | "sign" | "round" | "mod", _)
+        | ("upper" | "lower" | "trim" | "replace" | "coalesce" | "nullif", _)
+        | ("sum" | "avg" | "min" | "max" | "count", _) => {
+            format!("{}({})", function.name().to_ascii_uppercase(), function.args())
+        }
+        ("pow", _) => format!("POWER({})", function.args()),
+        ("log", 1) => format!("LOG10({})", arg(0)),
+        ("log", _) => format!("(LN({}) / LN({}))", arg(0), arg(1)),
+        ("len", _) => format!("CHAR_LENGTH({})", arg(0)),
+        ("substr", 2) => format!("SUBSTRING({} FROM {})", arg(0), arg(1)),
+        ("substr", _) => format!("SUBSTRING({} FROM {} FOR {})", arg(0), arg(1), arg(2)),
+        ("left", _) => format!("SUBSTRING({} FROM 1 FOR {})", arg(0), arg(1)),
+        //a start before the first character still ends at the end of the string
+        ("right", _) => format!(
+            "SUBSTRING({} FROM CHAR_LENGTH({}) - {} + 1)",
+            arg(0),
+            arg(0),
+            operand(1)
+        ),
+        ("contains", _) => format!("(POSITION({} IN {}) > 0)", arg(1), arg(0)),
+        ("startswith", _) => format!(
+            "(SUBSTRING({} FROM 1 FOR CHAR_LENGTH({})) = {})",
+            arg(0),
+            arg(1),
+            operand(1)
+        ),
+        ("concat", _) => concat_strings(dialect, function)?,
+        ("ifnull", _) => format!("COALESCE({})", function.args()),
+        ("zn", _) => format!("COALESCE({}, 0)", arg(0)),
+        //the fourth argument of iif is the result when the condition is NULL
+        ("iif", count) => {
+            let unknown = match count {
+                4 => format!(" ELSE {}", arg(3)),
+                _ => String::new(),
+            };
+            let not_condition = function.args[0].operand(NOT);
+            format!(
+                "CASE WHEN {} THEN {} WHEN NOT {} THEN {}{} END",
+                arg(0),
+                arg(1),
+                not_condition,
+                arg(2),
+                unknown
+            )
+        }
+        ("str", _) => cast(dialect, arg(0), ValueType::String)?,
+        ("int", _) => cast(dialect, arg(0), ValueType::Integer)?,
+        ("float", _) => cast(dialect, arg(0), ValueType::Number)?,
+        ("bool", _) => cast(dialect, arg(0), ValueType::Boolean)?,
+        ("today", _) => "CURRENT_DATE".to_string(),
+        ("now", _) => "LOCALTIMESTAMP".to_string(),
+        ("datepart", _) => format!("EXTRACT({} FROM {})", date_part_keyword(function.date_part()?), arg(1)),
+        ("datediff", _) => {
+            let (start, end) = (arg(1), arg(2));
+            let extract = |part| format!("EXTRACT({} FROM {}) - EXTRACT({} FROM {})", part, end, part, start);
+            match function.date_part()? {
+                DatePart::Year => format!("({})", extract("YEAR")),
+                DatePart::Quarter => format!("(({}) * 4 + {})", extract("YEAR"), extract("QUARTER")),
+                DatePart::Month => format!("(({}) * 12 + {})", extract("YEAR"), extract("MONTH")),
+                _ => return None,
+            }
+        }
+        ("dateadd", _) => {
+            let value_type = function.arg_type(2).unwrap_or(ValueType::Timestamp);
+            dialect.add_to_date(&operand(2), value_type, function.date_part()?, &operand(1))?
+        }
+        ("countd", _) => format!("COUNT(DISTINCT {})", arg(0)),
+        ("median", _) => format!("PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY {})", arg(0)),
+        ("percentile", _) => format!("PERCENTILE_CONT({}) WITHIN GROUP (ORDER BY {})", arg(1), arg(0)),
+        ("stddev", _) => format!("STDDEV_SAMP({})", arg(0)),
+        ("variance", _) => format!("VAR_SAMP({})", arg(0)),
+        _ => return None,
+    };
+    Some(sql)
+}
+
+/// ANSI SQL.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ansi;
+
+impl Dialect for Ansi {
+    fn name(&self) -> &str {
+        "ANSI SQL"
+    }
+}
+
+/// PostgreSQL 12 or later.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PostgreSql;
+
+impl Dialect for PostgreSql {
+    fn name(&self) -> &str {
+        "PostgreSQL"
+    }
+
+    fn interval_literal(&self, interval: Interval) -> Option<String> {
+        Some(format!("INTERVAL '{}'", interval_to_string(&interval)))
+    }
+
+    fn type_name(&self, value_type: ValueType) -> Option<&str> {
+        match value_type {
+            ValueType::String => Some("TEXT"),
+            ValueType::Decimal => Some("NUMERIC"),
+            value_type => Ansi.type_name(value_type),
+        }
+    }
+
+    //dates stay dates when whole days are added
+    fn add_to_date(&self, value: &str, value_type: ValueType, part: DatePart, amount: &str) -> Option<String> {
+        let (factor, unit) = interval_unit(part);
+        let shifted = format!("{} + {} * INTERVAL '1 {}'", value, scaled(amount, factor), unit);
+        match (value_type, unit) {
+            (ValueType::Date, "DAY") => Some(format!("({} + {})", value, scaled(amount, factor))),
+            (ValueType::Date, "YEAR" | "MONTH") => Some(format!("CAST({} AS DATE)", shifted)),
+            _ => Some(format!("({})", shifted)),
+        }
+    }
+
+    fn function(&self, function: &SqlFunction) -> Option<String> {
+        let arg = |index| function.arg(index);
+        let sql = match function.name() {
+            //there is no ROUND(DOUBLE PRECISION, INTEGER)
+            "round" if function.arg_count() == 2 && function.arg_type(0) == Some(ValueType::Number) => {
+                format!("ROUND(CAST({} AS NUMERIC), {})", arg(0), arg(1))
+            }
+            "len" => format!("LENGTH({})", arg(0)),
+            "left" | "right" => format!("{}({})", function.name().to_ascii_uppercase(), function.args()),
+            "contains" => format!("(STRPOS({}) > 0)", function.args()),
+            "startswith" => format!("STARTS_WITH({})", function.args()),
+            "split" => format!("SPLIT_PART({})", function.args()),
+            "date_trunc" => {
+                let truncated = format!("DATE_TRUNC('{}', {})", date_part_keyword(function.date_part()?), arg(1));
+                cast_back_to_date(truncated, function.arg_type(1))
+            }
+            "datediff" => {
+                let (start, end) = (arg(1), arg(2));
+                let seconds = match function.date_part()? {
+                    DatePart::Day => return Some(format!("(CAST({} AS DATE) - CAST({} AS DATE))", end, start)),
+                    DatePart::Week => 604_800,
+                    DatePart::Hour => 3_600,
+                    DatePart::Minute => 60,
+                    DatePart::Second => 1,
+                    _ => return standard_function(self, function),
+                };
+                let keyword = date_part_keyword(function.date_part()?);
+                let truncate = |value: &str| format!("DATE_TRUNC('{}', CAST({} AS TIMESTAMP))", keyword, value);
+                let difference = format!("EXTRACT(EPOCH FROM {} - {})", truncate(end), truncate(start));
+                let difference = match seconds {
+                    1 => difference,
+                    seconds => format!("{} / {}", difference, seconds),
+                };
+                format!("CAST({} AS BIGINT)", difference)
+            }
+            _ => return standard_function(self, function),
+        };
+        Some(sql)
+    }
+}
+
+/// SQLite 3.35 or later, with the math functions. Dates and timestamps are stored as text
+/// in the formats of the SQLite date functions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sqlite;
+
+impl Dialect for Sqlite {
+    fn name(&self) -> &str {
+        "SQLite"
+    }
+
+    fn date_literal(&self, date: NaiveDate) -> String {
+        format!("'{}'", date.format("%Y-%m-%d"))
+    }
+
+    fn timestamp_literal(&self, timestamp: NaiveDateTime) -> String {
+        format!("'{}'", timestamp.format("%Y-%m-%d %H:%M:%S%.f"))
+    }
+
+    fn interval_literal(&self, _interval: Interval) -> Option<String> {
+        None
+    }
+
+    fn type_name(&self, value_type: ValueType) -> Option<&str> {
+        match value_type {
+            ValueType::String | ValueType::Date | ValueType::Timestamp => Some("TEXT"),
+            ValueType::Integer => Some("INTEGER"),
+            ValueType::Decimal | ValueType::Number => Some("REAL"),
+            ValueType::Boolean | ValueType::Interval | ValueType::Null => None,
+        }
+    }
+
+    fn null_safe_equal(&self, left: &str, right: &str) -> String {
+        format!("{} IS {}", left, right)
+    }
+
+    fn supports_interval_arithmetic(&self) -> bool {
+        false
+    }
+
+    fn add_to_date(&self, value: &str, value_type: ValueType, part: DatePart, amount: &str) -> Option<String> {
+        let (factor, unit) = interval_unit(part);
+        let modifier = match unit {
+            "SECOND" => "%+f seconds".to_string(),
+            unit => format!("%+d {}s", unit.to_ascii_lowercase()),
+        };
+        Some(format!(
+            "{}({}, PRINTF('{}', {}))",
+            date_function(value_type, part),
+            value,
+            modifier,
+            scaled(amount, factor)
+        ))
+    }
+
+    fn function(&self, function: &SqlFunction) -> Option<String> {
+        let (arg, operand) = (|index| function.arg(index), |index| function.operand(index));
+        let integers = (0..function.arg_count()).all(|index| function.arg_type(index) == Some(ValueType::Integer));
+        let sql = match function.name() {
+            //`%` converts its operands to integers
+            "mod" if integers => format!("({} % {})", operand(0), operand(1)),
+            "mod" => return None,
+            "len" => format!("LENGTH({})", arg(0)),
+            "substr" => format!("SUBSTR({})", function.args()),
+            "left" => format!("SUBSTR({}, 1, {})", arg(0), arg(1)),
+            "right" => format!("SUBSTR({}, MAX(LENGTH({}) - {} + 1, 1))", arg(0), arg(0), operand(1)),
+            "contains" => format!("(INSTR({}) > 0)", function.args()),
+            "startswith" => format!("(SUBSTR({}, 1, LENGTH({})) = {})", arg(0), arg(1), operand(1)),
+            "today" => "DATE('now', 'localtime')".to_string(),
+            "now" => "DATETIME('now', 'localtime')".to_string(),
+            "datepart" => {
+                let value = arg(1);
+                match function.date_part()? {
+                    DatePart::Week => return None,
+                    DatePart::Quarter => format!("(({} + 2) / 3)", sqlite_part(value, "%m")),
+                    part => sqlite_part(value, sqlite_format(part)),
+                }
+            }
+            "date_trunc" => sqlite_truncate(arg(1), function.arg_type(1), function.date_part()?),
+            "datediff" => {
+                let (start, end) = (arg(1), arg(2));
+                let year = format!("{} - {}", sqlite_part(end, "%Y"), sqlite_part(start, "%Y"));
+                let month = |value| sqlite_part(value, "%m");
+                let quarter = |value| format!("({} + 2) / 3", month(value));
+                let part = function.date_part()?;
+                let scale = match part {
+                    DatePart::Year => return Some(format!("({})", year)),
+                    DatePart::Quarter => {
+                        return Some(format!("(({}) * 4 + {} - {})", year, quarter(end), quarter(start)))
+                    }
+                    DatePart::Month => return Some(format!("(({}) * 12 + {} - {})", year, month(end), month(start))),
+                    DatePart::Week => " / 7",
+                    DatePart::Day => "",
+                    DatePart::Hour => " * 24",
+                    DatePart::Minute => " * 1440",
+                    DatePart::Second => " * 86400",
+                };
+                //both values are truncated to the unit, the difference is a whole number of units
+                let truncate = |value| sqlite_truncate(value, Some(ValueType::Timestamp), part);
+                let days = format!("(JULIANDAY({}) - JULIANDAY({}))", truncate(end), truncate(start));
+                format!("CAST(ROUND({}{}) AS INTEGER)", days, scale)
+            }
+            "median" | "percentile" | "stddev" | "variance" => return None,
+            _ => return standard_function(self, function),
+        };
+        Some(sql)
+    }
+}
+
+/// DuckDB.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DuckDb;
+
+impl Dialect for DuckDb {
+    fn name(&self) -> &str {
+        "DuckDB"
+    }
+
+    fn interval_literal(&self, interval: Interval) -> Option<String> {
+        PostgreSql.interval_literal(interval)
+    }
+
+    fn type_name(&self, value_type: ValueType) -> Option<&str> {
+        match value_type {
+            ValueType::Decimal => Some("DECIMAL(38, 10)"),
+            ValueType::Number => Some("DOUBLE"),
+            value_type => Ansi.type_name(value_type),
+        }
+    }
+
+    fn concat(&self, parts: &[String]) -> String {
+        format!("CONCAT({})", parts.join(", "))
+    }
+
+    //`/` is the floating point division
+    fn truncates_integer_division(&self) -> bool {
+        false
+    }
+
+    fn add_to_date(&self, value: &str, value_type: ValueType, part: DatePart, amount: &str) -> Option<String> {
+        let (factor, unit) = interval_unit(part);
+        let amount = scaled(amount, factor);
+        let shifted = format!("{} + TO_{}S({})", value, unit, amount);
+        match (value_type, unit) {
+            (ValueType::Date, "DAY") => Some(format!("({} + {})", value, amount)),
+            (ValueType::Date, "YEAR" | "MONTH") => Some(format!("CAST({} AS DATE)", shifted)),
+            _ => Some(format!("({})", shifted)),
+        }
+    }
+
+    fn function(&self, function: &SqlFunction) -> Option<String> {
+        let arg = |index| function.arg(index);
+        let sql = match function.name() {
+            "mod" => format!("({} % {})", function.operand(0), function.operand(1)),
+            "len" => format!("LENGTH({})", arg(0)),
+            "left" | "right" => format!("{}({})", function.name().to_ascii_uppercase(), function.args()),
+            "contains" => format!("CONTAINS({})", function.args()),
+            "startswith" => format!("STARTS_WITH({})", function.args()),
+            "split" => format!("SPLIT_PART({})", function.args()),
+            "now" => "CAST(CURRENT_TIMESTAMP AS TIMESTAMP)".to_string(),
+            "date_trunc" => {
+                let truncated = format!("DATE_TRUNC('{}', {})", date_part_keyword(function.date_part()?), arg(1));
+                cast_back_to_date(truncated, function.arg_type(1))
+            }
+            "datediff" => {
+                let keyword = date_part_keyword(function.date_part()?);
+                format!("DATE_DIFF('{}', {}, {})", keyword, arg(1), arg(2))
+            }
+            "median" => format!("MEDIAN({})", arg(0)),
+            "percentile" => format!("QUANTILE_CONT({})", function.args()),
+            _ => return standard_function(self, function),
+        };
+        Some(sql)
+    }
+}
+
+/// MySQL 8.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MySql;
+
+impl Dialect for MySql {
+    fn name(&self) -> &str {
+        "MySQL"
+    }
+
+    fn quote_identifier(&self, identifier: &str) -> String {
+        format!("`{}`", identifier.replace('`', "``"))
+    }
+
+    //backslashes start escape sequences in MySQL strings
+    fn string_literal(&self, value: &str) -> String {
+        format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''"))
+    }
+
+    fn interval_literal(&self, _interval: Interval) -> Option<String> {
+        None
+    }
+
+    fn type_name(&self, value_type: ValueType) -> Option<&str> {
+        match value_type {
+            ValueType::String => Some("CHAR"),
+            ValueType::Integer => Some("SIGNED"),
+            ValueType::Decimal => Some("DECIMAL(65, 30)"),
+            ValueType::Number => Some("DOUBLE"),
+            ValueType::Timestamp => Some("DATETIME"),
+            ValueType::Date => Some("DATE"),
+            ValueType::Boolean | ValueType::Interval | ValueType::Null => None,
+        }
+    }
+
+    //`||` is a logical operator in MySQL
+    fn concat(&self, parts: &[String]) -> String {
+        format!("CONCAT({})", parts.join(", "))
+    }
+
+    fn null_safe_equal(&self, left: &str, right: &str) -> String {
+        format!("{} <=> {}", left, right)
+    }
+
+    fn truncates_integer_division(&self) -> bool {
+        false
+    }
+
+    fn supports_interval_arithmetic(&self) -> bool {
+        false
+    }
+
+    fn add_to_date(&self, value: &str, _value_type: ValueType, part: DatePart, amount: &str) -> Option<String> {
+        Some(format!(
+            "DATE_ADD({}, INTERVAL {} {})",
+            value,
+            amount,
+            date_part_keyword(part)
+        ))
+    }
+
+    fn function(&self, function: &SqlFunction) -> Option<String> {
+        let arg = |index| function.arg(index);
+        let sql = match function.name() {
+            "left" | "right" => format!("{}({})", function.name().to_ascii_uppercase(), function.args()),
+            "startswith" => format!("(LEFT({}, CHAR_LENGTH({})) = {})", arg(0), arg(1), function.operand(1)),
+            //ISO weeks, like the built-in datepart
+            "datepart" if function.date_part() == Some(DatePart::Week) => format!("WEEK({}, 3)", arg(1)),
+            "datediff" if function.date_part() == Some(DatePart::Day) => format!("DATEDIFF({}, {})", arg(2), arg(1)),
+            "datediff"
+                if !matches!(
+                    function.date_part(),
+                    Some(DatePart::Year | DatePart::Quarter | DatePart::Month)
+                ) =>
+            {
+                return None
+            }
+            "median" | "percentile" => return None,
+            _ => return standard_function(self, function),
+        };
+        Some(sql)
+    }
+}
+
+/// Google BigQuery standard SQL. Timestamps without a time zone are `DATETIME` values.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BigQuery;
+
+impl Dialect for BigQuery {
+    fn name(&self) -> &str {
+        "BigQuery"
+    }
+
+    fn quote_identifier(&self, identifier: &str) -> String {
+        format!("`{}`", identifier.replace('\\', "\\\\").replace('`', "\\`"))
+    }
+
+    fn string_literal(&self, value: &str) -> String {
+        format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+    }
+
+    fn timestamp_literal(&self, timestamp: NaiveDateTime) -> String {
+        format!("DATETIME '{}'", timestamp.format("%Y-%m-%d %H:%M:%S%.f"))
+    }
+
+    fn interval_literal(&self, _interval: Interval) -> Option<String> {
+        None
+    }
+
+    fn type_name(&self, value_type: ValueType) -> Option<&str> {
+        match value_type {
+            ValueType::String => Some("STRING"),
+            ValueType::Integer => Some("INT64"),
+            ValueType::Decimal => Some("NUMERIC"),
+            ValueType::Number => Some("FLOAT64"),
+            ValueType::Boolean => Some("BOOL"),
+            ValueType::Date => Some("DATE"),
+            ValueType::Timestamp => Some("DATETIME"),
+            ValueType::Interval | ValueType::Null => None,
+        }
+    }
+
+    fn concat(&self, parts: &[String]) -> String {
+        format!("CONCAT({})", parts.join(", "))
+    }
+
+    fn truncates_integer_division(&self) -> bool {
+        false
+    }
+
+    fn supports_interval_arithmetic(&self) -> bool {
+        false
+    }
+
+    fn add_to_date(&self, value: &str, value_type: ValueType, part: DatePart, amount: &str) -> Option<String> {
+        let keyword = date_part_keyword(part);
+        match (value_type, is_time_of_day(part)) {
+            (ValueType::Date, false) => Some(format!("DATE_ADD({}, INTERVAL {} {})", value, amount, keyword)),
+            _ => Some(format!(
+                "DATETIME_ADD({}, INTERVAL {} {})",
+                bigquery_datetime(value, Some(value_type)),
+                amount,
+                keyword
+            )),
+        }
+    }
+
+    fn function(&self, function: &SqlFunction) -> Option<String> {
+        let arg = |index| function.arg(index);
+        //weeks start on Monday, like the built-in date functions
+        let keyword = |part| match part {
+            DatePart::Week => "ISOWEEK",
+            part => date_part_keyword(part),
+        };
+        let sql = match function.name() {
+            "len" => format!("LENGTH({})", arg(0)),
+            "substr" => format!("SUBSTR({})", function.args()),
+            "left" | "right" => format!("{}({})", function.name().to_ascii_uppercase(), function.args()),
+            "contains" => format!("(STRPOS({}) > 0)", function.args()),
+            "startswith" => format!("STARTS_WITH({})", function.args()),
+            "split" => format!("SPLIT({}, {})[SAFE_ORDINAL({})]", arg(0), arg(1), arg(2)),
+            "today" => "CURRENT_DATE()".to_string(),
+            "now" => "CURRENT_DATETIME()".to_string(),
+            "datepart" => format!("EXTRACT({} FROM {})", keyword(function.date_part()?), arg(1)),
+            "date_trunc" => match (function.arg_type(1), function.date_part()?) {
+                //dates have no time of day to truncate
+                (Some(ValueType::Date), part) if is_time_of_day(part) => arg(1).to_string(),
+                (Some(ValueType::Date), part) => format!("DATE_TRUNC({}, {})", arg(1), keyword(part)),
+                (_, part) => format!("DATETIME_TRUNC({}, {})", arg(1), keyword(part)),
+            },
+            "datediff" => {
+                let part = function.date_part()?;
+                let dates =
+                    function.arg_type(1) == Some(ValueType::Date) && function.arg_type(2) == Some(ValueType::Date);
+                if dates && !is_time_of_day(part) {
+                    format!("DATE_DIFF({}, {}, {})", arg(2), arg(1), keyword(part))
+                } else {
+                    format!(
+                        "DATETIME_DIFF({}, {}, {})",
+                        bigquery_datetime(arg(2), function.arg_type(2)),
+                        bigquery_datetime(arg(1), function.arg_type(1)),
+                        keyword(part)
+                    )
+                }
+            }
+            "median" | "percentile" => return None,
+            _ => return standard_function(self, function),
+        };
+        Some(sql)
+    }
+}
+
+/// SQL for a sub-expression, with the precedence of its outermost operator.
+#[derive(Debug, Clone)]
+struct Sql {
+    text: String,
+    precedence: u8,
+}
+
+impl Sql {
+    fn new(text: String, precedence: u8) -> Self {
+        Sql { text, precedence }
+    }
+
+    fn atom(text: String) -> Self {
+        Sql::new(text, ATOM)
+    }
+
+    //the SQL as an operand of an operator with the given precedence
+    fn operand(&self, precedence: u8) -> Cow<'_, str> {
+        if self.precedence >= precedence {
+            Cow::Borrowed(&self.text)
+        } else {
+            Cow::Owned(format!("({})", self.text))
+        }
+    }
+}
+
+struct Translator<'d> {
+    dialect: &'d dyn Dialect,
+    //SQL of the fields when they are read from a query instead of the tables, by field id
+    columns: Option<&'d HashMap<String, String>>,
+}
+
+impl Translator<'_> {
+    fn translate(&self, typed: &TypedExpression) -> Result<Sql, Error> {
+        match typed.expression {
+            Expression::Literal { value, span } => self.literal(value, *span),
+            Expression::FieldReference { field_id, .. } => {
+                if let Some(column) = self.columns.and_then(|columns| columns.get(field_id)) {
+                    return Ok(Sql::atom(column.clone()));
+                }
+                Ok(Sql::atom(field_sql(self.dialect, field_id)))
+            }
+            Expression::Function {
+                function_name, span, ..
+            } => {
+                let args = self.translate_children(typed)?;
+                if is_operator(function_name) {
+                    self.operator(function_name, args, typed, *span)
+                } else {
+                    self.function(function_name, args, typed, *span)
+                }
+            }
+            //a NULL condition takes the ELSE branch, like in the evaluator
+            Expression::IfExpression { .. } => {
+                let children = self.translate_children(typed)?;
+                Ok(Sql::atom(format!(
+                    "CASE WHEN {} THEN {} ELSE {} END",
+                    children[0].text, children[1].text, children[2].text
+                )))
+            }
+            Expression::CaseExpression { else_result, .. } => {
+                let children = self.translate_children(typed)?;
+                let branches = children.len() - usize::from(else_result.is_some());
+                let mut sql = "CASE".to_string();
+                for branch in children[..branches].chunks(2) {
+                    sql.push_str(&format!(" WHEN {} THEN {}", branch[0].text, branch[1].text));
+                }
+                if let Some(else_result) = children.get(branches) {
+                    sql.push_str(&format!(" ELSE {}", else_result.text));
+                }
+                sql.push_str(" END");
+                Ok(Sql::atom(sql))
+            }
+            Expression::ModifierExpression { span, .. } => Err(self.unsupported("WHERE and GROUP BY modifiers", *span)),
+        }
+    }
+
+    fn translate_children(&self, typed: &TypedExpression) -> Result<Vec<Sql>, Error> {
+        typed.children.iter().map(|child| self.translate(child)).collect()
+    }
+
+    fn literal(&self, value: &LiteralValue, span: Span) -> Result<Sql, Error> {
+        let sql = match value {
+            LiteralValue::StringValue(value) => self.dialect.string_literal(value),
+            //the exponent makes it a floating point number in SQL as well
+            LiteralValue::NumberValue(value) if value.is_finite() => format!("{:e}", value),
+            LiteralValue::NumberValue(_) => return Err(self.unsupported("infinite numbers", span)),
+            LiteralValue::IntegerValue(value) => value.to_string(),
+            LiteralValue::DecimalValue(value) => value.to_string(),
+            LiteralValue::BooleanValue(value) => if *value { "TRUE" } else { "FALSE" }.to_string(),
+            LiteralValue::DateValue(value) => self.dialect.date_literal(*value),
+            LiteralValue::TimestampValue(value) => self.dialect.timestamp_literal(*value),
+            LiteralValue::IntervalValue(value) => self
+                .dialect
+                .interval_literal(*value)
+                .ok_or_else(|| self.unsupported("interval literal", span))?,
+            LiteralValue::NullValue => "NULL".to_string(),
+        };
+        Ok(Sql::atom(sql))
+    }
+
+    fn operator(&self, operator: &str, args: Vec<Sql>, typed: &TypedExpression, span: Span) -> Result<Sql, Error> {
+        let operator = operator.to_ascii_lowercase();
+        let sql = match (operator.as_str(), args.as_slice()) {
+            ("and" | "or", args) => {
+                let (keyword, precedence) = if operator == "and" {
+                    (" AND ", AND)
+                } else {
+                    (" OR ", OR)
+                };
+                let operands: Vec<Cow<str>> = args.iter().map(|arg| arg.operand(precedence)).collect();
+                Sql::new(operands.join(keyword), precedence)
+            }
+            ("not", [operand]) => Sql::new(format!("NOT {}", operand.operand(NOT)), NOT),
+            ("is null", [operand]) => Sql::new(format!("{} IS NULL", operand.operand(COMPARISON + 1)), COMPARISON),
+            ("is not null", [operand]) => {
+                Sql::new(format!("{} IS NOT NULL", operand.operand(COMPARISON + 1)), COMPARISON)
+            }
+            ("=" | "!=" | "<" | "<=" | ">" | ">=", [left, right]) => {
+                let operator = if operator == "!=" { "<>" } else { operator.as_str() };
+                let (left, right) = (left.operand(COMPARISON + 1), right.operand(COMPARISON + 1));
+                Sql::new(format!("{} {} {}", left, operator, right), COMPARISON)
+            }
+            ("+" | "-" | "*" | "/", [left, right]) => return self.arithmetic(&operator, left, right, typed, span),
+            _ => return Err(self.unsupported(&format!("operator `{}`", operator), span)),
+        };
+        Ok(sql)
+    }
+
+    fn arithmetic(
+        &self,
+        operator: &str,
+        left: &Sql,
+        right: &Sql,
+        typed: &TypedExpression,
+        span: Span,
+    ) -> Result<Sql, Error> {
+        use ValueType::{Date, Integer, Interval, Timestamp};
+
+        let (left_type, right_type) = (typed.children[0].value_type, typed.children[1].value_type);
+        let shifted = |sql: Option<String>| {
+            sql.map(Sql::atom)
+                .ok_or_else(|| self.unsupported("date arithmetic", span))
+        };
+        match (operator, left_type, right_type) {
+            ("+" | "-", Some(value_type @ (Date | Timestamp)), Some(Interval)) => {
+                if let Some(interval) = interval_literal(&typed.children[1]) {
+                    return shifted(self.shift(left, value_type, interval, operator == "-"));
+                }
+            }
+            ("+", Some(Interval), Some(value_type @ (Date | Timestamp))) => {
+                if let Some(interval) = interval_literal(&typed.children[0]) {
+                    return shifted(self.shift(right, value_type, interval, false));
+                }
+            }
+            ("+" | "-", Some(Date), Some(Integer)) => {
+                //only negative literals start with a minus, `--` would start a comment
+                let days = match (operator, right.operand(ATOM)) {
+                    ("-", Cow::Borrowed(days)) if days.starts_with('-') => Cow::Borrowed(&days[1..]),
+                    ("-", days) => Cow::Owned(format!("-{}", days)),
+                    (_, days) => days,
+                };
+                return shifted(
+                    self.dialect
+                        .add_to_date(&left.operand(ATOM), Date, DatePart::Day, &days),
+                );
+            }
+            ("+", Some(Integer), Some(Date)) => {
+                return shifted(self.dialect.add_to_date(
+                    &right.operand(ATOM),
+                    Date,
+                    DatePart::Day,
+                    &left.operand(ATOM),
+                ));
+            }
+            //the number of days between two dates
+            ("-", Some(Date), Some(Date)) => {
+                let function = SqlFunction {
+                    name: "datediff".to_string(),
+                    args: vec![
+                        Sql::atom(self.dialect.string_literal("day")),
+                        right.clone(),
+                        left.clone(),
+                    ],
+                    arg_types: vec![Some(ValueType::String), Some(Date), Some(Date)],
+                    date_part: Some(DatePart::Day),
+                };
+                return shifted(self.dialect.function(&function));
+            }
+            ("/", Some(Integer), Some(Integer)) if self.dialect.truncates_integer_division() => {
+                let decimal = self
+                    .dialect
+                    .type_name(ValueType::Decimal)
+                    .ok_or_else(|| self.unsupported("decimal division", span))?;
+                let sql = format!(
+                    "CAST({} AS {}) / {}",
+                    left.text,
+                    decimal,
+                    right.operand(MULTIPLICATION + 1)
+                );
+                return Ok(Sql::new(sql, MULTIPLICATION));
+            }
+            _ => {}
+        }
+        let temporal = [left_type, right_type]
+            .iter()
+            .any(|value_type| matches!(value_type, Some(Date | Timestamp | Interval)));
+        if temporal && !self.dialect.supports_interval_arithmetic() {
+            return Err(self.unsupported("date and interval arithmetic", span));
+        }
+        let precedence = match operator {
+            "+" | "-" => ADDITION,
+            _ => MULTIPLICATION,
+        };
+        let (left, right) = (left.operand(precedence), right.operand(precedence + 1));
+        Ok(Sql::new(format!("{} {} {}", left, operator, right), precedence))
+    }
+
+    //add the parts of an interval literal one by one, like the evaluator shifts dates
+    fn shift(&self, value: &Sql, value_type: ValueType, interval: Interval, negate: bool) -> Option<String> {
+        let sign = if negate { -1 } else { 1 };
+        let parts = [
+            (DatePart::Month, i64::from(interval.months) * sign, String::new()),
+            (DatePart::Day, i64::from(interval.days) * sign, String::new()),
+            (
+                DatePart::Second,
+                interval.microseconds * sign,
+                seconds(interval.microseconds * sign),
+            ),
+        ];
+        let mut sql = value.operand(ATOM).into_owned();
+        let mut value_type = value_type;
+        for (part, amount, seconds) in parts {
+            if amount == 0 {
+                continue;
+            }
+            let amount = if part == DatePart::Second {
+                seconds
+            } else {
+                amount.to_string()
+            };
+            sql = self.dialect.add_to_date(&sql, value_type, part, &amount)?;
+            if part == DatePart::Second {
+                value_type = ValueType::Timestamp;
+            }
+        }
+        Some(sql)
+    }
+
+    fn function(&self, function_name: &str, args: Vec<Sql>, typed: &TypedExpression, span: Span) -> Result<Sql, Error> {
+        let name = function_name.to_ascii_lowercase();
+        let date_part = match name.as_str() {
+            "datepart" | "date_trunc" | "datediff" | "dateadd" => Some(self.date_part(&name, &typed.children[0])?),
+            _ => None,
+        };
+        let function = SqlFunction {
+            name,
+            args,
+            arg_types: typed.children.iter().map(|child| child.value_type).collect(),
+            date_part,
+        };
+        self.dialect
+            .function(&function)
+            .map(Sql::atom)
+            .ok_or_else(|| self.unsupported(&format!("function `{}`", function_name), span))
+    }
+
+    //the unit has to be known to generate SQL, so it has to be a literal
+    fn date_part(&self, function_name: &str, typed: &TypedExpression) -> Result<DatePart, Error> {
+        let span = typed.expression.span();
+        match typed.expression {
+            Expression::Literal {
+                value: LiteralValue::StringValue(name),
+                ..
+            } => DatePart::parse(name).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidArgument {
+                        function_name: function_name.to_string(),
+                        reason: format!("unknown date part `{}`", name),
+                    },
+                    span,
+                )
+            }),
+            _ => Err(self.unsupported("date part that is not a string literal", span)),
+        }
+    }
+
+    fn unsupported(&self, feature: &str, span: Span) -> Error {
+        Error::new(
+            ErrorKind::UnsupportedInDialect {
+                dialect: self.dialect.name().to_string(),
+                feature: feature.to_string(),
+            },
+            span,
+        )
+    }
+}
+
+//the parts of dotted field ids are qualified names, e.g. `orders.date` is a column of `orders`
+fn field_sql(dialect: &dyn Dialect, field_id: &str) -> String {
+    let parts: Vec<String> = field_id.split('.').map(|part| dialect.quote_identifier(part)).collect();
+    parts.join(".")
+}
+
+fn first_error(mut errors: Vec<Error>) -> Error {
+    errors.remove(0)
+}
+
+fn interval_literal(typed: &TypedExpression) -> Option<Interval> {
+    match typed.expression {
+        Expression::Literal {
+            value: LiteralValue::IntervalValue(interval),
+            ..
+        } => Some(*interval),
+        _ => None,
+    }
+}
+
+fn seconds(microseconds: i64) -> String {
+    Decimal::new(microseconds, 6).normalize().to_string()
+}
+
+//`amount` is an operand, multiplying it keeps it one
+fn scaled(amount: &str, factor: i64) -> String {
+    match factor {
+        1 => amount.to_string(),
+        factor => format!("{} * {}", factor, amount),
+    }
+}
+
+//units of intervals in all dialects, quarters and weeks are expressed in months and days
+fn interval_unit(part: DatePart) -> (i64, &'static str) {
+    match part {
+        DatePart::Year => (1, "YEAR"),
+        DatePart::Quarter => (3, "MONTH"),
+        DatePart::Month => (1, "MONTH"),
+        DatePart::Week => (7, "DAY"),
+        DatePart::Day => (1, "DAY"),
+        DatePart::Hour => (1, "HOUR"),
+        DatePart::Minute => (1, "MINUTE"),
+        DatePart::Second => (1, "SECOND"),
+    }
+}
+
+fn date_part_keyword(part: DatePart) -> &'static str {
+    match part {
+        DatePart::Year => "YEAR",
+        DatePart::Quarter => "QUARTER",
+        DatePart::Month => "MONTH",
+        DatePart::Week => "WEEK",
+        DatePart::Day => "DAY",
+        DatePart::Hour => "HOUR",
+        DatePart::Minute => "MINUTE",
+        DatePart::Second => "SECOND",
+    }
+}
+
+fn is_time_of_day(part: DatePart) -> bool {
+    matches!(part, DatePart::Hour | DatePart::Minute | DatePart::Second)
+}
+
+fn cast<D: Dialect + ?Sized>(dialect: &D, sql: &str, value_type: ValueType) -> Option<String> {
+    Some(format!("CAST({} AS {})", sql, dialect.type_name(value_type)?))
+}
+
+//concat converts its arguments to strings and skips NULL values, booleans are `true` and `false`
+fn concat_strings<D: Dialect + ?Sized>(dialect: &D, function: &SqlFunction) -> Option<String> {
+    let mut parts = Vec::new();
+    for index in 0..function.arg_count() {
+        let part = match function.arg_type(index) {
+            Some(ValueType::Null) => continue,
+            Some(ValueType::String) => function.arg(index).to_string(),
+            Some(ValueType::Boolean) => format!(
+                "CASE WHEN {} THEN {} WHEN NOT {} THEN {} END",
+                function.arg(index),
+                dialect.string_literal("true"),
+                function.args[index].operand(NOT),
+                dialect.string_literal("false")
+            ),
+            _ => cast(dialect, function.arg(index), ValueType::String)?,
+        };
+        parts.push(format!("COALESCE({}, '')", part));
+    }
+    if parts.is_empty() {
+        return Some(dialect.string_literal(""));
+    }
+    Some(dialect.concat(&parts))
+}
+
+//truncating dates produces timestamps in PostgreSQL and DuckDB, the evaluator keeps dates
+fn cast_back_to_date(truncated: String, value_type: Option<ValueType>) -> String {
+    match value_type {
+        Some(ValueType::Date) => format!("CAST({} AS DATE)", truncated),
+        _ => truncated,
+    }
+}
+
+//dates stay dates when they are shifted by whole days
+fn date_function(value_type: ValueType, part: DatePart) -> &'static str {
+    if value_type == ValueType::Date && !is_time_of_day(part) {
+        "DATE"
+    } else {
+        "DATETIME"
+    }
+}
+
+fn sqlite_part(value: &str, format: &str) -> String {
+    format!("CAST(STRFTIME('{}', {}) AS INTEGER)", format, value)
+}
+
+fn sqlite_format(part: DatePart) -> &'static str {
+    match part {
+        DatePart::Year => "%Y",
+        DatePart::Month | DatePart::Quarter => "%m",
+        DatePart::Week => "%W",
+        DatePart::Day => "%d",
+        DatePart::Hour => "%H",
+        DatePart::Minute => "%M",
+        DatePart::Second => "%S",
+    }
+}
+
+//start of the unit the value falls into, weeks start on Monday
+fn sqlite_truncate(value: &str, value_type: Option<ValueType>, part: DatePart) -> String {
+    let function = date_function(value_type.unwrap_or(ValueType::Timestamp), DatePart::Day);
+    match part {
+        DatePart::Year => format!("{}({}, 'start of year')", function, value),
+        DatePart::Quarter => format!(
+            "{}({}, 'start of month', PRINTF('-%d months', ({} - 1) % 3))",
+            function,
+            value,
+            sqlite_part(value, "%m")
+        ),
+        DatePart::Month => format!("{}({}, 'start of month')", function, value),
+        DatePart::Week => format!("{}({}, 'start of day', '-6 days', 'weekday 1')", function, value),
+        DatePart::Day => format!("{}({}, 'start of day')", function, value),
+        //dates have no time of day to truncate
+        _ if value_type == Some(ValueType::Date) => value.to_string(),
+        DatePart::Hour => format!("STRFTIME('%Y-%m-%d %H:00:00', {})", value),
+        DatePart::Minute => format!("STRFTIME('%Y-%m-%d %H:%M:00', {})", value),
+        DatePart::Second => format!("STRFTIME('%Y-%m-%d %H:%M:%S', {})", value),
+    }
+}
+
+fn bigquery_datetime(value: &str, value_type: Option<ValueType>) -> String {
+    match value_type {
+        Some(ValueType::Date) => format!("CAST({} AS DATETIME)", value),
+        _ => value.to_string(),
+    }
+}
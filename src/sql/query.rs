@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+use std::iter;
+
+use super::{field_sql, first_error, Dialect, Sql, Translator, COMPARISON};
+use crate::aggregate::{is_aggregate, map_children, DashboardFilter, FilterScope};
+use crate::ast::{func, if_expr, lit_int, lit_null, Expression, GroupByContext, GroupReference, Span, ValueType};
+use crate::error::{Error, ErrorKind};
+use crate::functions::FunctionRegistry;
+use crate::lod;
+use crate::schema::Schema;
+use crate::typecheck::check_types;
+
+//values of GROUP BY modifiers are resolved as fields with this prefix, which field references cannot have
+const LEVEL_PREFIX: &str = "#level of detail ";
+
+/// Translates an expression to a query that computes it for every group of a query grouped by
+/// `query_groups`, with the same results as [`evaluate_grouped`](crate::evaluate_grouped).
+///
+/// `from` is the SQL of the FROM clause, e.g. a table name. The query returns a column
+/// `group_N` for every query group and the result in the column `value`. Without modifiers
+/// the dashboard filters become the WHERE clause. WHERE modifiers restrict the rows of the
+/// aggregates inside them with `CASE` expressions instead, and a HAVING clause removes the
+/// groups without rows that pass the dashboard filters. Every GROUP BY modifier is computed
+/// in a common table expression grouped by its level of detail and joined to the rows, so
+/// outer aggregates and modifiers can use its value like a field. Outside of aggregates the
+/// value of a GROUP BY modifier is taken with `MIN`, and checked against `MAX` unless the
+/// modifier groups by a subset of the query groups. Groups with different values get NULL,
+/// where the evaluator fails with [`ErrorKind::AmbiguousLevelOfDetail`].
+///
+/// ```
+/// use analytical_expression_parser::ast::ValueType;
+/// use analytical_expression_parser::sql::{to_sql_query, PostgreSql};
+/// use analytical_expression_parser::{parse, DashboardFilter, Schema};
+///
+/// let schema = Schema::new()
+///     .with_field("region", ValueType::String)
+///     .with_field("sales", ValueType::Integer);
+/// let expression = parse("sum(sales) [where ignore filters on region]").unwrap();
+/// let filters = [DashboardFilter::new("region", parse(r#"region = "EU""#).unwrap())];
+/// let sql = to_sql_query(&expression, &schema, &PostgreSql, "orders", &[], &filters).unwrap();
+/// assert_eq!(sql, r#"SELECT SUM("sales") AS "value"
+/// FROM orders"#);
+/// ```
+pub fn to_sql_query(
+    expression: &Expression,
+    schema: &Schema,
+    dialect: &dyn Dialect,
+    from: &str,
+    query_groups: &[Expression],
+    dashboard_filters: &[DashboardFilter],
+) -> Result<String, Error> {
+    schema
+        .validate_modifiers(expression, query_groups.len())
+        .map_err(first_error)?;
+    let conditions = dashboard_filters.iter().map(|filter| &filter.condition);
+    for checked in iter::once(expression).chain(query_groups).chain(conditions) {
+        check_types(checked, schema, FunctionRegistry::builtins()).map_err(first_error)?;
+    }
+
+    let mut planner = Planner {
+        dialect,
+        schema: schema.clone(),
+        query_groups,
+        columns: None,
+        ctes: Vec::new(),
+        rows: from.to_string(),
+        levels: 0,
+    };
+    if lod::has_level_of_detail(expression) {
+        planner.read_fields(from, expression, dashboard_filters);
+    }
+    //without modifiers all aggregates use the same rows, which the WHERE clause selects
+    let modifiers = has_modifiers(expression);
+    let scope_filters = if modifiers { dashboard_filters } else { &[] };
+    let resolved = planner.resolve(expression, &FilterScope::new(scope_filters), false)?;
+
+    let groups = query_groups
+        .iter()
+        .map(|group| Ok(planner.translate(group)?.text))
+        .collect::<Result<Vec<_>, Error>>()?;
+    let mut columns: Vec<String> = groups
+        .iter()
+        .enumerate()
+        .map(|(index, group)| {
+            format!(
+                "{} AS {}",
+                group,
+                dialect.quote_identifier(&format!("group_{}", index + 1))
+            )
+        })
+        .collect();
+    columns.push(format!(
+        "{} AS {}",
+        planner.translate(&resolved)?.text,
+        dialect.quote_identifier("value")
+    ));
+
+    let mut sql = String::new();
+    if !planner.ctes.is_empty() {
+        sql.push_str(&format!("WITH {}\n", planner.ctes.join(",\n")));
+    }
+    sql.push_str(&format!("SELECT {}\nFROM {}", columns.join(", "), planner.rows));
+    let condition = all_of(dashboard_filters.iter().map(|filter| filter.condition.clone()));
+    if let (false, Some(condition)) = (modifiers, &condition) {
+        sql.push_str(&format!("\nWHERE {}", planner.translate(condition)?.text));
+    }
+    if !groups.is_empty() {
+        sql.push_str(&format!("\nGROUP BY {}", groups.join(", ")));
+        //groups that the dashboard filters remove completely are not part of the result
+        if let (true, Some(condition)) = (modifiers, condition) {
+            let visible_rows = func("count", vec![if_expr(condition, lit_int(1), lit_null())]);
+            let having = func(">", vec![visible_rows, lit_int(0)]);
+            sql.push_str(&format!("\nHAVING {}", planner.translate(&having)?.text));
+        }
+    }
+    Ok(sql)
+}
+
+struct Planner<'a> {
+    dialect: &'a dyn Dialect,
+    //fields of the data source and the values of the GROUP BY modifiers planned so far
+    schema: Schema,
+    query_groups: &'a [Expression],
+    //columns of the fields when they are read from the common table expressions
+    columns: Option<HashMap<String, String>>,
+    ctes: Vec<String>,
+    //relation that has the fields and the values of the GROUP BY modifiers
+    rows: String,
+    levels: usize,
+}
+
+impl<'a> Planner<'a> {
+    fn translate(&self, expression: &Expression) -> Result<Sql, Error> {
+        let typed = check_types(expression, &self.schema, FunctionRegistry::builtins()).map_err(first_error)?;
+        let translator = Translator {
+            dialect: self.dialect,
+            columns: self.columns.as_ref(),
+        };
+        translator.translate(&typed)
+    }
+
+    fn quote(&self, identifier: &str) -> String {
+        self.dialect.quote_identifier(identifier)
+    }
+
+    //read the rows with all fields of the expression into a common table expression, the values of
+    //GROUP BY modifiers are joined to it as further columns
+    fn read_fields(&mut self, from: &str, expression: &Expression, dashboard_filters: &[DashboardFilter]) {
+        let mut field_ids = Vec::new();
+        let conditions = dashboard_filters.iter().map(|filter| &filter.condition);
+        for searched in iter::once(expression).chain(self.query_groups).chain(conditions) {
+            collect_fields(searched, &mut field_ids);
+        }
+        let mut columns = HashMap::new();
+        let mut select = Vec::new();
+        for (index, field_id) in field_ids.into_iter().enumerate() {
+            let column = self.quote(&format!("field_{}", index + 1));
+            select.push(format!("{} AS {}", field_sql(self.dialect, field_id), column));
+            columns.insert(field_id.to_string(), column);
+        }
+        //every row counts, even without fields
+        if select.is_empty() {
+            select.push(format!("1 AS {}", self.quote("row")));
+        }
+        let source = self.quote("lod_source");
+        self.ctes
+            .push(format!("{} AS (SELECT {} FROM {})", source, select.join(", "), from));
+        self.columns = Some(columns);
+        self.rows = source;
+    }
+
+    //copy of the expression with the filters of the WHERE modifiers applied to the aggregates and
+    //GROUP BY modifiers replaced by references to their values, like `lod::Levels::resolve`
+    fn resolve(
+        &mut self,
+        expression: &'a Expression,
+        scope: &FilterScope<'a>,
+        in_aggregate: bool,
+    ) -> Result<Expression, Error> {
+        match expression {
+            Expression::ModifierExpression {
+                expression: modified,
+                where_modifier,
+                group_by_modifier,
+                span,
+            } => {
+                let mut inner_scope = scope.clone();
+                if let Some(where_modifier) = where_modifier {
+                    inner_scope.apply(where_modifier);
+                }
+                let Some(group_by_modifier) = group_by_modifier else {
+                    return self.resolve(modified, &inner_scope, in_aggregate);
+                };
+                let modified = self.resolve(modified, &inner_scope, false)?;
+                let value = self.add_level(&modified, &group_by_modifier.group_context, *span)?;
+                if in_aggregate {
+                    return Ok(value);
+                }
+                //outside of aggregates the value has to be the same for all rows of a group, the rows
+                //are filtered like the aggregates around the modifier
+                let value = filtered(value, scope);
+                let min = func("min", vec![value.clone()]);
+                if self.within_query_groups(&group_by_modifier.group_context) {
+                    return Ok(min);
+                }
+                let max = func("max", vec![value]);
+                Ok(if_expr(func("=", vec![min.clone(), max]), min, lit_null()))
+            }
+            Expression::Function {
+                function_name,
+                params,
+                span,
+            } if is_aggregate(function_name) => {
+                if in_aggregate {
+                    return Err(Error::new(
+                        ErrorKind::UnexpectedAggregate {
+                            function_name: function_name.clone(),
+                        },
+                        *span,
+                    ));
+                }
+                let mut params = params
+                    .iter()
+                    .map(|param| self.resolve(param, scope, true))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if !params.is_empty() {
+                    let argument = params.remove(0);
+                    params.insert(0, filtered(argument, scope));
+                }
+                Ok(Expression::Function {
+                    function_name: function_name.clone(),
+                    params,
+                    span: *span,
+                })
+            }
+            Expression::FieldReference { field_id, span } if !in_aggregate => Err(Error::new(
+                ErrorKind::FieldNotAggregated {
+                    field_id: field_id.clone(),
+                },
+                *span,
+            )),
+            _ => map_children(expression, |child| self.resolve(child, scope, in_aggregate)),
+        }
+    }
+
+    //whether every key of the level is one of the query groups, so every group of the query has one value
+    fn within_query_groups(&self, group_context: &GroupByContext) -> bool {
+        let GroupByContext::IncludedGroups { groups } = group_context else {
+            return true;
+        };
+        groups.iter().all(|group| match group {
+            GroupReference::QueryGroup { .. } => true,
+            GroupReference::FieldGroup { field } => {
+                self.query_groups.iter().any(|query_group| query_group.eq_ignoring_spans(field))
+            }
+        })
+    }
+
+    //compute the expression for every group of the level, and join the value of its group to every row
+    fn add_level(
+        &mut self,
+        expression: &Expression,
+        group_context: &GroupByContext,
+        span: Span,
+    ) -> Result<Expression, Error> {
+        let keys = match group_context {
+            GroupByContext::AllGroups() => Vec::new(),
+            GroupByContext::IncludedGroups { groups } => groups
+                .iter()
+                .map(|group| match group {
+                    GroupReference::QueryGroup { index, span } => index
+                        .checked_sub(1)
+                        .and_then(|index| self.query_groups.get(index))
+                        .ok_or_else(|| Error::new(ErrorKind::UnknownGroup { index: *index }, *span)),
+                    GroupReference::FieldGroup { field } => Ok(field),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+        let keys = keys
+            .into_iter()
+            .map(|key| self.translate(key))
+            .collect::<Result<Vec<_>, _>>()?;
+        let value_type = check_types(expression, &self.schema, FunctionRegistry::builtins())
+            .map_err(first_error)?
+            .value_type;
+        let value = self.translate(expression)?;
+
+        self.levels += 1;
+        let level = self.quote(&format!("lod_level_{}", self.levels));
+        let rows = self.quote(&format!("lod_rows_{}", self.levels));
+        let column = self.quote(&format!("lod_{}", self.levels));
+        let value_column = self.quote("value");
+        let key_columns: Vec<String> = (1..=keys.len())
+            .map(|index| self.quote(&format!("key_{}", index)))
+            .collect();
+
+        let mut select: Vec<String> = keys
+            .iter()
+            .zip(&key_columns)
+            .map(|(key, key_column)| format!("{} AS {}", key.text, key_column))
+            .collect();
+        select.push(format!("{} AS {}", value.text, value_column));
+        let mut level_sql = format!("{} AS (SELECT {} FROM {}", level, select.join(", "), self.rows);
+        let join = if keys.is_empty() {
+            format!("CROSS JOIN {}", level)
+        } else {
+            let key_sql: Vec<&str> = keys.iter().map(|key| key.text.as_str()).collect();
+            level_sql.push_str(&format!(" GROUP BY {}", key_sql.join(", ")));
+            let conditions: Vec<String> = keys
+                .iter()
+                .zip(&key_columns)
+                .map(|(key, key_column)| {
+                    let key = key.operand(COMPARISON + 1);
+                    self.dialect.null_safe_equal(&key, &format!("{}.{}", level, key_column))
+                })
+                .collect();
+            format!("LEFT JOIN {} ON {}", level, conditions.join(" AND "))
+        };
+        level_sql.push(')');
+        let rows_sql = format!(
+            "{} AS (SELECT {}.*, {}.{} AS {} FROM {} {})",
+            rows, self.rows, level, value_column, column, self.rows, join
+        );
+        self.ctes.push(level_sql);
+        self.ctes.push(rows_sql);
+        self.rows = rows;
+
+        let field_id = format!("{}{}", LEVEL_PREFIX, self.levels);
+        self.schema = std::mem::take(&mut self.schema).with_field(&field_id, value_type.unwrap_or(ValueType::Null));
+        self.columns
+            .get_or_insert_with(HashMap::new)
+            .insert(field_id.clone(), column);
+        Ok(Expression::FieldReference { field_id, span })
+    }
+}
+
+//the value where all filters of the scope hold, NULL elsewhere, which aggregates ignore
+fn filtered(value: Expression, scope: &FilterScope) -> Expression {
+    match all_of(scope.filters().into_iter().cloned()) {
+        Some(condition) => if_expr(condition, value, lit_null()),
+        None => value,
+    }
+}
+
+fn all_of(conditions: impl IntoIterator<Item = Expression>) -> Option<Expression> {
+    conditions
+        .into_iter()
+        .reduce(|all, condition| func("and", vec![all, condition]))
+}
+
+fn has_modifiers(expression: &Expression) -> bool {
+    matches!(expression, Expression::ModifierExpression { .. }) || expression.children().into_iter().any(has_modifiers)
+}
+
+fn collect_fields<'e>(expression: &'e Expression, field_ids: &mut Vec<&'e str>) {
+    match expression {
+        Expression::FieldReference { field_id, .. } if !field_ids.contains(&field_id.as_str()) => {
+            field_ids.push(field_id)
+        }
+        _ => expression
+            .children()
+            .into_iter()
+            .for_each(|child| collect_fields(child, field_ids)),
+    }
+}
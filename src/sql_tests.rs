@@ -0,0 +1,388 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+
+use crate::ast::{Expression, LiteralValue, ValueType};
+use crate::sql::{to_sql, to_sql_query, Ansi, BigQuery, Dialect, DuckDb, MySql, PostgreSql, Sqlite};
+use crate::{evaluate, evaluate_aggregate, evaluate_grouped, parse, DashboardFilter, ErrorKind, NaiveDate, Schema};
+
+//expressions translated to every dialect, the results are in tests/golden/sql
+const CASES: &[&str] = &[
+    "sales + qty * 2",
+    "(sales + qty) * 2",
+    "sales - (qty - 1)",
+    "qty - -1",
+    "qty / 4",
+    "qty / (qty + 1)",
+    "price / 2.5 * 1e3",
+    r#"region = "EU" and not paid or qty >= 10"#,
+    r#"not (region != "EU" or paid) and (qty < 2 or qty > 4)"#,
+    "price is null or (qty + 1) is not null",
+    "if paid then sales else 0",
+    r#"case when qty > 5 then "big" when qty > 1 then "medium" end"#,
+    r#"region = "it's \"quoted\" \\ here""#,
+    r#"concat(region, "-", qty, null, paid)"#,
+    "round(price, 1) + abs(sales - 10)",
+    "pow(qty, 2) + sqrt(qty) + mod(qty, 3)",
+    "log(qty) + log(qty, 2) + ln(qty) + exp(1)",
+    "len(region) + len(order_id)",
+    "substr(region, 2) = substr(order_id, 1, 2)",
+    "concat(upper(left(region, 1)), lower(right(region, 1)))",
+    r#"contains(region, "U") or startswith(order_id, "A")"#,
+    r#"replace(trim(region), "E", "e")"#,
+    r#"split(order_id, "-", 2)"#,
+    "coalesce(price, sales, 0) + ifnull(price, 1) + zn(price)",
+    "nullif(qty, 0)",
+    "iif(paid, 1, 0) + iif(qty > 2, 1, 0, 2)",
+    "str(qty)",
+    "int(price) + float(sales)",
+    "bool(qty)",
+    "today() < now()",
+    r#"datepart("year", orders.date) * 100 + datepart("month", orders.date)"#,
+    r#"datepart("quarter", orders.date)"#,
+    r#"datepart("week", orders.date)"#,
+    r#"datepart("hour", orders.shipped)"#,
+    r#"date_trunc("month", orders.date)"#,
+    r#"date_trunc("quarter", orders.date)"#,
+    r#"date_trunc("week", orders.shipped)"#,
+    r#"date_trunc("hour", orders.shipped)"#,
+    r#"datediff("day", orders.date, orders.shipped)"#,
+    r#"datediff("week", orders.date, orders.shipped)"#,
+    r#"datediff("month", orders.date, orders.shipped)"#,
+    r#"datediff("quarter", orders.date, orders.shipped)"#,
+    r#"datediff("year", orders.date, orders.shipped)"#,
+    r#"datediff("hour", orders.date, orders.shipped)"#,
+    r#"dateadd("month", 2, orders.date)"#,
+    r#"dateadd("week", qty, orders.date)"#,
+    r#"dateadd("hour", qty, orders.shipped)"#,
+    "orders.date + 7",
+    "orders.date - qty",
+    "orders.date - date '2024-01-01'",
+    "orders.date + interval '1 month 2 days'",
+    "orders.shipped - interval '90 minutes'",
+    "orders.date < timestamp '2024-03-01 12:30:00'",
+    "orders.shipped - orders.date",
+    "interval '3 days' * qty",
+    "sum(sales) / count(order_id)",
+    "avg(qty) + countd(region)",
+    "min(orders.date) < max(orders.shipped)",
+    "median(price)",
+    "percentile(price, 0.9)",
+    "stddev(qty) + variance(qty)",
+    "sum(sales) [where ignore all filters]",
+    r#"datepart("fortnight", orders.date)"#,
+    "datepart(region, orders.date)",
+    "discount * 2",
+    r#"sales + "a""#,
+];
+
+fn schema() -> Schema {
+    Schema::new()
+        .with_field("sales", ValueType::Decimal)
+        .with_field("qty", ValueType::Integer)
+        .with_field("price", ValueType::Number)
+        .with_field("region", ValueType::String)
+        .with_field("order_id", ValueType::String)
+        .with_field("paid", ValueType::Boolean)
+        .with_field("orders.date", ValueType::Date)
+        .with_field("orders.shipped", ValueType::Timestamp)
+}
+
+const DIALECTS: [(&str, &dyn Dialect); 6] = [
+    ("ansi", &Ansi),
+    ("postgresql", &PostgreSql),
+    ("sqlite", &Sqlite),
+    ("duckdb", &DuckDb),
+    ("mysql", &MySql),
+    ("bigquery", &BigQuery),
+];
+
+//compare with a file of tests/golden/sql, which UPDATE_GOLDEN=1 replaces
+fn assert_golden_file(file_name: &str, sql: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/sql").join(file_name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, sql).unwrap();
+    }
+    let expected = fs::read_to_string(&path).unwrap();
+    assert!(
+        sql == expected,
+        "{} differs from the translation, run the tests with UPDATE_GOLDEN=1 to update it",
+        path.display()
+    );
+}
+
+#[test]
+fn test_sql_golden_files() {
+    let schema = schema();
+    for (file_name, dialect) in DIALECTS {
+        let mut sql = String::new();
+        for case in CASES {
+            writeln!(sql, "-- {}", case).unwrap();
+            match to_sql(&parse(case).unwrap(), &schema, dialect) {
+                Ok(translated) => writeln!(sql, "{}\n", translated).unwrap(),
+                Err(error) => writeln!(sql, "-- error: {}\n", error.kind()).unwrap(),
+            }
+        }
+        assert_golden_file(&format!("{}.sql", file_name), &sql);
+    }
+}
+
+#[test]
+fn test_sql_errors() {
+    let schema = schema();
+    let error = to_sql(&parse("median(price)").unwrap(), &schema, &Sqlite).unwrap_err();
+    assert_eq!(
+        error.kind(),
+        &ErrorKind::UnsupportedInDialect {
+            dialect: "SQLite".to_string(),
+            feature: "function `median`".to_string()
+        }
+    );
+    assert_eq!((error.span().start, error.span().end), (0, 13));
+
+    let error = to_sql(&parse("qty + sales [where ignore all filters]").unwrap(), &schema, &Ansi).unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::UnsupportedInDialect { .. }));
+
+    //the first type error is returned
+    let error = to_sql(&parse(r#"upper(qty) + missing"#).unwrap(), &schema, &Ansi).unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::TypeMismatch { .. }));
+}
+
+//rows of the SQLite execution check, inserted into the `orders` table in this order
+fn sqlite_rows() -> Vec<HashMap<String, LiteralValue>> {
+    let date = |text: &str| NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap();
+    let rows = [
+        ("12.5", 3, Some(2.5), "EU", "A-1", true, "2024-01-31", "2024-02-02 08:15:00"),
+        ("7", 1, None, "US", "B-2", false, "2024-02-29", "2024-04-01 23:59:59"),
+        ("0.25", 6, Some(10.0), "it's", "A-3", false, "2023-12-25", "2024-01-08 00:00:00"),
+        ("3", 2, None, "EU", "A-4", true, "2024-03-15", "2024-03-16 10:00:00"),
+        ("1.5", 4, Some(1.0), "US", "B-5", true, "2024-01-02", "2024-01-05 12:00:00"),
+    ];
+    rows.iter()
+        .map(|(sales, qty, price, region, order_id, paid, order_date, shipped)| {
+            HashMap::from([
+                ("sales".to_string(), LiteralValue::DecimalValue(sales.parse().unwrap())),
+                ("qty".to_string(), LiteralValue::IntegerValue(*qty)),
+                ("price".to_string(), price.map_or(LiteralValue::NullValue, LiteralValue::NumberValue)),
+                ("region".to_string(), LiteralValue::StringValue(region.to_string())),
+                ("order_id".to_string(), LiteralValue::StringValue(order_id.to_string())),
+                ("paid".to_string(), LiteralValue::BooleanValue(*paid)),
+                ("orders.date".to_string(), LiteralValue::DateValue(date(order_date))),
+                (
+                    "orders.shipped".to_string(),
+                    LiteralValue::TimestampValue(date(&shipped[..10]).and_time(shipped[11..].parse().unwrap())),
+                ),
+            ])
+        })
+        .collect()
+}
+
+//values as SQLite stores them: booleans are integers, dates and timestamps text
+fn sqlite_value(value: &LiteralValue) -> Value {
+    match value {
+        LiteralValue::StringValue(value) => Value::Text(value.clone()),
+        LiteralValue::NumberValue(value) => Value::Real(*value),
+        LiteralValue::IntegerValue(value) => Value::Integer(*value),
+        LiteralValue::DecimalValue(value) => Value::Real(f64::try_from(*value).unwrap()),
+        LiteralValue::BooleanValue(value) => Value::Integer(i64::from(*value)),
+        LiteralValue::DateValue(value) => Value::Text(value.format("%Y-%m-%d").to_string()),
+        LiteralValue::TimestampValue(value) => Value::Text(value.format("%Y-%m-%d %H:%M:%S").to_string()),
+        LiteralValue::IntervalValue(_) | LiteralValue::NullValue => Value::Null,
+    }
+}
+
+fn assert_same_value(input: &str, actual: &Value, expected: &Value) {
+    let number = |value: &Value| match value {
+        Value::Integer(value) => Some(*value as f64),
+        Value::Real(value) => Some(*value),
+        _ => None,
+    };
+    match (number(actual), number(expected)) {
+        (Some(actual), Some(expected)) => {
+            assert!((actual - expected).abs() < 1e-9, "{}: {} != {}", input, actual, expected)
+        }
+        _ => assert_eq!(actual, expected, "{}", input),
+    }
+}
+
+//in-memory database with the rows of `sqlite_rows` in the `orders` table
+fn sqlite_orders(rows: &[HashMap<String, LiteralValue>]) -> Connection {
+    let connection = Connection::open_in_memory().unwrap();
+    connection
+        .execute_batch(
+            "CREATE TABLE orders (sales REAL, qty INTEGER, price REAL, region TEXT, order_id TEXT, paid INTEGER, \
+             date TEXT, shipped TEXT)",
+        )
+        .unwrap();
+    //the bundled SQLite is built without the math functions
+    let math = [("SQRT", f64::sqrt as fn(f64) -> f64), ("LN", f64::ln), ("EXP", f64::exp), ("LOG10", f64::log10)];
+    for (name, function) in math {
+        connection
+            .create_scalar_function(name, 1, FunctionFlags::SQLITE_DETERMINISTIC, move |context| {
+                Ok(context.get::<Option<f64>>(0)?.map(function))
+            })
+            .unwrap();
+    }
+    connection
+        .create_scalar_function("POWER", 2, FunctionFlags::SQLITE_DETERMINISTIC, |context| {
+            Ok(context.get::<f64>(0)?.powf(context.get::<f64>(1)?))
+        })
+        .unwrap();
+    for row in rows {
+        let columns = ["sales", "qty", "price", "region", "order_id", "paid", "orders.date", "orders.shipped"]
+            .map(|field| sqlite_value(&row[field]));
+        connection
+            .execute("INSERT INTO orders VALUES (?, ?, ?, ?, ?, ?, ?, ?)", params_from_iter(columns))
+            .unwrap();
+    }
+    connection
+}
+
+#[test]
+fn test_sql_runs_on_sqlite() {
+    let rows = sqlite_rows();
+    let connection = sqlite_orders(&rows);
+    let schema = schema();
+    let mut checked = 0;
+    for case in CASES {
+        let expression = parse(case).unwrap();
+        let Ok(sql) = to_sql(&expression, &schema, &Sqlite) else {
+            continue;
+        };
+        //the current time differs between the database and the evaluator
+        if case.contains("today()") {
+            continue;
+        }
+        let mut statement = connection.prepare(&format!("SELECT {} FROM orders ORDER BY rowid", sql)).unwrap();
+        let values: Vec<Value> = statement
+            .query_map([], |result| result.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let expected: Vec<Value> = match evaluate_aggregate(&expression, &rows) {
+            Ok(value) => vec![sqlite_value(&value)],
+            Err(_) => rows
+                .iter()
+                .map(|row| sqlite_value(&evaluate(&expression, row).unwrap()))
+                .collect(),
+        };
+        //aggregates produce a single row
+        assert_eq!(values.len(), expected.len(), "{}", case);
+        for (actual, expected) in values.iter().zip(&expected) {
+            assert_same_value(&format!("{}: {}", case, sql), actual, expected);
+        }
+        checked += 1;
+    }
+    assert!(checked > 40, "only {} expressions were checked", checked);
+}
+
+//dashboard filters as (field, condition)
+type Filters = &'static [(&'static str, &'static str)];
+
+//expressions with modifiers, with the query groups and the dashboard filters of the query
+const QUERY_CASES: &[(&str, &[&str], Filters)] = &[
+    ("sum(sales)", &["region"], &[("paid", "paid")]),
+    ("sum(sales) / sum(sales) [where ignore all filters]", &["region"], &[("paid", "paid")]),
+    (
+        "sum(qty) [where allow filters on region and qty > 1]",
+        &["region"],
+        &[("region", r#"region != "it's""#), ("paid", "paid")],
+    ),
+    ("sum(sales) / sum(sales) [group by region]", &["order_id"], &[]),
+    ("avg(sum(sales) [group by region])", &[], &[]),
+    ("countd(order_id) [group by all groups]", &["region"], &[("paid", "paid")]),
+    ("sum(qty) [group by group(1)] - sum(qty)", &["region", "paid"], &[("qty", "qty < 5")]),
+    ("max(sum(qty) [group by region, paid]) [group by region]", &["region", "paid"], &[]),
+    ("sum(qty) [where ignore all filters] [group by region]", &["region"], &[("region", r#"region = "EU""#)]),
+    ("sum(qty) [group by missing]", &["region"], &[]),
+    ("sum(qty) [group by order_id]", &["region"], &[]),
+];
+
+fn query(query_groups: &[&str], filters: &[(&str, &str)]) -> (Vec<Expression>, Vec<DashboardFilter>) {
+    let query_groups = query_groups.iter().map(|group| parse(group).unwrap()).collect();
+    let filters = filters
+        .iter()
+        .map(|(field_id, condition)| DashboardFilter::new(field_id, parse(condition).unwrap()))
+        .collect();
+    (query_groups, filters)
+}
+
+#[test]
+fn test_sql_query_golden_file() {
+    let schema = schema();
+    let mut sql = String::new();
+    for (input, query_groups, filters) in QUERY_CASES {
+        let (query_groups, filters) = query(query_groups, filters);
+        for (name, dialect) in DIALECTS {
+            writeln!(sql, "-- {} ({})", input, name).unwrap();
+            match to_sql_query(&parse(input).unwrap(), &schema, dialect, "orders", &query_groups, &filters) {
+                Ok(query) => writeln!(sql, "{};\n", query).unwrap(),
+                Err(error) => writeln!(sql, "-- error: {}\n", error.kind()).unwrap(),
+            }
+        }
+    }
+    assert_golden_file("queries.sql", &sql);
+}
+
+#[test]
+fn test_sql_queries_run_on_sqlite() {
+    let rows = sqlite_rows();
+    let connection = sqlite_orders(&rows);
+    let schema = schema();
+    for (input, query_groups, filters) in QUERY_CASES {
+        let expression = parse(input).unwrap();
+        let (query_groups, filters) = query(query_groups, filters);
+        let sql = match to_sql_query(&expression, &schema, &Sqlite, "orders", &query_groups, &filters) {
+            Ok(sql) => sql,
+            //queries that cannot be planned fail in the evaluator with the same error
+            Err(error) => {
+                let expected = evaluate_grouped(&expression, &rows, &query_groups, &filters).unwrap_err();
+                assert_eq!(error.kind(), expected.kind(), "{}", input);
+                assert_eq!(error.span(), expected.span(), "{}", input);
+                continue;
+            }
+        };
+        let mut statement = connection.prepare(&sql).unwrap();
+        let columns = statement.column_count();
+        let mut actual: Vec<Vec<Value>> = statement
+            .query_map([], |row| (0..columns).map(|index| row.get(index)).collect())
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let groups = match evaluate_grouped(&expression, &rows, &query_groups, &filters) {
+            Ok(groups) => groups,
+            //instead of the error, groups with different values of a level of detail are NULL
+            Err(error) if error.kind() == &ErrorKind::AmbiguousLevelOfDetail => {
+                assert!(actual.iter().any(|row| row[columns - 1] == Value::Null), "{}", input);
+                continue;
+            }
+            Err(error) => panic!("{}: {}", input, error),
+        };
+        let mut expected: Vec<Vec<Value>> = groups
+            .iter()
+            .map(|group| group.keys.iter().chain([&group.value]).map(sqlite_value).collect())
+            .collect();
+        //the order of the groups is not defined in SQL
+        actual.sort_by_key(|row| format!("{:?}", &row[..columns - 1]));
+        expected.sort_by_key(|row| format!("{:?}", &row[..columns - 1]));
+        assert_eq!(actual.len(), expected.len(), "{}", input);
+        for (actual, expected) in actual.iter().zip(&expected) {
+            assert_eq!(actual[..columns - 1], expected[..columns - 1], "{}", input);
+            assert_same_value(&format!("{}: {}", input, sql), &actual[columns - 1], &expected[columns - 1]);
+        }
+    }
+}
+
+#[test]
+fn test_sql_query_errors() {
+    let (query_groups, filters) = query(&["region"], &[]);
+    let expression = parse("sum(qty) [group by region, group(2)]").unwrap();
+    let error = to_sql_query(&expression, &schema(), &Sqlite, "orders", &query_groups, &filters).unwrap_err();
+    assert_eq!(error.kind(), &ErrorKind::UnknownGroup { index: 2 });
+    assert_eq!((error.span().start, error.span().end), (27, 35));
+}
@@ -0,0 +1,435 @@
+use std::cmp::Ordering;
+
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use crate::ast::{Interval, LiteralValue, Span, ValueType};
+use crate::error::{ErrorKind, ValueTypeError};
+use crate::eval::{compare_values, promote, NumericOperands};
+use crate::functions::{Conditional, FunctionRegistry, NullHandling, ParamType, Signature};
+
+pub(crate) fn register_standard_functions(functions: &mut FunctionRegistry) {
+    use ParamType::{Any, Exact, Numeric};
+
+    let string = Exact(ValueType::String);
+    let integer = Exact(ValueType::Integer);
+    let boolean = Exact(ValueType::Boolean);
+    let float = Exact(ValueType::Number);
+    let pass_nulls = |signature: Signature| signature.with_null_handling(NullHandling::PassThrough);
+
+    //math
+    functions
+        .register("abs", Signature::new(vec![Numeric], Numeric), |args| {
+            map_numeric(&args[0], i64::checked_abs, |value| Some(value.abs()), f64::abs)
+        })
+        .register(
+            "round",
+            Signature::new(vec![Numeric], Numeric).with_optional(integer),
+            round,
+        )
+        .register("ceil", Signature::new(vec![Numeric], Numeric), |args| {
+            map_numeric(&args[0], Some, |value| Some(value.ceil()), f64::ceil)
+        })
+        .register("floor", Signature::new(vec![Numeric], Numeric), |args| {
+            map_numeric(&args[0], Some, |value| Some(value.floor()), f64::floor)
+        })
+        .register("sqrt", Signature::new(vec![Numeric], float), |args| {
+            let value = float_arg(&args[0])?;
+            if value < 0.0 {
+                return Err(invalid_argument("sqrt", "square root of a negative number"));
+            }
+            Ok(LiteralValue::NumberValue(value.sqrt()))
+        })
+        .register("pow", Signature::new(vec![Numeric, Numeric], Numeric), pow)
+        .register("ln", Signature::new(vec![Numeric], float), |args| {
+            logarithm("ln", &args[0], None)
+        })
+        .register(
+            "log",
+            Signature::new(vec![Numeric], float).with_optional(Numeric),
+            |args| logarithm("log", &args[0], Some(args.get(1).map_or(Ok(10.0), float_arg)?)),
+        )
+        .register("exp", Signature::new(vec![Numeric], float), |args| {
+            Ok(LiteralValue::NumberValue(float_arg(&args[0])?.exp()))
+        })
+        .register("sign", Signature::new(vec![Numeric], integer), |args| {
+            let sign = match compare_values(&args[0], &LiteralValue::IntegerValue(0), Span::default()) {
+                Ok(Some(Ordering::Less)) => -1,
+                Ok(Some(Ordering::Greater)) => 1,
+                _ => 0,
+            };
+            Ok(LiteralValue::IntegerValue(sign))
+        })
+        .register("mod", Signature::new(vec![Numeric, Numeric], Numeric), modulo);
+
+    //strings, positions count characters and start at 1
+    functions
+        .register("len", Signature::new(vec![string], integer), |args| {
+            Ok(LiteralValue::IntegerValue(string_arg(&args[0])?.chars().count() as i64))
+        })
+        .register("upper", Signature::new(vec![string], string), |args| {
+            Ok(LiteralValue::StringValue(string_arg(&args[0])?.to_uppercase()))
+        })
+        .register("lower", Signature::new(vec![string], string), |args| {
+            Ok(LiteralValue::StringValue(string_arg(&args[0])?.to_lowercase()))
+        })
+        .register("trim", Signature::new(vec![string], string), |args| {
+            Ok(LiteralValue::StringValue(string_arg(&args[0])?.trim().to_string()))
+        })
+        .register(
+            "substr",
+            Signature::new(vec![string, integer], string).with_optional(integer),
+            substr,
+        )
+        .register("left", Signature::new(vec![string, integer], string), |args| {
+            let length = length_arg("left", &args[1])?;
+            Ok(LiteralValue::StringValue(
+                string_arg(&args[0])?.chars().take(length).collect(),
+            ))
+        })
+        .register("right", Signature::new(vec![string, integer], string), |args| {
+            let value = string_arg(&args[0])?;
+            let skip = value.chars().count().saturating_sub(length_arg("right", &args[1])?);
+            Ok(LiteralValue::StringValue(value.chars().skip(skip).collect()))
+        })
+        .register("contains", Signature::new(vec![string, string], boolean), |args| {
+            Ok(LiteralValue::BooleanValue(
+                string_arg(&args[0])?.contains(string_arg(&args[1])?),
+            ))
+        })
+        .register("startswith", Signature::new(vec![string, string], boolean), |args| {
+            Ok(LiteralValue::BooleanValue(
+                string_arg(&args[0])?.starts_with(string_arg(&args[1])?),
+            ))
+        })
+        .register(
+            "replace",
+            Signature::new(vec![string, string, string], string),
+            |args| {
+                let value = string_arg(&args[0])?;
+                let from = string_arg(&args[1])?;
+                if from.is_empty() {
+                    return Ok(LiteralValue::StringValue(value.to_string()));
+                }
+                Ok(LiteralValue::StringValue(value.replace(from, string_arg(&args[2])?)))
+            },
+        )
+        //NULL arguments are skipped, like in most SQL dialects
+        .register(
+            "concat",
+            pass_nulls(Signature::new(vec![Any], string).with_variadic(Any)),
+            |args| {
+                let parts: Vec<String> = args
+                    .iter()
+                    .filter(|arg| **arg != LiteralValue::NullValue)
+                    .map(to_string)
+                    .collect();
+                Ok(LiteralValue::StringValue(parts.concat()))
+            },
+        )
+        .register("split", Signature::new(vec![string, string, integer], string), split);
+
+    //conditionals get NULL arguments to be able to replace them, `coalesce`, `ifnull` and `iif`
+    //only evaluate the arguments they need
+    functions
+        .register_conditional(
+            "coalesce",
+            pass_nulls(Signature::new(vec![Any], Any).with_variadic(Any)),
+            Conditional::FirstNonNull,
+            |args| Ok(first_non_null(args)),
+        )
+        .register_conditional(
+            "ifnull",
+            pass_nulls(Signature::new(vec![Any, Any], Any)),
+            Conditional::FirstNonNull,
+            |args| Ok(first_non_null(args)),
+        )
+        .register("nullif", pass_nulls(Signature::new(vec![Any, Any], Any)), |args| {
+            if args.contains(&LiteralValue::NullValue) {
+                return Ok(args[0].clone());
+            }
+            let ordering = compare_values(&args[0], &args[1], Span::default())
+                .map_err(|error| error.kind().clone())?;
+            match ordering {
+                Some(Ordering::Equal) => Ok(LiteralValue::NullValue),
+                _ => Ok(args[0].clone()),
+            }
+        })
+        //iif(condition, then, else [, unknown]) returns `unknown` when the condition is NULL
+        .register_conditional(
+            "iif",
+            pass_nulls(Signature::new(vec![boolean, Any, Any], Any).with_optional(Any)),
+            Conditional::Iif,
+            |args| match args[0] {
+                LiteralValue::BooleanValue(true) => Ok(args[1].clone()),
+                LiteralValue::BooleanValue(false) => Ok(args[2].clone()),
+                _ => Ok(args.get(3).cloned().unwrap_or(LiteralValue::NullValue)),
+            },
+        )
+        .register(
+            "zn",
+            pass_nulls(Signature::new(vec![Numeric], Numeric)),
+            |args| match args[0] {
+                LiteralValue::NullValue => Ok(LiteralValue::IntegerValue(0)),
+                ref value => Ok(value.clone()),
+            },
+        );
+
+    //conversions, strings that cannot be converted produce NULL
+    functions
+        .register("str", Signature::new(vec![Any], string), |args| {
+            Ok(LiteralValue::StringValue(to_string(&args[0])))
+        })
+        .register("int", Signature::new(vec![Any], integer), to_integer)
+        .register("float", Signature::new(vec![Any], float), |args| {
+            let value = match &args[0] {
+                LiteralValue::StringValue(value) => value.trim().parse::<f64>().ok(),
+                LiteralValue::BooleanValue(value) => Some(if *value { 1.0 } else { 0.0 }),
+                other => Some(float_arg(other)?),
+            };
+            Ok(value.map_or(LiteralValue::NullValue, LiteralValue::NumberValue))
+        })
+        .register("bool", Signature::new(vec![Any], boolean), |args| {
+            let value = match &args[0] {
+                LiteralValue::StringValue(value) if value.trim().eq_ignore_ascii_case("true") => Some(true),
+                LiteralValue::StringValue(value) if value.trim().eq_ignore_ascii_case("false") => Some(false),
+                LiteralValue::StringValue(_) => None,
+                LiteralValue::BooleanValue(value) => Some(*value),
+                other => Some(float_arg(other)? != 0.0),
+            };
+            Ok(value.map_or(LiteralValue::NullValue, LiteralValue::BooleanValue))
+        });
+}
+
+fn invalid_argument(function_name: &str, reason: &str) -> ErrorKind {
+    ErrorKind::InvalidArgument {
+        function_name: function_name.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+fn string_arg(value: &LiteralValue) -> Result<&str, ErrorKind> {
+    match value {
+        LiteralValue::StringValue(value) => Ok(value),
+        other => Err(ValueTypeError::new(ValueType::String, other).into()),
+    }
+}
+
+fn integer_arg(value: &LiteralValue) -> Result<i64, ErrorKind> {
+    Ok(i64::try_from(value.clone())?)
+}
+
+fn float_arg(value: &LiteralValue) -> Result<f64, ErrorKind> {
+    Ok(f64::try_from(value.clone())?)
+}
+
+fn length_arg(function_name: &str, value: &LiteralValue) -> Result<usize, ErrorKind> {
+    usize::try_from(integer_arg(value)?).map_err(|_| invalid_argument(function_name, "negative length"))
+}
+
+//apply the operation matching the kind of number, None means an overflow
+fn map_numeric(
+    value: &LiteralValue,
+    integer: impl Fn(i64) -> Option<i64>,
+    decimal: impl Fn(Decimal) -> Option<Decimal>,
+    float: impl Fn(f64) -> f64,
+) -> Result<LiteralValue, ErrorKind> {
+    match value {
+        LiteralValue::IntegerValue(value) => integer(*value)
+            .map(LiteralValue::IntegerValue)
+            .ok_or(ErrorKind::NumericOverflow),
+        LiteralValue::DecimalValue(value) => decimal(*value)
+            .map(LiteralValue::DecimalValue)
+            .ok_or(ErrorKind::NumericOverflow),
+        other => Ok(LiteralValue::NumberValue(float(float_arg(other)?))),
+    }
+}
+
+//round(value [, digits]) rounds halves away from zero, negative digits round to tens, hundreds, ...
+fn round(args: &[LiteralValue]) -> Result<LiteralValue, ErrorKind> {
+    let digits = args.get(1).map_or(Ok(0), integer_arg)?;
+    let digits = i32::try_from(digits).map_err(|_| invalid_argument("round", "too many digits"))?;
+    let round_decimal = |value: Decimal| -> Option<Decimal> {
+        if digits >= 0 {
+            return Some(value.round_dp_with_strategy(digits as u32, RoundingStrategy::MidpointAwayFromZero));
+        }
+        //decimals are below 10^29, so they all round to 0 at that magnitude
+        if digits < -(Decimal::MAX_SCALE as i32) {
+            return Some(Decimal::ZERO);
+        }
+        let factor = Decimal::try_from_i128_with_scale(10_i128.pow(digits.unsigned_abs()), 0).ok()?;
+        value
+            .checked_div(factor)?
+            .round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+            .checked_mul(factor)
+    };
+    map_numeric(
+        &args[0],
+        |value| round_decimal(Decimal::from(value))?.to_i64(),
+        round_decimal,
+        |value| {
+            //beyond the range of float exponents the factor would be 0 or infinite
+            let factor = 10_f64.powi(digits.clamp(-f64::MAX_10_EXP, f64::MAX_10_EXP));
+            if (value * factor).is_finite() {
+                (value * factor).round() / factor
+            } else {
+                value
+            }
+        },
+    )
+}
+
+//integer powers with a non-negative exponent stay integers, everything else is a float
+fn pow(args: &[LiteralValue]) -> Result<LiteralValue, ErrorKind> {
+    if let (LiteralValue::IntegerValue(base), LiteralValue::IntegerValue(exponent)) = (&args[0], &args[1]) {
+        if let Ok(exponent) = u32::try_from(*exponent) {
+            return base
+                .checked_pow(exponent)
+                .map(LiteralValue::IntegerValue)
+                .ok_or(ErrorKind::NumericOverflow);
+        }
+    }
+    Ok(LiteralValue::NumberValue(
+        float_arg(&args[0])?.powf(float_arg(&args[1])?),
+    ))
+}
+
+fn logarithm(function_name: &str, value: &LiteralValue, base: Option<f64>) -> Result<LiteralValue, ErrorKind> {
+    let value = float_arg(value)?;
+    if value <= 0.0 {
+        return Err(invalid_argument(
+            function_name,
+            "logarithm of a number that is not positive",
+        ));
+    }
+    let result = match base {
+        Some(base) if base <= 0.0 || base == 1.0 => {
+            return Err(invalid_argument(function_name, "invalid logarithm base"));
+        }
+        //the dedicated functions are exact for powers of the base
+        Some(10.0) => value.log10(),
+        Some(2.0) => value.log2(),
+        Some(base) => value.log(base),
+        None => value.ln(),
+    };
+    Ok(LiteralValue::NumberValue(result))
+}
+
+//remainder with the sign of the dividend, like `%` in SQL
+fn modulo(args: &[LiteralValue]) -> Result<LiteralValue, ErrorKind> {
+    let operands = promote(&args[0], &args[1]).ok_or_else(|| ErrorKind::TypeMismatch {
+        expected: ValueType::Number,
+        actual: args[1].value_type(),
+    })?;
+    match operands {
+        NumericOperands::Integer(_, 0) => Err(ErrorKind::DivisionByZero),
+        NumericOperands::Integer(left, right) => left
+            .checked_rem(right)
+            .map(LiteralValue::IntegerValue)
+            .ok_or(ErrorKind::NumericOverflow),
+        NumericOperands::Decimal(_, right) if right.is_zero() => Err(ErrorKind::DivisionByZero),
+        NumericOperands::Decimal(left, right) => left
+            .checked_rem(right)
+            .map(LiteralValue::DecimalValue)
+            .ok_or(ErrorKind::NumericOverflow),
+        NumericOperands::Float(_, 0.0) => Err(ErrorKind::DivisionByZero),
+        NumericOperands::Float(left, right) => Ok(LiteralValue::NumberValue(left % right)),
+    }
+}
+
+//substr(value, start [, length]), a start past the end gives an empty string
+fn substr(args: &[LiteralValue]) -> Result<LiteralValue, ErrorKind> {
+    let value = string_arg(&args[0])?;
+    let start = integer_arg(&args[1])?;
+    if start < 1 {
+        return Err(invalid_argument("substr", "positions start at 1"));
+    }
+    let characters = value.chars().skip(usize::try_from(start - 1).unwrap_or(usize::MAX));
+    let result = match args.get(2) {
+        Some(length) => characters.take(length_arg("substr", length)?).collect(),
+        None => characters.collect(),
+    };
+    Ok(LiteralValue::StringValue(result))
+}
+
+//split(value, delimiter, token) returns the token-th part, negative tokens count from the end
+fn split(args: &[LiteralValue]) -> Result<LiteralValue, ErrorKind> {
+    let value = string_arg(&args[0])?;
+    let delimiter = string_arg(&args[1])?;
+    let token = integer_arg(&args[2])?;
+    if delimiter.is_empty() {
+        return Err(invalid_argument("split", "empty delimiter"));
+    }
+    let parts: Vec<&str> = value.split(delimiter).collect();
+    let index = match token {
+        0 => return Err(invalid_argument("split", "tokens start at 1")),
+        1.. => usize::try_from(token - 1).ok(),
+        _ => usize::try_from(token.unsigned_abs())
+            .ok()
+            .and_then(|from_end| parts.len().checked_sub(from_end)),
+    };
+    let part = index.and_then(|index| parts.get(index)).copied().unwrap_or_default();
+    Ok(LiteralValue::StringValue(part.to_string()))
+}
+
+fn first_non_null(args: &[LiteralValue]) -> LiteralValue {
+    args.iter()
+        .find(|arg| **arg != LiteralValue::NullValue)
+        .cloned()
+        .unwrap_or(LiteralValue::NullValue)
+}
+
+//text of a value as used by str() and concat(), dates use the ISO format
+fn to_string(value: &LiteralValue) -> String {
+    match value {
+        LiteralValue::StringValue(value) => value.clone(),
+        LiteralValue::NumberValue(value) => value.to_string(),
+        LiteralValue::IntegerValue(value) => value.to_string(),
+        LiteralValue::DecimalValue(value) => value.to_string(),
+        LiteralValue::BooleanValue(value) => value.to_string(),
+        LiteralValue::DateValue(value) => value.format("%Y-%m-%d").to_string(),
+        LiteralValue::TimestampValue(value) => value.format("%Y-%m-%d %H:%M:%S%.f").to_string(),
+        LiteralValue::IntervalValue(value) => interval_to_string(value),
+        LiteralValue::NullValue => "null".to_string(),
+    }
+}
+
+//same format as interval literals, e.g. `1 months 2 days 30 seconds`
+pub(crate) fn interval_to_string(interval: &Interval) -> String {
+    let seconds = Decimal::new(interval.microseconds, 6).normalize();
+    let parts: Vec<String> = [
+        (interval.months != 0).then(|| format!("{} months", interval.months)),
+        (interval.days != 0).then(|| format!("{} days", interval.days)),
+        (!seconds.is_zero()).then(|| format!("{} seconds", seconds)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if parts.is_empty() {
+        "0 seconds".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+//int() truncates towards zero
+fn to_integer(args: &[LiteralValue]) -> Result<LiteralValue, ErrorKind> {
+    let value = match &args[0] {
+        LiteralValue::IntegerValue(value) => Some(*value),
+        LiteralValue::DecimalValue(value) => Some(value.trunc().to_i64().ok_or(ErrorKind::NumericOverflow)?),
+        LiteralValue::NumberValue(value) => {
+            let truncated = Decimal::from_f64(value.trunc()).and_then(|value| value.to_i64());
+            Some(truncated.ok_or(ErrorKind::NumericOverflow)?)
+        }
+        LiteralValue::StringValue(value) => {
+            let value = value.trim();
+            value
+                .parse::<i64>()
+                .ok()
+                .or_else(|| value.parse::<Decimal>().ok().and_then(|value| value.trunc().to_i64()))
+        }
+        LiteralValue::BooleanValue(value) => Some(i64::from(*value)),
+        other => return Err(ValueTypeError::new(ValueType::Integer, other).into()),
+    };
+    Ok(value.map_or(LiteralValue::NullValue, LiteralValue::IntegerValue))
+}
@@ -0,0 +1,362 @@
+use std::ptr;
+
+use crate::aggregate;
+use crate::ast::{Expression, Span, ValueType};
+use crate::error::{Error, ErrorKind};
+use crate::functions::{is_operator, FunctionRegistry, ParamType};
+use crate::schema::Schema;
+
+/// An expression annotated with the type of its result, with the same shape as the expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedExpression<'a> {
+    pub expression: &'a Expression,
+    /// `None` when the type is not known before evaluation, e.g. for `coalesce(1, "a")`, or when
+    /// the expression has an error.
+    pub value_type: Option<ValueType>,
+    /// Typed sub-expressions, in the order of [`Expression::children`].
+    pub children: Vec<TypedExpression<'a>>,
+}
+
+/// Infers the type of every node of the expression from the field types of the schema and the
+/// signatures of the functions, and checks the types of all operands and arguments.
+///
+/// All errors are returned at once. A node with an error gets no type, so the error is not
+/// reported again for the nodes around it.
+///
+/// ```
+/// use analytical_expression_parser::ast::ValueType;
+/// use analytical_expression_parser::{check_types, parse, ErrorKind, FunctionRegistry, Schema};
+///
+/// let schema = Schema::new().with_field("sales", ValueType::Integer);
+/// let functions = FunctionRegistry::with_builtins();
+///
+/// let expression = parse("round(sales / 3, 1)").unwrap();
+/// let typed = check_types(&expression, &schema, &functions).unwrap();
+/// assert_eq!(typed.value_type, Some(ValueType::Decimal));
+/// assert_eq!(typed.children[0].value_type, Some(ValueType::Decimal));
+///
+/// let expression = parse(r#"if 3 then sales + "abc" else upper(sales)"#).unwrap();
+/// let errors = check_types(&expression, &schema, &functions).unwrap_err();
+/// assert_eq!(errors.len(), 3);
+/// assert!(matches!(errors[0].kind(), ErrorKind::TypeMismatch { expected: ValueType::Boolean, .. }));
+/// ```
+pub fn check_types<'a>(
+    expression: &'a Expression,
+    schema: &Schema,
+    functions: &FunctionRegistry,
+) -> Result<TypedExpression<'a>, Vec<Error>> {
+    let mut checker = TypeChecker {
+        schema,
+        functions,
+        errors: Vec::new(),
+    };
+    let typed = checker.check(expression);
+    //children are checked before their parents, report errors in the order of the source
+    checker.errors.sort_by_key(|error| error.span().start);
+    if checker.errors.is_empty() {
+        Ok(typed)
+    } else {
+        Err(checker.errors)
+    }
+}
+
+struct TypeChecker<'s> {
+    schema: &'s Schema,
+    functions: &'s FunctionRegistry,
+    errors: Vec<Error>,
+}
+
+impl TypeChecker<'_> {
+    fn check<'a>(&mut self, expression: &'a Expression) -> TypedExpression<'a> {
+        let children: Vec<TypedExpression> = expression
+            .children()
+            .into_iter()
+            .map(|child| self.check(child))
+            .collect();
+        let value_type = match expression {
+            Expression::Literal { value, .. } => Some(value.value_type()),
+            Expression::FieldReference { field_id, span } => {
+                let value_type = self.schema.field_type(field_id);
+                if value_type.is_none() {
+                    self.error(
+                        ErrorKind::UnknownField {
+                            field_id: field_id.clone(),
+                        },
+                        *span,
+                    );
+                }
+                value_type
+            }
+            Expression::Function {
+                function_name, span, ..
+            } => self.function_type(function_name, &children, *span),
+            Expression::IfExpression { .. } => {
+                self.expect(ValueType::Boolean, &children[0]);
+                self.unify_branches([&children[1], &children[2]])
+            }
+            Expression::CaseExpression { else_result, .. } => {
+                let branches = children.len() - usize::from(else_result.is_some());
+                for condition in children[..branches].iter().step_by(2) {
+                    self.expect(ValueType::Boolean, condition);
+                }
+                let results = children[1..branches].iter().step_by(2).chain(&children[branches..]);
+                self.unify_branches(results)
+            }
+            Expression::ModifierExpression { where_modifier, .. } => {
+                let filters = where_modifier
+                    .iter()
+                    .flat_map(|where_modifier| &where_modifier.additional_filters);
+                for filter in filters {
+                    if let Some(filter) = children.iter().find(|child| ptr::eq(child.expression, filter)) {
+                        self.expect(ValueType::Boolean, filter);
+                    }
+                }
+                children[0].value_type
+            }
+        };
+        TypedExpression {
+            expression,
+            value_type,
+            children,
+        }
+    }
+
+    fn function_type(&mut self, function_name: &str, args: &[TypedExpression], span: Span) -> Option<ValueType> {
+        if is_operator(function_name) {
+            return self.operator_type(function_name, args);
+        }
+        if let Some(expected) = aggregate::aggregate_arity(function_name) {
+            if args.len() != expected {
+                self.error(
+                    ErrorKind::ArityMismatch {
+                        function_name: function_name.to_string(),
+                        expected_min: expected,
+                        expected_max: Some(expected),
+                        actual: args.len(),
+                    },
+                    span,
+                );
+                return None;
+            }
+            return self.aggregate_type(function_name, args);
+        }
+        let Some(function) = self.functions.get(function_name) else {
+            self.error(
+                ErrorKind::UnknownFunction {
+                    function_name: function_name.to_string(),
+                },
+                span,
+            );
+            return None;
+        };
+        let signature = function.signature();
+        if let Err(error) = signature.check_arity(function_name, args.len(), span) {
+            self.errors.push(error);
+            return None;
+        }
+        let mut valid = true;
+        for (index, arg) in args.iter().enumerate() {
+            let param_type = signature.param_type(index).unwrap_or(ParamType::Any);
+            valid &= self.expect_param(param_type, arg);
+        }
+        if !valid {
+            return None;
+        }
+        //generic results have the common type of the arguments declared with the same type
+        match signature.return_type() {
+            ParamType::Exact(value_type) => Some(value_type),
+            return_type => {
+                let same_type = args
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| signature.param_type(*index) == Some(return_type))
+                    .map(|(_, arg)| arg.value_type);
+                match (return_type, unify(same_type)) {
+                    (ParamType::Numeric, Some(ValueType::Null)) => Some(ValueType::Number),
+                    (ParamType::DateTime, Some(ValueType::Null)) => Some(ValueType::Timestamp),
+                    (_, value_type) => value_type,
+                }
+            }
+        }
+    }
+
+    fn operator_type(&mut self, operator: &str, operands: &[TypedExpression]) -> Option<ValueType> {
+        use ValueType::{Boolean, Date, Integer, Interval, Null, Timestamp};
+
+        let operator = operator.to_ascii_lowercase();
+        match operator.as_str() {
+            "and" | "or" | "not" => {
+                let mut valid = true;
+                for operand in operands {
+                    valid &= self.expect(Boolean, operand);
+                }
+                valid.then_some(Boolean)
+            }
+            "is null" | "is not null" => Some(Boolean),
+            "=" | "!=" | "<" | "<=" | ">" | ">=" => {
+                let [left_operand, right_operand] = operands else {
+                    return None;
+                };
+                let (left, right) = (left_operand.value_type?, right_operand.value_type?);
+                let comparable = left == right
+                    || left == Null
+                    || right == Null
+                    || (is_numeric(left) && is_numeric(right))
+                    || (matches!(left, Date | Timestamp) && matches!(right, Date | Timestamp));
+                if !comparable {
+                    self.mismatch(left, right_operand);
+                    return None;
+                }
+                Some(Boolean)
+            }
+            _ => {
+                let [left_operand, right_operand] = operands else {
+                    return None;
+                };
+                let (left, right) = (left_operand.value_type?, right_operand.value_type?);
+                if left == Null || right == Null {
+                    return Some(Null);
+                }
+                //the same combinations as `datetime::eval_temporal_arithmetic`
+                let result = match (operator.as_str(), left, right) {
+                    (_, left, right) if is_numeric(left) && is_numeric(right) => {
+                        if operator == "/" && left == Integer && right == Integer {
+                            ValueType::Decimal
+                        } else {
+                            widest(left, right)
+                        }
+                    }
+                    ("+" | "-", Date | Timestamp, Interval) => left,
+                    ("+", Interval, Date | Timestamp) => right,
+                    ("+" | "-", Date, Integer) | ("+", Integer, Date) => Date,
+                    ("-", Date, Date) => Integer,
+                    ("-", Date | Timestamp, Date | Timestamp) => Interval,
+                    ("+" | "-", Interval, Interval) | ("*", Interval, Integer) | ("*", Integer, Interval) => Interval,
+                    ("+" | "-", Date | Timestamp, _) => {
+                        self.mismatch(Interval, right_operand);
+                        return None;
+                    }
+                    //report temporal operands first, then the first operand that is not a number
+                    _ if is_temporal(left) || !(is_temporal(right) || is_numeric(left)) => {
+                        self.mismatch(ValueType::Number, left_operand);
+                        return None;
+                    }
+                    _ => {
+                        self.mismatch(ValueType::Number, right_operand);
+                        return None;
+                    }
+                };
+                Some(result)
+            }
+        }
+    }
+
+    fn aggregate_type(&mut self, function_name: &str, args: &[TypedExpression]) -> Option<ValueType> {
+        let function_name = function_name.to_ascii_lowercase();
+        match function_name.as_str() {
+            "count" | "countd" => return Some(ValueType::Integer),
+            "min" | "max" => return args[0].value_type,
+            _ => {}
+        }
+        let mut valid = true;
+        for arg in args {
+            valid &= self.expect_param(ParamType::Numeric, arg);
+        }
+        if !valid {
+            return None;
+        }
+        //exact values stay exact, like in `aggregate::Accumulator`
+        match (function_name.as_str(), args[0].value_type?) {
+            (_, ValueType::Null) => Some(ValueType::Null),
+            ("sum", value_type) => Some(value_type),
+            ("stddev" | "variance", _) | (_, ValueType::Number) => Some(ValueType::Number),
+            _ => Some(ValueType::Decimal),
+        }
+    }
+
+    //common type of the results of IF and CASE, the first result without it is a mismatch
+    fn unify_branches<'t, 'a: 't>(
+        &mut self,
+        branches: impl IntoIterator<Item = &'t TypedExpression<'a>>,
+    ) -> Option<ValueType> {
+        let mut result = ValueType::Null;
+        for branch in branches {
+            let value_type = branch.value_type?;
+            result = match common_type(result, value_type) {
+                Some(value_type) => value_type,
+                None => {
+                    self.mismatch(result, branch);
+                    return None;
+                }
+            };
+        }
+        Some(result)
+    }
+
+    //check that the type of the expression is the expected one or NULL, unknown types are not checked
+    fn expect(&mut self, expected: ValueType, typed: &TypedExpression) -> bool {
+        self.expect_param(ParamType::Exact(expected), typed)
+    }
+
+    fn expect_param(&mut self, expected: ParamType, typed: &TypedExpression) -> bool {
+        match typed.value_type {
+            Some(actual) if !expected.accepts(actual) => {
+                self.mismatch(expected.expected_type(), typed);
+                false
+            }
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn mismatch(&mut self, expected: ValueType, typed: &TypedExpression) {
+        if let Some(actual) = typed.value_type {
+            self.error(ErrorKind::TypeMismatch { expected, actual }, typed.expression.span());
+        }
+    }
+
+    fn error(&mut self, kind: ErrorKind, span: Span) {
+        self.errors.push(Error::new(kind, span));
+    }
+}
+
+fn is_numeric(value_type: ValueType) -> bool {
+    ParamType::Numeric.accepts(value_type) && value_type != ValueType::Null
+}
+
+fn is_temporal(value_type: ValueType) -> bool {
+    matches!(value_type, ValueType::Date | ValueType::Timestamp | ValueType::Interval)
+}
+
+//integers are promoted to decimals and both to floating point numbers, like in `eval::promote`
+fn widest(left: ValueType, right: ValueType) -> ValueType {
+    let rank = |value_type| match value_type {
+        ValueType::Integer => 0,
+        ValueType::Decimal => 1,
+        _ => 2,
+    };
+    if rank(left) >= rank(right) {
+        left
+    } else {
+        right
+    }
+}
+
+//common type of values that can come from any of the expressions, `None` if they have no common type
+fn unify(types: impl IntoIterator<Item = Option<ValueType>>) -> Option<ValueType> {
+    let mut result = ValueType::Null;
+    for value_type in types {
+        result = common_type(result, value_type?)?;
+    }
+    Some(result)
+}
+
+fn common_type(left: ValueType, right: ValueType) -> Option<ValueType> {
+    Some(match (left, right) {
+        (ValueType::Null, value_type) | (value_type, ValueType::Null) => value_type,
+        (left, right) if left == right => left,
+        (left, right) if is_numeric(left) && is_numeric(right) => widest(left, right),
+        (ValueType::Date | ValueType::Timestamp, ValueType::Date | ValueType::Timestamp) => ValueType::Timestamp,
+        _ => return None,
+    })
+}
@@ -0,0 +1,430 @@
+//! Compilation of numeric and boolean expressions to WebAssembly, so they can be evaluated by any
+//! WebAssembly runtime without the evaluator.
+//!
+//! The module imports its memory as `env.memory` and exports an `evaluate` function taking the
+//! address of a row in that memory. A row is a sequence of `f64` slots, one for each field in
+//! [`WatModule::fields`], booleans are stored as 0 or 1. The result is an `f64` for numbers and an
+//! `i32` (0 or 1) for booleans.
+//!
+//! All numbers are computed as `f64`, so integer and decimal results are approximated like
+//! floating point numbers. There are no NULL values: expressions that can produce NULL are not
+//! compiled, and errors of the evaluator such as a division by zero trap.
+//!
+//! ```
+//! use analytical_expression_parser::ast::ValueType;
+//! use analytical_expression_parser::wasm::to_wat;
+//! use analytical_expression_parser::{parse, Schema};
+//!
+//! let schema = Schema::new().with_field("price", ValueType::Number).with_field("paid", ValueType::Boolean);
+//! let module = to_wat(&parse("if paid then price * 2 else 0").unwrap(), &schema).unwrap();
+//! assert_eq!(module.fields, vec!["paid", "price"]);
+//! assert_eq!(module.value_type, ValueType::Number);
+//! assert!(module.wat.contains(
+//!     "    local.get $row
+//!     f64.load offset=0
+//!     f64.const 0
+//!     f64.ne
+//!     if (result f64)
+//!       local.get $row
+//!       f64.load offset=8
+//!       f64.const 2
+//!       f64.mul
+//!     else
+//!       f64.const 0
+//!     end"
+//! ));
+//! ```
+
+use std::fmt::Write;
+
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::ast::{Expression, LiteralValue, Span, ValueType};
+use crate::error::{Error, ErrorKind};
+use crate::functions::{is_operator, FunctionRegistry};
+use crate::schema::Schema;
+use crate::typecheck::{check_types, TypedExpression};
+
+//functions used by the generated code, only the ones that are called are added to the module
+const HELPERS: &[(&str, &str)] = &[
+    (
+        "$div",
+        "(func $div (param $left f64) (param $right f64) (result f64)
+    local.get $right
+    f64.const 0
+    f64.eq
+    if
+      unreachable
+    end
+    local.get $left
+    local.get $right
+    f64.div
+  )",
+    ),
+    (
+        "$mod",
+        "(func $mod (param $left f64) (param $right f64) (result f64)
+    local.get $right
+    f64.const 0
+    f64.eq
+    if
+      unreachable
+    end
+    local.get $left
+    local.get $left
+    local.get $right
+    f64.div
+    f64.trunc
+    local.get $right
+    f64.mul
+    f64.sub
+  )",
+    ),
+    (
+        "$sqrt",
+        "(func $sqrt (param $value f64) (result f64)
+    local.get $value
+    f64.const 0
+    f64.lt
+    if
+      unreachable
+    end
+    local.get $value
+    f64.sqrt
+  )",
+    ),
+    (
+        "$sign",
+        "(func $sign (param $value f64) (result f64)
+    local.get $value
+    f64.const 0
+    f64.gt
+    local.get $value
+    f64.const 0
+    f64.lt
+    i32.sub
+    f64.convert_i32_s
+  )",
+    ),
+    //halves are rounded away from zero, like `f64::round`
+    (
+        "$round",
+        "(func $round (param $value f64) (result f64)
+    (local $truncated f64)
+    local.get $value
+    f64.trunc
+    local.tee $truncated
+    f64.const 1
+    local.get $value
+    f64.copysign
+    f64.const 0
+    local.get $value
+    local.get $truncated
+    f64.sub
+    f64.abs
+    f64.const 0.5
+    f64.ge
+    select
+    f64.add
+  )",
+    ),
+];
+
+/// A WebAssembly module in text format evaluating an expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatModule {
+    pub wat: String,
+    /// Fields read by the expression, in the order of their slots in a row: the field at index `i`
+    /// is read from the address of the row plus `8 * i`.
+    pub fields: Vec<String>,
+    /// Type of the expression, booleans are returned as `i32` and all other types as `f64`.
+    pub value_type: ValueType,
+}
+
+/// Compiles an expression with numeric and boolean values to a WebAssembly module.
+///
+/// Fails with the first type error of the expression, or when it uses values, functions or
+/// modifiers that have no WebAssembly equivalent.
+pub fn to_wat(expression: &Expression, schema: &Schema) -> Result<WatModule, Error> {
+    let typed = check_types(expression, schema, FunctionRegistry::builtins()).map_err(|mut errors| errors.remove(0))?;
+    let mut compiler = Compiler {
+        fields: Vec::new(),
+        helpers: Vec::new(),
+        code: String::new(),
+        depth: 2,
+    };
+    compiler.compile(&typed)?;
+
+    let result_type = wasm_type(&typed)?;
+    let mut wat = "(module\n  (import \"env\" \"memory\" (memory 1))\n".to_string();
+    for (name, helper) in HELPERS {
+        if compiler.helpers.contains(name) {
+            writeln!(wat, "  {}", helper).unwrap();
+        }
+    }
+    writeln!(
+        wat,
+        "  (func (export \"evaluate\") (param $row i32) (result {})",
+        result_type
+    )
+    .unwrap();
+    wat.push_str(&compiler.code);
+    wat.push_str("  )\n)\n");
+    Ok(WatModule {
+        wat,
+        fields: compiler.fields,
+        value_type: typed.value_type.expect("compiled expressions have a type"),
+    })
+}
+
+struct Compiler {
+    fields: Vec<String>,
+    helpers: Vec<&'static str>,
+    code: String,
+    //indentation of the next instruction
+    depth: usize,
+}
+
+impl Compiler {
+    //adds the instructions leaving the value of the expression on the stack
+    fn compile(&mut self, typed: &TypedExpression) -> Result<(), Error> {
+        let result_type = wasm_type(typed)?;
+        match typed.expression {
+            Expression::Literal { value, span } => match value {
+                LiteralValue::BooleanValue(value) => self.emit(&format!("i32.const {}", i32::from(*value))),
+                LiteralValue::IntegerValue(value) => self.emit(&format!("f64.const {}", float_literal(*value as f64))),
+                LiteralValue::DecimalValue(value) => {
+                    let value = value.to_f64().ok_or_else(|| unsupported("decimal literal", *span))?;
+                    self.emit(&format!("f64.const {}", float_literal(value)))
+                }
+                LiteralValue::NumberValue(value) => self.emit(&format!("f64.const {}", float_literal(*value))),
+                _ => unreachable!("literals of other types are rejected by `wasm_type`"),
+            },
+            Expression::FieldReference { field_id, .. } => {
+                let slot = match self.fields.iter().position(|field| field == field_id) {
+                    Some(slot) => slot,
+                    None => {
+                        self.fields.push(field_id.clone());
+                        self.fields.len() - 1
+                    }
+                };
+                self.emit("local.get $row");
+                self.emit(&format!("f64.load offset={}", slot * 8));
+                if result_type == "i32" {
+                    self.emit("f64.const 0");
+                    self.emit("f64.ne");
+                }
+            }
+            Expression::Function {
+                function_name, span, ..
+            } => {
+                if is_operator(function_name) {
+                    self.operator(function_name, &typed.children)?;
+                } else {
+                    self.function(function_name, &typed.children, *span)?;
+                }
+            }
+            Expression::IfExpression { .. } => {
+                self.compile(&typed.children[0])?;
+                self.branches(result_type, &typed.children[1], &typed.children[2])?;
+            }
+            Expression::CaseExpression { else_result, span, .. } => {
+                if else_result.is_none() {
+                    return Err(unsupported("CASE without ELSE", *span));
+                }
+                self.case_branches(result_type, &typed.children)?;
+            }
+            Expression::ModifierExpression { span, .. } => {
+                return Err(unsupported("WHERE and GROUP BY modifiers", *span));
+            }
+        }
+        Ok(())
+    }
+
+    fn operator(&mut self, operator: &str, operands: &[TypedExpression]) -> Result<(), Error> {
+        let operator = operator.to_ascii_lowercase();
+        //AND and OR do not evaluate their right operand when the left one decides the result
+        if operator == "and" || operator == "or" {
+            self.compile(&operands[0])?;
+            self.emit("if (result i32)");
+            self.depth += 1;
+            if operator == "and" {
+                self.compile(&operands[1])?;
+                self.else_branch();
+                self.emit("i32.const 0");
+            } else {
+                self.emit("i32.const 1");
+                self.else_branch();
+                self.compile(&operands[1])?;
+            }
+            self.depth -= 1;
+            self.emit("end");
+            return Ok(());
+        }
+        for operand in operands {
+            self.compile(operand)?;
+        }
+        if operator == "/" {
+            self.call("$div");
+            return Ok(());
+        }
+        //comparisons of booleans have `i32` operands, everything else works on numbers
+        let instruction = match (operator.as_str(), wasm_type(&operands[0])?) {
+            ("not", _) => "i32.eqz",
+            ("is null", _) => "drop\ni32.const 0",
+            ("is not null", _) => "drop\ni32.const 1",
+            ("+", _) => "f64.add",
+            ("-", _) => "f64.sub",
+            ("*", _) => "f64.mul",
+            ("=", "i32") => "i32.eq",
+            ("!=", "i32") => "i32.ne",
+            ("<", "i32") => "i32.lt_u",
+            ("<=", "i32") => "i32.le_u",
+            (">", "i32") => "i32.gt_u",
+            (">=", "i32") => "i32.ge_u",
+            ("=", _) => "f64.eq",
+            ("!=", _) => "f64.ne",
+            ("<", _) => "f64.lt",
+            ("<=", _) => "f64.le",
+            (">", _) => "f64.gt",
+            _ => "f64.ge",
+        };
+        for instruction in instruction.lines() {
+            self.emit(instruction);
+        }
+        Ok(())
+    }
+
+    fn function(&mut self, function_name: &str, args: &[TypedExpression], span: Span) -> Result<(), Error> {
+        let function_name = function_name.to_ascii_lowercase();
+        //only the value is compiled for `round(value, digits)`, the digits have to be a literal
+        let digits = match (function_name.as_str(), args) {
+            ("round", [_, digits]) => match digits.expression {
+                Expression::Literal {
+                    value: LiteralValue::IntegerValue(digits),
+                    ..
+                } => Some(i32::try_from(*digits).map_err(|_| unsupported("round to that many digits", span))?),
+                _ => return Err(unsupported("round with digits that are not an integer literal", span)),
+            },
+            _ => None,
+        };
+        let args = if digits.is_some() { &args[..1] } else { args };
+        for arg in args {
+            self.compile(arg)?;
+        }
+        let arg_type = match args.first() {
+            Some(arg) => wasm_type(arg)?,
+            None => "f64",
+        };
+        match (function_name.as_str(), arg_type) {
+            ("abs", _) => self.emit("f64.abs"),
+            ("ceil", _) => self.emit("f64.ceil"),
+            ("floor", _) => self.emit("f64.floor"),
+            ("sqrt", _) => self.call("$sqrt"),
+            ("sign", _) => self.call("$sign"),
+            ("mod", _) => self.call("$mod"),
+            ("round", _) => match digits {
+                Some(digits) => {
+                    let factor = float_literal(10_f64.powi(digits));
+                    self.emit(&format!("f64.const {}", factor));
+                    self.emit("f64.mul");
+                    self.call("$round");
+                    self.emit(&format!("f64.const {}", factor));
+                    self.emit("f64.div");
+                }
+                None => self.call("$round"),
+            },
+            ("int" | "float", "i32") => self.emit("f64.convert_i32_u"),
+            ("int", _) => self.emit("f64.trunc"),
+            ("float", _) => {}
+            _ => return Err(unsupported(&format!("function `{}`", function_name), span)),
+        }
+        Ok(())
+    }
+
+    //the condition is on the stack
+    fn branches(
+        &mut self,
+        result_type: &str,
+        result: &TypedExpression,
+        else_result: &TypedExpression,
+    ) -> Result<(), Error> {
+        self.emit(&format!("if (result {})", result_type));
+        self.depth += 1;
+        self.compile(result)?;
+        self.else_branch();
+        self.compile(else_result)?;
+        self.depth -= 1;
+        self.emit("end");
+        Ok(())
+    }
+
+    //CASE branches as nested IF blocks, `children` are the conditions and results followed by the ELSE result
+    fn case_branches(&mut self, result_type: &str, children: &[TypedExpression]) -> Result<(), Error> {
+        match children {
+            [else_result] => self.compile(else_result),
+            [condition, result, rest @ ..] => {
+                self.compile(condition)?;
+                self.emit(&format!("if (result {})", result_type));
+                self.depth += 1;
+                self.compile(result)?;
+                self.else_branch();
+                self.case_branches(result_type, rest)?;
+                self.depth -= 1;
+                self.emit("end");
+                Ok(())
+            }
+            [] => unreachable!("CASE expressions have an ELSE result"),
+        }
+    }
+
+    fn else_branch(&mut self) {
+        self.depth -= 1;
+        self.emit("else");
+        self.depth += 1;
+    }
+
+    fn call(&mut self, helper: &'static str) {
+        if !self.helpers.contains(&helper) {
+            self.helpers.push(helper);
+        }
+        self.emit(&format!("call {}", helper));
+    }
+
+    fn emit(&mut self, instruction: &str) {
+        writeln!(self.code, "{:indent$}{}", "", instruction, indent = self.depth * 2).unwrap();
+    }
+}
+
+//type of the value of the expression on the stack
+fn wasm_type(typed: &TypedExpression) -> Result<&'static str, Error> {
+    let span = typed.expression.span();
+    match typed.value_type {
+        Some(ValueType::Boolean) => Ok("i32"),
+        Some(ValueType::Integer | ValueType::Decimal | ValueType::Number) => Ok("f64"),
+        Some(ValueType::Null) => Err(unsupported("NULL values", span)),
+        Some(value_type) => Err(unsupported(&format!("{} values", value_type), span)),
+        None => Err(unsupported("values of an unknown type", span)),
+    }
+}
+
+//`f64.const` operand, the shortest text that is parsed back to the same number
+fn float_literal(value: f64) -> String {
+    if value.is_nan() {
+        "nan".to_string()
+    } else if value.is_infinite() {
+        format!("{}inf", if value < 0.0 { "-" } else { "" })
+    } else {
+        value.to_string()
+    }
+}
+
+fn unsupported(feature: &str, span: Span) -> Error {
+    Error::new(
+        ErrorKind::UnsupportedInDialect {
+            dialect: "WebAssembly".to_string(),
+            feature: feature.to_string(),
+        },
+        span,
+    )
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use rust_decimal::prelude::ToPrimitive;
+use wasmi::{Engine, Linker, Memory, MemoryType, Module, Store, Val};
+
+use crate::ast::{LiteralValue, ValueType};
+use crate::wasm::{to_wat, WatModule};
+use crate::{evaluate, parse, Decimal, ErrorKind, Schema};
+
+//expressions compiled to WebAssembly and compared with the evaluator on `rows`
+const CASES: &[&str] = &[
+    "qty + price * 2 - sales",
+    "(qty + 1) * (price - 0.5)",
+    "qty / 4",
+    "sales / qty",
+    "-1.5 * qty - -2",
+    "price / 2.5 * 1e3",
+    "qty >= 3",
+    "qty = 3 or price < 10 and not paid",
+    "paid = (qty > 2)",
+    "paid != false and paid > (qty < 0)",
+    "qty > 0 and 10 / qty > 2",
+    "qty = 0 or 10 / qty > 2",
+    "price is null or qty is not null",
+    "if paid then sales else 0",
+    "if qty > 2 then paid else qty < 1",
+    "case when qty > 4 then 3 when qty > 1 then 2.5 else price end",
+    "abs(sales - 10) + ceil(price) + floor(price)",
+    "sqrt(qty) + sign(sales - 10) + sign(0)",
+    "mod(qty, 3) + mod(price, 2.5)",
+    "round(price) + round(price, 1) + round(sales, -1)",
+    "round(-2.5) + round(2.5) + round(0.49999999999999994)",
+    "int(price) + float(qty) + int(paid) + float(paid)",
+];
+
+fn schema() -> Schema {
+    Schema::new()
+        .with_field("qty", ValueType::Integer)
+        .with_field("price", ValueType::Number)
+        .with_field("sales", ValueType::Decimal)
+        .with_field("paid", ValueType::Boolean)
+}
+
+fn rows() -> Vec<HashMap<String, LiteralValue>> {
+    let values: [(i64, f64, &str, bool); 4] = [
+        (0, 9.75, "12.5", false),
+        (1, 0.0, "-3.25", true),
+        (3, 125.5, "10", true),
+        (7, 2.25, "0.1", false),
+    ];
+    values
+        .iter()
+        .map(|(qty, price, sales, paid)| {
+            HashMap::from([
+                ("qty".to_string(), LiteralValue::IntegerValue(*qty)),
+                ("price".to_string(), LiteralValue::NumberValue(*price)),
+                (
+                    "sales".to_string(),
+                    LiteralValue::DecimalValue(sales.parse::<Decimal>().unwrap()),
+                ),
+                ("paid".to_string(), LiteralValue::BooleanValue(*paid)),
+            ])
+        })
+        .collect()
+}
+
+//the slot of a field as written by a host, numbers as `f64` and booleans as 0 or 1
+fn slot(value: &LiteralValue) -> f64 {
+    match value {
+        LiteralValue::IntegerValue(value) => *value as f64,
+        LiteralValue::NumberValue(value) => *value,
+        LiteralValue::DecimalValue(value) => value.to_f64().unwrap(),
+        LiteralValue::BooleanValue(value) => f64::from(u8::from(*value)),
+        other => panic!("no slot for {:?}", other),
+    }
+}
+
+//instantiates the module and evaluates it on every row, `None` when the evaluation traps
+fn run(module: &WatModule, rows: &[HashMap<String, LiteralValue>]) -> Vec<Option<Val>> {
+    let engine = Engine::default();
+    let wasm = wat::parse_str(&module.wat).unwrap_or_else(|error| panic!("{}\n{}", error, module.wat));
+    let wasm_module = Module::new(&engine, &wasm).unwrap();
+    let mut store = Store::new(&engine, ());
+    let memory = Memory::new(&mut store, MemoryType::new(1, None).unwrap()).unwrap();
+    let mut linker = Linker::new(&engine);
+    linker.define("env", "memory", memory).unwrap();
+    let instance = linker
+        .instantiate(&mut store, &wasm_module)
+        .unwrap()
+        .start(&mut store)
+        .unwrap();
+    let evaluate = instance.get_func(&store, "evaluate").unwrap();
+
+    let row_size = module.fields.len() * 8;
+    for (index, row) in rows.iter().enumerate() {
+        let bytes: Vec<u8> = module
+            .fields
+            .iter()
+            .flat_map(|field| slot(&row[field]).to_le_bytes())
+            .collect();
+        memory.write(&mut store, index * row_size, &bytes).unwrap();
+    }
+    (0..rows.len())
+        .map(|index| {
+            let mut result = [Val::I32(0)];
+            let row = Val::I32((index * row_size) as i32);
+            evaluate
+                .call(&mut store, &[row], &mut result)
+                .ok()
+                .map(|()| result[0].clone())
+        })
+        .collect()
+}
+
+#[test]
+fn test_wasm_runs_on_wasmi() {
+    let schema = schema();
+    let rows = rows();
+    for case in CASES {
+        let expression = parse(case).unwrap();
+        let module = to_wat(&expression, &schema).unwrap_or_else(|error| panic!("{}: {}", case, error));
+        let results = run(&module, &rows);
+        for (row, actual) in rows.iter().zip(results) {
+            let expected = evaluate(&expression, row).ok();
+            match (actual, expected) {
+                (Some(Val::I32(actual)), Some(LiteralValue::BooleanValue(expected))) => {
+                    assert_eq!(actual, i32::from(expected), "{} on {:?}", case, row)
+                }
+                (Some(Val::F64(actual)), Some(expected)) => {
+                    let (actual, expected) = (f64::from(actual), slot(&expected));
+                    assert!(
+                        (actual - expected).abs() < 1e-9,
+                        "{} on {:?}: {} != {}",
+                        case,
+                        row,
+                        actual,
+                        expected
+                    )
+                }
+                (None, None) => {}
+                (actual, expected) => panic!("{} on {:?}: {:?} != {:?}", case, row, actual, expected),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_wasm_traps_on_evaluation_errors() {
+    let schema = schema();
+    let rows = rows();
+    for case in ["price / qty", "mod(price, qty)", "sqrt(sales)"] {
+        let expression = parse(case).unwrap();
+        let results = run(&to_wat(&expression, &schema).unwrap(), &rows);
+        for (row, actual) in rows.iter().zip(results) {
+            assert_eq!(
+                actual.is_none(),
+                evaluate(&expression, row).is_err(),
+                "{} on {:?}",
+                case,
+                row
+            );
+        }
+    }
+}
+
+#[test]
+fn test_wasm_errors() {
+    let schema = schema().with_field("region", ValueType::String);
+    let cases = [
+        (r#"region = "EU""#, "string values cannot be translated to WebAssembly"),
+        ("qty + null", "NULL values cannot be translated to WebAssembly"),
+        (
+            "case when paid then 1 end",
+            "CASE without ELSE cannot be translated to WebAssembly",
+        ),
+        ("pow(qty, 2)", "function `pow` cannot be translated to WebAssembly"),
+        (
+            "round(price, qty)",
+            "round with digits that are not an integer literal cannot be translated to WebAssembly",
+        ),
+        ("sum(qty)", "function `sum` cannot be translated to WebAssembly"),
+        (
+            "sum(qty) [group by region]",
+            "WHERE and GROUP BY modifiers cannot be translated to WebAssembly",
+        ),
+        (
+            "date '2024-01-01' - 1",
+            "date values cannot be translated to WebAssembly",
+        ),
+    ];
+    for (input, message) in cases {
+        let error = to_wat(&parse(input).unwrap(), &schema).unwrap_err();
+        assert!(
+            matches!(error.kind(), ErrorKind::UnsupportedInDialect { .. }),
+            "{}",
+            input
+        );
+        assert_eq!(error.kind().to_string(), message, "{}", input);
+    }
+
+    let error = to_wat(&parse("qty + paid").unwrap(), &schema).unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::TypeMismatch { .. }));
+}
@@ -0,0 +1,198 @@
+-- sales + qty * 2
+"sales" + "qty" * 2
+
+-- (sales + qty) * 2
+("sales" + "qty") * 2
+
+-- sales - (qty - 1)
+"sales" - ("qty" - 1)
+
+-- qty - -1
+"qty" - -1
+
+-- qty / 4
+"qty" / 4
+
+-- qty / (qty + 1)
+"qty" / ("qty" + 1)
+
+-- price / 2.5 * 1e3
+"price" / 2.5 * 1e3
+
+-- region = "EU" and not paid or qty >= 10
+"region" = 'EU' AND NOT "paid" OR "qty" >= 10
+
+-- not (region != "EU" or paid) and (qty < 2 or qty > 4)
+NOT ("region" <> 'EU' OR "paid") AND ("qty" < 2 OR "qty" > 4)
+
+-- price is null or (qty + 1) is not null
+"price" IS NULL OR "qty" + 1 IS NOT NULL
+
+-- if paid then sales else 0
+CASE WHEN "paid" THEN "sales" ELSE 0 END
+
+-- case when qty > 5 then "big" when qty > 1 then "medium" end
+CASE WHEN "qty" > 5 THEN 'big' WHEN "qty" > 1 THEN 'medium' END
+
+-- region = "it's \"quoted\" \\ here"
+"region" = 'it''s "quoted" \ here'
+
+-- concat(region, "-", qty, null, paid)
+CONCAT(COALESCE("region", ''), COALESCE('-', ''), COALESCE(CAST("qty" AS VARCHAR), ''), COALESCE(CASE WHEN "paid" THEN 'true' WHEN NOT "paid" THEN 'false' END, ''))
+
+-- round(price, 1) + abs(sales - 10)
+ROUND("price", 1) + ABS("sales" - 10)
+
+-- pow(qty, 2) + sqrt(qty) + mod(qty, 3)
+POWER("qty", 2) + SQRT("qty") + ("qty" % 3)
+
+-- log(qty) + log(qty, 2) + ln(qty) + exp(1)
+LOG10("qty") + (LN("qty") / LN(2)) + LN("qty") + EXP(1)
+
+-- len(region) + len(order_id)
+LENGTH("region") + LENGTH("order_id")
+
+-- substr(region, 2) = substr(order_id, 1, 2)
+SUBSTRING("region" FROM 2) = SUBSTRING("order_id" FROM 1 FOR 2)
+
+-- concat(upper(left(region, 1)), lower(right(region, 1)))
+CONCAT(COALESCE(UPPER(LEFT("region", 1)), ''), COALESCE(LOWER(RIGHT("region", 1)), ''))
+
+-- contains(region, "U") or startswith(order_id, "A")
+CONTAINS("region", 'U') OR STARTS_WITH("order_id", 'A')
+
+-- replace(trim(region), "E", "e")
+REPLACE(TRIM("region"), 'E', 'e')
+
+-- split(order_id, "-", 2)
+SPLIT_PART("order_id", '-', 2)
+
+-- coalesce(price, sales, 0) + ifnull(price, 1) + zn(price)
+COALESCE("price", "sales", 0) + COALESCE("price", 1) + COALESCE("price", 0)
+
+-- nullif(qty, 0)
+NULLIF("qty", 0)
+
+-- iif(paid, 1, 0) + iif(qty > 2, 1, 0, 2)
+CASE WHEN "paid" THEN 1 WHEN NOT "paid" THEN 0 END + CASE WHEN "qty" > 2 THEN 1 WHEN NOT "qty" > 2 THEN 0 ELSE 2 END
+
+-- str(qty)
+CAST("qty" AS VARCHAR)
+
+-- int(price) + float(sales)
+CAST("price" AS BIGINT) + CAST("sales" AS DOUBLE)
+
+-- bool(qty)
+CAST("qty" AS BOOLEAN)
+
+-- today() < now()
+CURRENT_DATE < CAST(CURRENT_TIMESTAMP AS TIMESTAMP)
+
+-- datepart("year", orders.date) * 100 + datepart("month", orders.date)
+EXTRACT(YEAR FROM "orders"."date") * 100 + EXTRACT(MONTH FROM "orders"."date")
+
+-- datepart("quarter", orders.date)
+EXTRACT(QUARTER FROM "orders"."date")
+
+-- datepart("week", orders.date)
+EXTRACT(WEEK FROM "orders"."date")
+
+-- datepart("hour", orders.shipped)
+EXTRACT(HOUR FROM "orders"."shipped")
+
+-- date_trunc("month", orders.date)
+CAST(DATE_TRUNC('MONTH', "orders"."date") AS DATE)
+
+-- date_trunc("quarter", orders.date)
+CAST(DATE_TRUNC('QUARTER', "orders"."date") AS DATE)
+
+-- date_trunc("week", orders.shipped)
+DATE_TRUNC('WEEK', "orders"."shipped")
+
+-- date_trunc("hour", orders.shipped)
+DATE_TRUNC('HOUR', "orders"."shipped")
+
+-- datediff("day", orders.date, orders.shipped)
+DATE_DIFF('DAY', "orders"."date", "orders"."shipped")
+
+-- datediff("week", orders.date, orders.shipped)
+DATE_DIFF('WEEK', "orders"."date", "orders"."shipped")
+
+-- datediff("month", orders.date, orders.shipped)
+DATE_DIFF('MONTH', "orders"."date", "orders"."shipped")
+
+-- datediff("quarter", orders.date, orders.shipped)
+DATE_DIFF('QUARTER', "orders"."date", "orders"."shipped")
+
+-- datediff("year", orders.date, orders.shipped)
+DATE_DIFF('YEAR', "orders"."date", "orders"."shipped")
+
+-- datediff("hour", orders.date, orders.shipped)
+DATE_DIFF('HOUR', "orders"."date", "orders"."shipped")
+
+-- dateadd("month", 2, orders.date)
+CAST("orders"."date" + TO_MONTHS(2) AS DATE)
+
+-- dateadd("week", qty, orders.date)
+("orders"."date" + 7 * "qty")
+
+-- dateadd("hour", qty, orders.shipped)
+("orders"."shipped" + TO_HOURS("qty"))
+
+-- orders.date + 7
+("orders"."date" + 7)
+
+-- orders.date - qty
+("orders"."date" + -"qty")
+
+-- orders.date - date '2024-01-01'
+DATE_DIFF('DAY', DATE '2024-01-01', "orders"."date")
+
+-- orders.date + interval '1 month 2 days'
+(CAST("orders"."date" + TO_MONTHS(1) AS DATE) + 2)
+
+-- orders.shipped - interval '90 minutes'
+("orders"."shipped" + TO_SECONDS(-5400))
+
+-- orders.date < timestamp '2024-03-01 12:30:00'
+"orders"."date" < TIMESTAMP '2024-03-01 12:30:00'
+
+-- orders.shipped - orders.date
+"orders"."shipped" - "orders"."date"
+
+-- interval '3 days' * qty
+INTERVAL '3 days' * "qty"
+
+-- sum(sales) / count(order_id)
+SUM("sales") / COUNT("order_id")
+
+-- avg(qty) + countd(region)
+AVG("qty") + COUNT(DISTINCT "region")
+
+-- min(orders.date) < max(orders.shipped)
+MIN("orders"."date") < MAX("orders"."shipped")
+
+-- median(price)
+MEDIAN("price")
+
+-- percentile(price, 0.9)
+QUANTILE_CONT("price", 0.9)
+
+-- stddev(qty) + variance(qty)
+STDDEV_SAMP("qty") + VAR_SAMP("qty")
+
+-- sum(sales) [where ignore all filters]
+-- error: WHERE and GROUP BY modifiers cannot be translated to DuckDB
+
+-- datepart("fortnight", orders.date)
+-- error: invalid argument of function `datepart`, unknown date part `fortnight`
+
+-- datepart(region, orders.date)
+-- error: date part that is not a string literal cannot be translated to DuckDB
+
+-- discount * 2
+-- error: unknown field `discount`
+
+-- sales + "a"
+-- error: type mismatch, expected number but got string
+
@@ -0,0 +1,198 @@
+-- sales + qty * 2
+`sales` + `qty` * 2
+
+-- (sales + qty) * 2
+(`sales` + `qty`) * 2
+
+-- sales - (qty - 1)
+`sales` - (`qty` - 1)
+
+-- qty - -1
+`qty` - -1
+
+-- qty / 4
+`qty` / 4
+
+-- qty / (qty + 1)
+`qty` / (`qty` + 1)
+
+-- price / 2.5 * 1e3
+`price` / 2.5 * 1e3
+
+-- region = "EU" and not paid or qty >= 10
+`region` = 'EU' AND NOT `paid` OR `qty` >= 10
+
+-- not (region != "EU" or paid) and (qty < 2 or qty > 4)
+NOT (`region` <> 'EU' OR `paid`) AND (`qty` < 2 OR `qty` > 4)
+
+-- price is null or (qty + 1) is not null
+`price` IS NULL OR `qty` + 1 IS NOT NULL
+
+-- if paid then sales else 0
+CASE WHEN `paid` THEN `sales` ELSE 0 END
+
+-- case when qty > 5 then "big" when qty > 1 then "medium" end
+CASE WHEN `qty` > 5 THEN 'big' WHEN `qty` > 1 THEN 'medium' END
+
+-- region = "it's \"quoted\" \\ here"
+`region` = 'it''s "quoted" \\ here'
+
+-- concat(region, "-", qty, null, paid)
+CONCAT(COALESCE(`region`, ''), COALESCE('-', ''), COALESCE(CAST(`qty` AS CHAR), ''), COALESCE(CASE WHEN `paid` THEN 'true' WHEN NOT `paid` THEN 'false' END, ''))
+
+-- round(price, 1) + abs(sales - 10)
+ROUND(`price`, 1) + ABS(`sales` - 10)
+
+-- pow(qty, 2) + sqrt(qty) + mod(qty, 3)
+POWER(`qty`, 2) + SQRT(`qty`) + MOD(`qty`, 3)
+
+-- log(qty) + log(qty, 2) + ln(qty) + exp(1)
+LOG10(`qty`) + (LN(`qty`) / LN(2)) + LN(`qty`) + EXP(1)
+
+-- len(region) + len(order_id)
+CHAR_LENGTH(`region`) + CHAR_LENGTH(`order_id`)
+
+-- substr(region, 2) = substr(order_id, 1, 2)
+SUBSTRING(`region` FROM 2) = SUBSTRING(`order_id` FROM 1 FOR 2)
+
+-- concat(upper(left(region, 1)), lower(right(region, 1)))
+CONCAT(COALESCE(UPPER(LEFT(`region`, 1)), ''), COALESCE(LOWER(RIGHT(`region`, 1)), ''))
+
+-- contains(region, "U") or startswith(order_id, "A")
+(POSITION('U' IN `region`) > 0) OR (LEFT(`order_id`, CHAR_LENGTH('A')) = 'A')
+
+-- replace(trim(region), "E", "e")
+REPLACE(TRIM(`region`), 'E', 'e')
+
+-- split(order_id, "-", 2)
+-- error: function `split` cannot be translated to MySQL
+
+-- coalesce(price, sales, 0) + ifnull(price, 1) + zn(price)
+COALESCE(`price`, `sales`, 0) + COALESCE(`price`, 1) + COALESCE(`price`, 0)
+
+-- nullif(qty, 0)
+NULLIF(`qty`, 0)
+
+-- iif(paid, 1, 0) + iif(qty > 2, 1, 0, 2)
+CASE WHEN `paid` THEN 1 WHEN NOT `paid` THEN 0 END + CASE WHEN `qty` > 2 THEN 1 WHEN NOT `qty` > 2 THEN 0 ELSE 2 END
+
+-- str(qty)
+CAST(`qty` AS CHAR)
+
+-- int(price) + float(sales)
+CAST(`price` AS SIGNED) + CAST(`sales` AS DOUBLE)
+
+-- bool(qty)
+-- error: function `bool` cannot be translated to MySQL
+
+-- today() < now()
+CURRENT_DATE < LOCALTIMESTAMP
+
+-- datepart("year", orders.date) * 100 + datepart("month", orders.date)
+EXTRACT(YEAR FROM `orders`.`date`) * 100 + EXTRACT(MONTH FROM `orders`.`date`)
+
+-- datepart("quarter", orders.date)
+EXTRACT(QUARTER FROM `orders`.`date`)
+
+-- datepart("week", orders.date)
+WEEK(`orders`.`date`, 3)
+
+-- datepart("hour", orders.shipped)
+EXTRACT(HOUR FROM `orders`.`shipped`)
+
+-- date_trunc("month", orders.date)
+-- error: function `date_trunc` cannot be translated to MySQL
+
+-- date_trunc("quarter", orders.date)
+-- error: function `date_trunc` cannot be translated to MySQL
+
+-- date_trunc("week", orders.shipped)
+-- error: function `date_trunc` cannot be translated to MySQL
+
+-- date_trunc("hour", orders.shipped)
+-- error: function `date_trunc` cannot be translated to MySQL
+
+-- datediff("day", orders.date, orders.shipped)
+DATEDIFF(`orders`.`shipped`, `orders`.`date`)
+
+-- datediff("week", orders.date, orders.shipped)
+-- error: function `datediff` cannot be translated to MySQL
+
+-- datediff("month", orders.date, orders.shipped)
+((EXTRACT(YEAR FROM `orders`.`shipped`) - EXTRACT(YEAR FROM `orders`.`date`)) * 12 + EXTRACT(MONTH FROM `orders`.`shipped`) - EXTRACT(MONTH FROM `orders`.`date`))
+
+-- datediff("quarter", orders.date, orders.shipped)
+((EXTRACT(YEAR FROM `orders`.`shipped`) - EXTRACT(YEAR FROM `orders`.`date`)) * 4 + EXTRACT(QUARTER FROM `orders`.`shipped`) - EXTRACT(QUARTER FROM `orders`.`date`))
+
+-- datediff("year", orders.date, orders.shipped)
+(EXTRACT(YEAR FROM `orders`.`shipped`) - EXTRACT(YEAR FROM `orders`.`date`))
+
+-- datediff("hour", orders.date, orders.shipped)
+-- error: function `datediff` cannot be translated to MySQL
+
+-- dateadd("month", 2, orders.date)
+DATE_ADD(`orders`.`date`, INTERVAL 2 MONTH)
+
+-- dateadd("week", qty, orders.date)
+DATE_ADD(`orders`.`date`, INTERVAL `qty` WEEK)
+
+-- dateadd("hour", qty, orders.shipped)
+DATE_ADD(`orders`.`shipped`, INTERVAL `qty` HOUR)
+
+-- orders.date + 7
+DATE_ADD(`orders`.`date`, INTERVAL 7 DAY)
+
+-- orders.date - qty
+DATE_ADD(`orders`.`date`, INTERVAL -`qty` DAY)
+
+-- orders.date - date '2024-01-01'
+DATEDIFF(`orders`.`date`, DATE '2024-01-01')
+
+-- orders.date + interval '1 month 2 days'
+-- error: interval literal cannot be translated to MySQL
+
+-- orders.shipped - interval '90 minutes'
+-- error: interval literal cannot be translated to MySQL
+
+-- orders.date < timestamp '2024-03-01 12:30:00'
+`orders`.`date` < TIMESTAMP '2024-03-01 12:30:00'
+
+-- orders.shipped - orders.date
+-- error: date and interval arithmetic cannot be translated to MySQL
+
+-- interval '3 days' * qty
+-- error: interval literal cannot be translated to MySQL
+
+-- sum(sales) / count(order_id)
+SUM(`sales`) / COUNT(`order_id`)
+
+-- avg(qty) + countd(region)
+AVG(`qty`) + COUNT(DISTINCT `region`)
+
+-- min(orders.date) < max(orders.shipped)
+MIN(`orders`.`date`) < MAX(`orders`.`shipped`)
+
+-- median(price)
+-- error: function `median` cannot be translated to MySQL
+
+-- percentile(price, 0.9)
+-- error: function `percentile` cannot be translated to MySQL
+
+-- stddev(qty) + variance(qty)
+STDDEV_SAMP(`qty`) + VAR_SAMP(`qty`)
+
+-- sum(sales) [where ignore all filters]
+-- error: WHERE and GROUP BY modifiers cannot be translated to MySQL
+
+-- datepart("fortnight", orders.date)
+-- error: invalid argument of function `datepart`, unknown date part `fortnight`
+
+-- datepart(region, orders.date)
+-- error: date part that is not a string literal cannot be translated to MySQL
+
+-- discount * 2
+-- error: unknown field `discount`
+
+-- sales + "a"
+-- error: type mismatch, expected number but got string
+
@@ -0,0 +1,486 @@
+-- sum(sales) (ansi)
+SELECT "region" AS "group_1", SUM("sales") AS "value"
+FROM orders
+WHERE "paid"
+GROUP BY "region";
+
+-- sum(sales) (postgresql)
+SELECT "region" AS "group_1", SUM("sales") AS "value"
+FROM orders
+WHERE "paid"
+GROUP BY "region";
+
+-- sum(sales) (sqlite)
+SELECT "region" AS "group_1", SUM("sales") AS "value"
+FROM orders
+WHERE "paid"
+GROUP BY "region";
+
+-- sum(sales) (duckdb)
+SELECT "region" AS "group_1", SUM("sales") AS "value"
+FROM orders
+WHERE "paid"
+GROUP BY "region";
+
+-- sum(sales) (mysql)
+SELECT `region` AS `group_1`, SUM(`sales`) AS `value`
+FROM orders
+WHERE `paid`
+GROUP BY `region`;
+
+-- sum(sales) (bigquery)
+SELECT `region` AS `group_1`, SUM(`sales`) AS `value`
+FROM orders
+WHERE `paid`
+GROUP BY `region`;
+
+-- sum(sales) / sum(sales) [where ignore all filters] (ansi)
+SELECT "region" AS "group_1", SUM(CASE WHEN "paid" THEN "sales" ELSE NULL END) / SUM("sales") AS "value"
+FROM orders
+GROUP BY "region"
+HAVING COUNT(CASE WHEN "paid" THEN 1 ELSE NULL END) > 0;
+
+-- sum(sales) / sum(sales) [where ignore all filters] (postgresql)
+SELECT "region" AS "group_1", SUM(CASE WHEN "paid" THEN "sales" ELSE NULL END) / SUM("sales") AS "value"
+FROM orders
+GROUP BY "region"
+HAVING COUNT(CASE WHEN "paid" THEN 1 ELSE NULL END) > 0;
+
+-- sum(sales) / sum(sales) [where ignore all filters] (sqlite)
+SELECT "region" AS "group_1", SUM(CASE WHEN "paid" THEN "sales" ELSE NULL END) / SUM("sales") AS "value"
+FROM orders
+GROUP BY "region"
+HAVING COUNT(CASE WHEN "paid" THEN 1 ELSE NULL END) > 0;
+
+-- sum(sales) / sum(sales) [where ignore all filters] (duckdb)
+SELECT "region" AS "group_1", SUM(CASE WHEN "paid" THEN "sales" ELSE NULL END) / SUM("sales") AS "value"
+FROM orders
+GROUP BY "region"
+HAVING COUNT(CASE WHEN "paid" THEN 1 ELSE NULL END) > 0;
+
+-- sum(sales) / sum(sales) [where ignore all filters] (mysql)
+SELECT `region` AS `group_1`, SUM(CASE WHEN `paid` THEN `sales` ELSE NULL END) / SUM(`sales`) AS `value`
+FROM orders
+GROUP BY `region`
+HAVING COUNT(CASE WHEN `paid` THEN 1 ELSE NULL END) > 0;
+
+-- sum(sales) / sum(sales) [where ignore all filters] (bigquery)
+SELECT `region` AS `group_1`, SUM(CASE WHEN `paid` THEN `sales` ELSE NULL END) / SUM(`sales`) AS `value`
+FROM orders
+GROUP BY `region`
+HAVING COUNT(CASE WHEN `paid` THEN 1 ELSE NULL END) > 0;
+
+-- sum(qty) [where allow filters on region and qty > 1] (ansi)
+SELECT "region" AS "group_1", SUM(CASE WHEN "region" <> 'it''s' AND "qty" > 1 THEN "qty" ELSE NULL END) AS "value"
+FROM orders
+GROUP BY "region"
+HAVING COUNT(CASE WHEN "region" <> 'it''s' AND "paid" THEN 1 ELSE NULL END) > 0;
+
+-- sum(qty) [where allow filters on region and qty > 1] (postgresql)
+SELECT "region" AS "group_1", SUM(CASE WHEN "region" <> 'it''s' AND "qty" > 1 THEN "qty" ELSE NULL END) AS "value"
+FROM orders
+GROUP BY "region"
+HAVING COUNT(CASE WHEN "region" <> 'it''s' AND "paid" THEN 1 ELSE NULL END) > 0;
+
+-- sum(qty) [where allow filters on region and qty > 1] (sqlite)
+SELECT "region" AS "group_1", SUM(CASE WHEN "region" <> 'it''s' AND "qty" > 1 THEN "qty" ELSE NULL END) AS "value"
+FROM orders
+GROUP BY "region"
+HAVING COUNT(CASE WHEN "region" <> 'it''s' AND "paid" THEN 1 ELSE NULL END) > 0;
+
+-- sum(qty) [where allow filters on region and qty > 1] (duckdb)
+SELECT "region" AS "group_1", SUM(CASE WHEN "region" <> 'it''s' AND "qty" > 1 THEN "qty" ELSE NULL END) AS "value"
+FROM orders
+GROUP BY "region"
+HAVING COUNT(CASE WHEN "region" <> 'it''s' AND "paid" THEN 1 ELSE NULL END) > 0;
+
+-- sum(qty) [where allow filters on region and qty > 1] (mysql)
+SELECT `region` AS `group_1`, SUM(CASE WHEN `region` <> 'it''s' AND `qty` > 1 THEN `qty` ELSE NULL END) AS `value`
+FROM orders
+GROUP BY `region`
+HAVING COUNT(CASE WHEN `region` <> 'it''s' AND `paid` THEN 1 ELSE NULL END) > 0;
+
+-- sum(qty) [where allow filters on region and qty > 1] (bigquery)
+SELECT `region` AS `group_1`, SUM(CASE WHEN `region` <> 'it\'s' AND `qty` > 1 THEN `qty` ELSE NULL END) AS `value`
+FROM orders
+GROUP BY `region`
+HAVING COUNT(CASE WHEN `region` <> 'it\'s' AND `paid` THEN 1 ELSE NULL END) > 0;
+
+-- sum(sales) / sum(sales) [group by region] (ansi)
+WITH "lod_source" AS (SELECT "sales" AS "field_1", "region" AS "field_2", "order_id" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS NOT DISTINCT FROM "lod_level_1"."key_1")
+SELECT "field_3" AS "group_1", SUM("field_1") / CASE WHEN MIN("lod_1") = MAX("lod_1") THEN MIN("lod_1") ELSE NULL END AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_3";
+
+-- sum(sales) / sum(sales) [group by region] (postgresql)
+WITH "lod_source" AS (SELECT "sales" AS "field_1", "region" AS "field_2", "order_id" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS NOT DISTINCT FROM "lod_level_1"."key_1")
+SELECT "field_3" AS "group_1", SUM("field_1") / CASE WHEN MIN("lod_1") = MAX("lod_1") THEN MIN("lod_1") ELSE NULL END AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_3";
+
+-- sum(sales) / sum(sales) [group by region] (sqlite)
+WITH "lod_source" AS (SELECT "sales" AS "field_1", "region" AS "field_2", "order_id" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS "lod_level_1"."key_1")
+SELECT "field_3" AS "group_1", SUM("field_1") / CASE WHEN MIN("lod_1") = MAX("lod_1") THEN MIN("lod_1") ELSE NULL END AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_3";
+
+-- sum(sales) / sum(sales) [group by region] (duckdb)
+WITH "lod_source" AS (SELECT "sales" AS "field_1", "region" AS "field_2", "order_id" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS NOT DISTINCT FROM "lod_level_1"."key_1")
+SELECT "field_3" AS "group_1", SUM("field_1") / CASE WHEN MIN("lod_1") = MAX("lod_1") THEN MIN("lod_1") ELSE NULL END AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_3";
+
+-- sum(sales) / sum(sales) [group by region] (mysql)
+WITH `lod_source` AS (SELECT `sales` AS `field_1`, `region` AS `field_2`, `order_id` AS `field_3` FROM orders),
+`lod_level_1` AS (SELECT `field_2` AS `key_1`, SUM(`field_1`) AS `value` FROM `lod_source` GROUP BY `field_2`),
+`lod_rows_1` AS (SELECT `lod_source`.*, `lod_level_1`.`value` AS `lod_1` FROM `lod_source` LEFT JOIN `lod_level_1` ON `field_2` <=> `lod_level_1`.`key_1`)
+SELECT `field_3` AS `group_1`, SUM(`field_1`) / CASE WHEN MIN(`lod_1`) = MAX(`lod_1`) THEN MIN(`lod_1`) ELSE NULL END AS `value`
+FROM `lod_rows_1`
+GROUP BY `field_3`;
+
+-- sum(sales) / sum(sales) [group by region] (bigquery)
+WITH `lod_source` AS (SELECT `sales` AS `field_1`, `region` AS `field_2`, `order_id` AS `field_3` FROM orders),
+`lod_level_1` AS (SELECT `field_2` AS `key_1`, SUM(`field_1`) AS `value` FROM `lod_source` GROUP BY `field_2`),
+`lod_rows_1` AS (SELECT `lod_source`.*, `lod_level_1`.`value` AS `lod_1` FROM `lod_source` LEFT JOIN `lod_level_1` ON `field_2` IS NOT DISTINCT FROM `lod_level_1`.`key_1`)
+SELECT `field_3` AS `group_1`, SUM(`field_1`) / CASE WHEN MIN(`lod_1`) = MAX(`lod_1`) THEN MIN(`lod_1`) ELSE NULL END AS `value`
+FROM `lod_rows_1`
+GROUP BY `field_3`;
+
+-- avg(sum(sales) [group by region]) (ansi)
+WITH "lod_source" AS (SELECT "sales" AS "field_1", "region" AS "field_2" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS NOT DISTINCT FROM "lod_level_1"."key_1")
+SELECT AVG("lod_1") AS "value"
+FROM "lod_rows_1";
+
+-- avg(sum(sales) [group by region]) (postgresql)
+WITH "lod_source" AS (SELECT "sales" AS "field_1", "region" AS "field_2" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS NOT DISTINCT FROM "lod_level_1"."key_1")
+SELECT AVG("lod_1") AS "value"
+FROM "lod_rows_1";
+
+-- avg(sum(sales) [group by region]) (sqlite)
+WITH "lod_source" AS (SELECT "sales" AS "field_1", "region" AS "field_2" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS "lod_level_1"."key_1")
+SELECT AVG("lod_1") AS "value"
+FROM "lod_rows_1";
+
+-- avg(sum(sales) [group by region]) (duckdb)
+WITH "lod_source" AS (SELECT "sales" AS "field_1", "region" AS "field_2" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS NOT DISTINCT FROM "lod_level_1"."key_1")
+SELECT AVG("lod_1") AS "value"
+FROM "lod_rows_1";
+
+-- avg(sum(sales) [group by region]) (mysql)
+WITH `lod_source` AS (SELECT `sales` AS `field_1`, `region` AS `field_2` FROM orders),
+`lod_level_1` AS (SELECT `field_2` AS `key_1`, SUM(`field_1`) AS `value` FROM `lod_source` GROUP BY `field_2`),
+`lod_rows_1` AS (SELECT `lod_source`.*, `lod_level_1`.`value` AS `lod_1` FROM `lod_source` LEFT JOIN `lod_level_1` ON `field_2` <=> `lod_level_1`.`key_1`)
+SELECT AVG(`lod_1`) AS `value`
+FROM `lod_rows_1`;
+
+-- avg(sum(sales) [group by region]) (bigquery)
+WITH `lod_source` AS (SELECT `sales` AS `field_1`, `region` AS `field_2` FROM orders),
+`lod_level_1` AS (SELECT `field_2` AS `key_1`, SUM(`field_1`) AS `value` FROM `lod_source` GROUP BY `field_2`),
+`lod_rows_1` AS (SELECT `lod_source`.*, `lod_level_1`.`value` AS `lod_1` FROM `lod_source` LEFT JOIN `lod_level_1` ON `field_2` IS NOT DISTINCT FROM `lod_level_1`.`key_1`)
+SELECT AVG(`lod_1`) AS `value`
+FROM `lod_rows_1`;
+
+-- countd(order_id) [group by all groups] (ansi)
+WITH "lod_source" AS (SELECT "order_id" AS "field_1", "region" AS "field_2", "paid" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT COUNT(DISTINCT CASE WHEN "field_3" THEN "field_1" ELSE NULL END) AS "value" FROM "lod_source"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" CROSS JOIN "lod_level_1")
+SELECT "field_2" AS "group_1", MIN(CASE WHEN "field_3" THEN "lod_1" ELSE NULL END) AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_2"
+HAVING COUNT(CASE WHEN "field_3" THEN 1 ELSE NULL END) > 0;
+
+-- countd(order_id) [group by all groups] (postgresql)
+WITH "lod_source" AS (SELECT "order_id" AS "field_1", "region" AS "field_2", "paid" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT COUNT(DISTINCT CASE WHEN "field_3" THEN "field_1" ELSE NULL END) AS "value" FROM "lod_source"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" CROSS JOIN "lod_level_1")
+SELECT "field_2" AS "group_1", MIN(CASE WHEN "field_3" THEN "lod_1" ELSE NULL END) AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_2"
+HAVING COUNT(CASE WHEN "field_3" THEN 1 ELSE NULL END) > 0;
+
+-- countd(order_id) [group by all groups] (sqlite)
+WITH "lod_source" AS (SELECT "order_id" AS "field_1", "region" AS "field_2", "paid" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT COUNT(DISTINCT CASE WHEN "field_3" THEN "field_1" ELSE NULL END) AS "value" FROM "lod_source"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" CROSS JOIN "lod_level_1")
+SELECT "field_2" AS "group_1", MIN(CASE WHEN "field_3" THEN "lod_1" ELSE NULL END) AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_2"
+HAVING COUNT(CASE WHEN "field_3" THEN 1 ELSE NULL END) > 0;
+
+-- countd(order_id) [group by all groups] (duckdb)
+WITH "lod_source" AS (SELECT "order_id" AS "field_1", "region" AS "field_2", "paid" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT COUNT(DISTINCT CASE WHEN "field_3" THEN "field_1" ELSE NULL END) AS "value" FROM "lod_source"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" CROSS JOIN "lod_level_1")
+SELECT "field_2" AS "group_1", MIN(CASE WHEN "field_3" THEN "lod_1" ELSE NULL END) AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_2"
+HAVING COUNT(CASE WHEN "field_3" THEN 1 ELSE NULL END) > 0;
+
+-- countd(order_id) [group by all groups] (mysql)
+WITH `lod_source` AS (SELECT `order_id` AS `field_1`, `region` AS `field_2`, `paid` AS `field_3` FROM orders),
+`lod_level_1` AS (SELECT COUNT(DISTINCT CASE WHEN `field_3` THEN `field_1` ELSE NULL END) AS `value` FROM `lod_source`),
+`lod_rows_1` AS (SELECT `lod_source`.*, `lod_level_1`.`value` AS `lod_1` FROM `lod_source` CROSS JOIN `lod_level_1`)
+SELECT `field_2` AS `group_1`, MIN(CASE WHEN `field_3` THEN `lod_1` ELSE NULL END) AS `value`
+FROM `lod_rows_1`
+GROUP BY `field_2`
+HAVING COUNT(CASE WHEN `field_3` THEN 1 ELSE NULL END) > 0;
+
+-- countd(order_id) [group by all groups] (bigquery)
+WITH `lod_source` AS (SELECT `order_id` AS `field_1`, `region` AS `field_2`, `paid` AS `field_3` FROM orders),
+`lod_level_1` AS (SELECT COUNT(DISTINCT CASE WHEN `field_3` THEN `field_1` ELSE NULL END) AS `value` FROM `lod_source`),
+`lod_rows_1` AS (SELECT `lod_source`.*, `lod_level_1`.`value` AS `lod_1` FROM `lod_source` CROSS JOIN `lod_level_1`)
+SELECT `field_2` AS `group_1`, MIN(CASE WHEN `field_3` THEN `lod_1` ELSE NULL END) AS `value`
+FROM `lod_rows_1`
+GROUP BY `field_2`
+HAVING COUNT(CASE WHEN `field_3` THEN 1 ELSE NULL END) > 0;
+
+-- sum(qty) [group by group(1)] - sum(qty) (ansi)
+WITH "lod_source" AS (SELECT "qty" AS "field_1", "region" AS "field_2", "paid" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM(CASE WHEN "field_1" < 5 THEN "field_1" ELSE NULL END) AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS NOT DISTINCT FROM "lod_level_1"."key_1")
+SELECT "field_2" AS "group_1", "field_3" AS "group_2", MIN(CASE WHEN "field_1" < 5 THEN "lod_1" ELSE NULL END) - SUM(CASE WHEN "field_1" < 5 THEN "field_1" ELSE NULL END) AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_2", "field_3"
+HAVING COUNT(CASE WHEN "field_1" < 5 THEN 1 ELSE NULL END) > 0;
+
+-- sum(qty) [group by group(1)] - sum(qty) (postgresql)
+WITH "lod_source" AS (SELECT "qty" AS "field_1", "region" AS "field_2", "paid" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM(CASE WHEN "field_1" < 5 THEN "field_1" ELSE NULL END) AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS NOT DISTINCT FROM "lod_level_1"."key_1")
+SELECT "field_2" AS "group_1", "field_3" AS "group_2", MIN(CASE WHEN "field_1" < 5 THEN "lod_1" ELSE NULL END) - SUM(CASE WHEN "field_1" < 5 THEN "field_1" ELSE NULL END) AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_2", "field_3"
+HAVING COUNT(CASE WHEN "field_1" < 5 THEN 1 ELSE NULL END) > 0;
+
+-- sum(qty) [group by group(1)] - sum(qty) (sqlite)
+WITH "lod_source" AS (SELECT "qty" AS "field_1", "region" AS "field_2", "paid" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM(CASE WHEN "field_1" < 5 THEN "field_1" ELSE NULL END) AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS "lod_level_1"."key_1")
+SELECT "field_2" AS "group_1", "field_3" AS "group_2", MIN(CASE WHEN "field_1" < 5 THEN "lod_1" ELSE NULL END) - SUM(CASE WHEN "field_1" < 5 THEN "field_1" ELSE NULL END) AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_2", "field_3"
+HAVING COUNT(CASE WHEN "field_1" < 5 THEN 1 ELSE NULL END) > 0;
+
+-- sum(qty) [group by group(1)] - sum(qty) (duckdb)
+WITH "lod_source" AS (SELECT "qty" AS "field_1", "region" AS "field_2", "paid" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM(CASE WHEN "field_1" < 5 THEN "field_1" ELSE NULL END) AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS NOT DISTINCT FROM "lod_level_1"."key_1")
+SELECT "field_2" AS "group_1", "field_3" AS "group_2", MIN(CASE WHEN "field_1" < 5 THEN "lod_1" ELSE NULL END) - SUM(CASE WHEN "field_1" < 5 THEN "field_1" ELSE NULL END) AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_2", "field_3"
+HAVING COUNT(CASE WHEN "field_1" < 5 THEN 1 ELSE NULL END) > 0;
+
+-- sum(qty) [group by group(1)] - sum(qty) (mysql)
+WITH `lod_source` AS (SELECT `qty` AS `field_1`, `region` AS `field_2`, `paid` AS `field_3` FROM orders),
+`lod_level_1` AS (SELECT `field_2` AS `key_1`, SUM(CASE WHEN `field_1` < 5 THEN `field_1` ELSE NULL END) AS `value` FROM `lod_source` GROUP BY `field_2`),
+`lod_rows_1` AS (SELECT `lod_source`.*, `lod_level_1`.`value` AS `lod_1` FROM `lod_source` LEFT JOIN `lod_level_1` ON `field_2` <=> `lod_level_1`.`key_1`)
+SELECT `field_2` AS `group_1`, `field_3` AS `group_2`, MIN(CASE WHEN `field_1` < 5 THEN `lod_1` ELSE NULL END) - SUM(CASE WHEN `field_1` < 5 THEN `field_1` ELSE NULL END) AS `value`
+FROM `lod_rows_1`
+GROUP BY `field_2`, `field_3`
+HAVING COUNT(CASE WHEN `field_1` < 5 THEN 1 ELSE NULL END) > 0;
+
+-- sum(qty) [group by group(1)] - sum(qty) (bigquery)
+WITH `lod_source` AS (SELECT `qty` AS `field_1`, `region` AS `field_2`, `paid` AS `field_3` FROM orders),
+`lod_level_1` AS (SELECT `field_2` AS `key_1`, SUM(CASE WHEN `field_1` < 5 THEN `field_1` ELSE NULL END) AS `value` FROM `lod_source` GROUP BY `field_2`),
+`lod_rows_1` AS (SELECT `lod_source`.*, `lod_level_1`.`value` AS `lod_1` FROM `lod_source` LEFT JOIN `lod_level_1` ON `field_2` IS NOT DISTINCT FROM `lod_level_1`.`key_1`)
+SELECT `field_2` AS `group_1`, `field_3` AS `group_2`, MIN(CASE WHEN `field_1` < 5 THEN `lod_1` ELSE NULL END) - SUM(CASE WHEN `field_1` < 5 THEN `field_1` ELSE NULL END) AS `value`
+FROM `lod_rows_1`
+GROUP BY `field_2`, `field_3`
+HAVING COUNT(CASE WHEN `field_1` < 5 THEN 1 ELSE NULL END) > 0;
+
+-- max(sum(qty) [group by region, paid]) [group by region] (ansi)
+WITH "lod_source" AS (SELECT "qty" AS "field_1", "region" AS "field_2", "paid" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", "field_3" AS "key_2", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2", "field_3"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS NOT DISTINCT FROM "lod_level_1"."key_1" AND "field_3" IS NOT DISTINCT FROM "lod_level_1"."key_2"),
+"lod_level_2" AS (SELECT "field_2" AS "key_1", MAX("lod_1") AS "value" FROM "lod_rows_1" GROUP BY "field_2"),
+"lod_rows_2" AS (SELECT "lod_rows_1".*, "lod_level_2"."value" AS "lod_2" FROM "lod_rows_1" LEFT JOIN "lod_level_2" ON "field_2" IS NOT DISTINCT FROM "lod_level_2"."key_1")
+SELECT "field_2" AS "group_1", "field_3" AS "group_2", MIN("lod_2") AS "value"
+FROM "lod_rows_2"
+GROUP BY "field_2", "field_3";
+
+-- max(sum(qty) [group by region, paid]) [group by region] (postgresql)
+WITH "lod_source" AS (SELECT "qty" AS "field_1", "region" AS "field_2", "paid" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", "field_3" AS "key_2", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2", "field_3"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS NOT DISTINCT FROM "lod_level_1"."key_1" AND "field_3" IS NOT DISTINCT FROM "lod_level_1"."key_2"),
+"lod_level_2" AS (SELECT "field_2" AS "key_1", MAX("lod_1") AS "value" FROM "lod_rows_1" GROUP BY "field_2"),
+"lod_rows_2" AS (SELECT "lod_rows_1".*, "lod_level_2"."value" AS "lod_2" FROM "lod_rows_1" LEFT JOIN "lod_level_2" ON "field_2" IS NOT DISTINCT FROM "lod_level_2"."key_1")
+SELECT "field_2" AS "group_1", "field_3" AS "group_2", MIN("lod_2") AS "value"
+FROM "lod_rows_2"
+GROUP BY "field_2", "field_3";
+
+-- max(sum(qty) [group by region, paid]) [group by region] (sqlite)
+WITH "lod_source" AS (SELECT "qty" AS "field_1", "region" AS "field_2", "paid" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", "field_3" AS "key_2", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2", "field_3"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS "lod_level_1"."key_1" AND "field_3" IS "lod_level_1"."key_2"),
+"lod_level_2" AS (SELECT "field_2" AS "key_1", MAX("lod_1") AS "value" FROM "lod_rows_1" GROUP BY "field_2"),
+"lod_rows_2" AS (SELECT "lod_rows_1".*, "lod_level_2"."value" AS "lod_2" FROM "lod_rows_1" LEFT JOIN "lod_level_2" ON "field_2" IS "lod_level_2"."key_1")
+SELECT "field_2" AS "group_1", "field_3" AS "group_2", MIN("lod_2") AS "value"
+FROM "lod_rows_2"
+GROUP BY "field_2", "field_3";
+
+-- max(sum(qty) [group by region, paid]) [group by region] (duckdb)
+WITH "lod_source" AS (SELECT "qty" AS "field_1", "region" AS "field_2", "paid" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", "field_3" AS "key_2", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2", "field_3"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS NOT DISTINCT FROM "lod_level_1"."key_1" AND "field_3" IS NOT DISTINCT FROM "lod_level_1"."key_2"),
+"lod_level_2" AS (SELECT "field_2" AS "key_1", MAX("lod_1") AS "value" FROM "lod_rows_1" GROUP BY "field_2"),
+"lod_rows_2" AS (SELECT "lod_rows_1".*, "lod_level_2"."value" AS "lod_2" FROM "lod_rows_1" LEFT JOIN "lod_level_2" ON "field_2" IS NOT DISTINCT FROM "lod_level_2"."key_1")
+SELECT "field_2" AS "group_1", "field_3" AS "group_2", MIN("lod_2") AS "value"
+FROM "lod_rows_2"
+GROUP BY "field_2", "field_3";
+
+-- max(sum(qty) [group by region, paid]) [group by region] (mysql)
+WITH `lod_source` AS (SELECT `qty` AS `field_1`, `region` AS `field_2`, `paid` AS `field_3` FROM orders),
+`lod_level_1` AS (SELECT `field_2` AS `key_1`, `field_3` AS `key_2`, SUM(`field_1`) AS `value` FROM `lod_source` GROUP BY `field_2`, `field_3`),
+`lod_rows_1` AS (SELECT `lod_source`.*, `lod_level_1`.`value` AS `lod_1` FROM `lod_source` LEFT JOIN `lod_level_1` ON `field_2` <=> `lod_level_1`.`key_1` AND `field_3` <=> `lod_level_1`.`key_2`),
+`lod_level_2` AS (SELECT `field_2` AS `key_1`, MAX(`lod_1`) AS `value` FROM `lod_rows_1` GROUP BY `field_2`),
+`lod_rows_2` AS (SELECT `lod_rows_1`.*, `lod_level_2`.`value` AS `lod_2` FROM `lod_rows_1` LEFT JOIN `lod_level_2` ON `field_2` <=> `lod_level_2`.`key_1`)
+SELECT `field_2` AS `group_1`, `field_3` AS `group_2`, MIN(`lod_2`) AS `value`
+FROM `lod_rows_2`
+GROUP BY `field_2`, `field_3`;
+
+-- max(sum(qty) [group by region, paid]) [group by region] (bigquery)
+WITH `lod_source` AS (SELECT `qty` AS `field_1`, `region` AS `field_2`, `paid` AS `field_3` FROM orders),
+`lod_level_1` AS (SELECT `field_2` AS `key_1`, `field_3` AS `key_2`, SUM(`field_1`) AS `value` FROM `lod_source` GROUP BY `field_2`, `field_3`),
+`lod_rows_1` AS (SELECT `lod_source`.*, `lod_level_1`.`value` AS `lod_1` FROM `lod_source` LEFT JOIN `lod_level_1` ON `field_2` IS NOT DISTINCT FROM `lod_level_1`.`key_1` AND `field_3` IS NOT DISTINCT FROM `lod_level_1`.`key_2`),
+`lod_level_2` AS (SELECT `field_2` AS `key_1`, MAX(`lod_1`) AS `value` FROM `lod_rows_1` GROUP BY `field_2`),
+`lod_rows_2` AS (SELECT `lod_rows_1`.*, `lod_level_2`.`value` AS `lod_2` FROM `lod_rows_1` LEFT JOIN `lod_level_2` ON `field_2` IS NOT DISTINCT FROM `lod_level_2`.`key_1`)
+SELECT `field_2` AS `group_1`, `field_3` AS `group_2`, MIN(`lod_2`) AS `value`
+FROM `lod_rows_2`
+GROUP BY `field_2`, `field_3`;
+
+-- sum(qty) [where ignore all filters] [group by region] (ansi)
+WITH "lod_source" AS (SELECT "qty" AS "field_1", "region" AS "field_2" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS NOT DISTINCT FROM "lod_level_1"."key_1")
+SELECT "field_2" AS "group_1", MIN(CASE WHEN "field_2" = 'EU' THEN "lod_1" ELSE NULL END) AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_2"
+HAVING COUNT(CASE WHEN "field_2" = 'EU' THEN 1 ELSE NULL END) > 0;
+
+-- sum(qty) [where ignore all filters] [group by region] (postgresql)
+WITH "lod_source" AS (SELECT "qty" AS "field_1", "region" AS "field_2" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS NOT DISTINCT FROM "lod_level_1"."key_1")
+SELECT "field_2" AS "group_1", MIN(CASE WHEN "field_2" = 'EU' THEN "lod_1" ELSE NULL END) AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_2"
+HAVING COUNT(CASE WHEN "field_2" = 'EU' THEN 1 ELSE NULL END) > 0;
+
+-- sum(qty) [where ignore all filters] [group by region] (sqlite)
+WITH "lod_source" AS (SELECT "qty" AS "field_1", "region" AS "field_2" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS "lod_level_1"."key_1")
+SELECT "field_2" AS "group_1", MIN(CASE WHEN "field_2" = 'EU' THEN "lod_1" ELSE NULL END) AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_2"
+HAVING COUNT(CASE WHEN "field_2" = 'EU' THEN 1 ELSE NULL END) > 0;
+
+-- sum(qty) [where ignore all filters] [group by region] (duckdb)
+WITH "lod_source" AS (SELECT "qty" AS "field_1", "region" AS "field_2" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS NOT DISTINCT FROM "lod_level_1"."key_1")
+SELECT "field_2" AS "group_1", MIN(CASE WHEN "field_2" = 'EU' THEN "lod_1" ELSE NULL END) AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_2"
+HAVING COUNT(CASE WHEN "field_2" = 'EU' THEN 1 ELSE NULL END) > 0;
+
+-- sum(qty) [where ignore all filters] [group by region] (mysql)
+WITH `lod_source` AS (SELECT `qty` AS `field_1`, `region` AS `field_2` FROM orders),
+`lod_level_1` AS (SELECT `field_2` AS `key_1`, SUM(`field_1`) AS `value` FROM `lod_source` GROUP BY `field_2`),
+`lod_rows_1` AS (SELECT `lod_source`.*, `lod_level_1`.`value` AS `lod_1` FROM `lod_source` LEFT JOIN `lod_level_1` ON `field_2` <=> `lod_level_1`.`key_1`)
+SELECT `field_2` AS `group_1`, MIN(CASE WHEN `field_2` = 'EU' THEN `lod_1` ELSE NULL END) AS `value`
+FROM `lod_rows_1`
+GROUP BY `field_2`
+HAVING COUNT(CASE WHEN `field_2` = 'EU' THEN 1 ELSE NULL END) > 0;
+
+-- sum(qty) [where ignore all filters] [group by region] (bigquery)
+WITH `lod_source` AS (SELECT `qty` AS `field_1`, `region` AS `field_2` FROM orders),
+`lod_level_1` AS (SELECT `field_2` AS `key_1`, SUM(`field_1`) AS `value` FROM `lod_source` GROUP BY `field_2`),
+`lod_rows_1` AS (SELECT `lod_source`.*, `lod_level_1`.`value` AS `lod_1` FROM `lod_source` LEFT JOIN `lod_level_1` ON `field_2` IS NOT DISTINCT FROM `lod_level_1`.`key_1`)
+SELECT `field_2` AS `group_1`, MIN(CASE WHEN `field_2` = 'EU' THEN `lod_1` ELSE NULL END) AS `value`
+FROM `lod_rows_1`
+GROUP BY `field_2`
+HAVING COUNT(CASE WHEN `field_2` = 'EU' THEN 1 ELSE NULL END) > 0;
+
+-- sum(qty) [group by missing] (ansi)
+-- error: unknown field `missing`
+
+-- sum(qty) [group by missing] (postgresql)
+-- error: unknown field `missing`
+
+-- sum(qty) [group by missing] (sqlite)
+-- error: unknown field `missing`
+
+-- sum(qty) [group by missing] (duckdb)
+-- error: unknown field `missing`
+
+-- sum(qty) [group by missing] (mysql)
+-- error: unknown field `missing`
+
+-- sum(qty) [group by missing] (bigquery)
+-- error: unknown field `missing`
+
+-- sum(qty) [group by order_id] (ansi)
+WITH "lod_source" AS (SELECT "qty" AS "field_1", "order_id" AS "field_2", "region" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS NOT DISTINCT FROM "lod_level_1"."key_1")
+SELECT "field_3" AS "group_1", CASE WHEN MIN("lod_1") = MAX("lod_1") THEN MIN("lod_1") ELSE NULL END AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_3";
+
+-- sum(qty) [group by order_id] (postgresql)
+WITH "lod_source" AS (SELECT "qty" AS "field_1", "order_id" AS "field_2", "region" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS NOT DISTINCT FROM "lod_level_1"."key_1")
+SELECT "field_3" AS "group_1", CASE WHEN MIN("lod_1") = MAX("lod_1") THEN MIN("lod_1") ELSE NULL END AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_3";
+
+-- sum(qty) [group by order_id] (sqlite)
+WITH "lod_source" AS (SELECT "qty" AS "field_1", "order_id" AS "field_2", "region" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS "lod_level_1"."key_1")
+SELECT "field_3" AS "group_1", CASE WHEN MIN("lod_1") = MAX("lod_1") THEN MIN("lod_1") ELSE NULL END AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_3";
+
+-- sum(qty) [group by order_id] (duckdb)
+WITH "lod_source" AS (SELECT "qty" AS "field_1", "order_id" AS "field_2", "region" AS "field_3" FROM orders),
+"lod_level_1" AS (SELECT "field_2" AS "key_1", SUM("field_1") AS "value" FROM "lod_source" GROUP BY "field_2"),
+"lod_rows_1" AS (SELECT "lod_source".*, "lod_level_1"."value" AS "lod_1" FROM "lod_source" LEFT JOIN "lod_level_1" ON "field_2" IS NOT DISTINCT FROM "lod_level_1"."key_1")
+SELECT "field_3" AS "group_1", CASE WHEN MIN("lod_1") = MAX("lod_1") THEN MIN("lod_1") ELSE NULL END AS "value"
+FROM "lod_rows_1"
+GROUP BY "field_3";
+
+-- sum(qty) [group by order_id] (mysql)
+WITH `lod_source` AS (SELECT `qty` AS `field_1`, `order_id` AS `field_2`, `region` AS `field_3` FROM orders),
+`lod_level_1` AS (SELECT `field_2` AS `key_1`, SUM(`field_1`) AS `value` FROM `lod_source` GROUP BY `field_2`),
+`lod_rows_1` AS (SELECT `lod_source`.*, `lod_level_1`.`value` AS `lod_1` FROM `lod_source` LEFT JOIN `lod_level_1` ON `field_2` <=> `lod_level_1`.`key_1`)
+SELECT `field_3` AS `group_1`, CASE WHEN MIN(`lod_1`) = MAX(`lod_1`) THEN MIN(`lod_1`) ELSE NULL END AS `value`
+FROM `lod_rows_1`
+GROUP BY `field_3`;
+
+-- sum(qty) [group by order_id] (bigquery)
+WITH `lod_source` AS (SELECT `qty` AS `field_1`, `order_id` AS `field_2`, `region` AS `field_3` FROM orders),
+`lod_level_1` AS (SELECT `field_2` AS `key_1`, SUM(`field_1`) AS `value` FROM `lod_source` GROUP BY `field_2`),
+`lod_rows_1` AS (SELECT `lod_source`.*, `lod_level_1`.`value` AS `lod_1` FROM `lod_source` LEFT JOIN `lod_level_1` ON `field_2` IS NOT DISTINCT FROM `lod_level_1`.`key_1`)
+SELECT `field_3` AS `group_1`, CASE WHEN MIN(`lod_1`) = MAX(`lod_1`) THEN MIN(`lod_1`) ELSE NULL END AS `value`
+FROM `lod_rows_1`
+GROUP BY `field_3`;
+
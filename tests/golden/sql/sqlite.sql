@@ -0,0 +1,198 @@
+-- sales + qty * 2
+"sales" + "qty" * 2
+
+-- (sales + qty) * 2
+("sales" + "qty") * 2
+
+-- sales - (qty - 1)
+"sales" - ("qty" - 1)
+
+-- qty - -1
+"qty" - -1
+
+-- qty / 4
+CAST("qty" AS REAL) / 4
+
+-- qty / (qty + 1)
+CAST("qty" AS REAL) / ("qty" + 1)
+
+-- price / 2.5 * 1e3
+"price" / 2.5 * 1e3
+
+-- region = "EU" and not paid or qty >= 10
+"region" = 'EU' AND NOT "paid" OR "qty" >= 10
+
+-- not (region != "EU" or paid) and (qty < 2 or qty > 4)
+NOT ("region" <> 'EU' OR "paid") AND ("qty" < 2 OR "qty" > 4)
+
+-- price is null or (qty + 1) is not null
+"price" IS NULL OR "qty" + 1 IS NOT NULL
+
+-- if paid then sales else 0
+CASE WHEN "paid" THEN "sales" ELSE 0 END
+
+-- case when qty > 5 then "big" when qty > 1 then "medium" end
+CASE WHEN "qty" > 5 THEN 'big' WHEN "qty" > 1 THEN 'medium' END
+
+-- region = "it's \"quoted\" \\ here"
+"region" = 'it''s "quoted" \ here'
+
+-- concat(region, "-", qty, null, paid)
+(COALESCE("region", '') || COALESCE('-', '') || COALESCE(CAST("qty" AS TEXT), '') || COALESCE(CASE WHEN "paid" THEN 'true' WHEN NOT "paid" THEN 'false' END, ''))
+
+-- round(price, 1) + abs(sales - 10)
+ROUND("price", 1) + ABS("sales" - 10)
+
+-- pow(qty, 2) + sqrt(qty) + mod(qty, 3)
+POWER("qty", 2) + SQRT("qty") + ("qty" % 3)
+
+-- log(qty) + log(qty, 2) + ln(qty) + exp(1)
+LOG10("qty") + (LN("qty") / LN(2)) + LN("qty") + EXP(1)
+
+-- len(region) + len(order_id)
+LENGTH("region") + LENGTH("order_id")
+
+-- substr(region, 2) = substr(order_id, 1, 2)
+SUBSTR("region", 2) = SUBSTR("order_id", 1, 2)
+
+-- concat(upper(left(region, 1)), lower(right(region, 1)))
+(COALESCE(UPPER(SUBSTR("region", 1, 1)), '') || COALESCE(LOWER(SUBSTR("region", MAX(LENGTH("region") - 1 + 1, 1))), ''))
+
+-- contains(region, "U") or startswith(order_id, "A")
+(INSTR("region", 'U') > 0) OR (SUBSTR("order_id", 1, LENGTH('A')) = 'A')
+
+-- replace(trim(region), "E", "e")
+REPLACE(TRIM("region"), 'E', 'e')
+
+-- split(order_id, "-", 2)
+-- error: function `split` cannot be translated to SQLite
+
+-- coalesce(price, sales, 0) + ifnull(price, 1) + zn(price)
+COALESCE("price", "sales", 0) + COALESCE("price", 1) + COALESCE("price", 0)
+
+-- nullif(qty, 0)
+NULLIF("qty", 0)
+
+-- iif(paid, 1, 0) + iif(qty > 2, 1, 0, 2)
+CASE WHEN "paid" THEN 1 WHEN NOT "paid" THEN 0 END + CASE WHEN "qty" > 2 THEN 1 WHEN NOT "qty" > 2 THEN 0 ELSE 2 END
+
+-- str(qty)
+CAST("qty" AS TEXT)
+
+-- int(price) + float(sales)
+CAST("price" AS INTEGER) + CAST("sales" AS REAL)
+
+-- bool(qty)
+-- error: function `bool` cannot be translated to SQLite
+
+-- today() < now()
+DATE('now', 'localtime') < DATETIME('now', 'localtime')
+
+-- datepart("year", orders.date) * 100 + datepart("month", orders.date)
+CAST(STRFTIME('%Y', "orders"."date") AS INTEGER) * 100 + CAST(STRFTIME('%m', "orders"."date") AS INTEGER)
+
+-- datepart("quarter", orders.date)
+((CAST(STRFTIME('%m', "orders"."date") AS INTEGER) + 2) / 3)
+
+-- datepart("week", orders.date)
+-- error: function `datepart` cannot be translated to SQLite
+
+-- datepart("hour", orders.shipped)
+CAST(STRFTIME('%H', "orders"."shipped") AS INTEGER)
+
+-- date_trunc("month", orders.date)
+DATE("orders"."date", 'start of month')
+
+-- date_trunc("quarter", orders.date)
+DATE("orders"."date", 'start of month', PRINTF('-%d months', (CAST(STRFTIME('%m', "orders"."date") AS INTEGER) - 1) % 3))
+
+-- date_trunc("week", orders.shipped)
+DATETIME("orders"."shipped", 'start of day', '-6 days', 'weekday 1')
+
+-- date_trunc("hour", orders.shipped)
+STRFTIME('%Y-%m-%d %H:00:00', "orders"."shipped")
+
+-- datediff("day", orders.date, orders.shipped)
+CAST(ROUND((JULIANDAY(DATETIME("orders"."shipped", 'start of day')) - JULIANDAY(DATETIME("orders"."date", 'start of day')))) AS INTEGER)
+
+-- datediff("week", orders.date, orders.shipped)
+CAST(ROUND((JULIANDAY(DATETIME("orders"."shipped", 'start of day', '-6 days', 'weekday 1')) - JULIANDAY(DATETIME("orders"."date", 'start of day', '-6 days', 'weekday 1'))) / 7) AS INTEGER)
+
+-- datediff("month", orders.date, orders.shipped)
+((CAST(STRFTIME('%Y', "orders"."shipped") AS INTEGER) - CAST(STRFTIME('%Y', "orders"."date") AS INTEGER)) * 12 + CAST(STRFTIME('%m', "orders"."shipped") AS INTEGER) - CAST(STRFTIME('%m', "orders"."date") AS INTEGER))
+
+-- datediff("quarter", orders.date, orders.shipped)
+((CAST(STRFTIME('%Y', "orders"."shipped") AS INTEGER) - CAST(STRFTIME('%Y', "orders"."date") AS INTEGER)) * 4 + (CAST(STRFTIME('%m', "orders"."shipped") AS INTEGER) + 2) / 3 - (CAST(STRFTIME('%m', "orders"."date") AS INTEGER) + 2) / 3)
+
+-- datediff("year", orders.date, orders.shipped)
+(CAST(STRFTIME('%Y', "orders"."shipped") AS INTEGER) - CAST(STRFTIME('%Y', "orders"."date") AS INTEGER))
+
+-- datediff("hour", orders.date, orders.shipped)
+CAST(ROUND((JULIANDAY(STRFTIME('%Y-%m-%d %H:00:00', "orders"."shipped")) - JULIANDAY(STRFTIME('%Y-%m-%d %H:00:00', "orders"."date"))) * 24) AS INTEGER)
+
+-- dateadd("month", 2, orders.date)
+DATE("orders"."date", PRINTF('%+d months', 2))
+
+-- dateadd("week", qty, orders.date)
+DATE("orders"."date", PRINTF('%+d days', 7 * "qty"))
+
+-- dateadd("hour", qty, orders.shipped)
+DATETIME("orders"."shipped", PRINTF('%+d hours', "qty"))
+
+-- orders.date + 7
+DATE("orders"."date", PRINTF('%+d days', 7))
+
+-- orders.date - qty
+DATE("orders"."date", PRINTF('%+d days', -"qty"))
+
+-- orders.date - date '2024-01-01'
+CAST(ROUND((JULIANDAY(DATETIME("orders"."date", 'start of day')) - JULIANDAY(DATETIME('2024-01-01', 'start of day')))) AS INTEGER)
+
+-- orders.date + interval '1 month 2 days'
+-- error: interval literal cannot be translated to SQLite
+
+-- orders.shipped - interval '90 minutes'
+-- error: interval literal cannot be translated to SQLite
+
+-- orders.date < timestamp '2024-03-01 12:30:00'
+"orders"."date" < '2024-03-01 12:30:00'
+
+-- orders.shipped - orders.date
+-- error: date and interval arithmetic cannot be translated to SQLite
+
+-- interval '3 days' * qty
+-- error: interval literal cannot be translated to SQLite
+
+-- sum(sales) / count(order_id)
+SUM("sales") / COUNT("order_id")
+
+-- avg(qty) + countd(region)
+AVG("qty") + COUNT(DISTINCT "region")
+
+-- min(orders.date) < max(orders.shipped)
+MIN("orders"."date") < MAX("orders"."shipped")
+
+-- median(price)
+-- error: function `median` cannot be translated to SQLite
+
+-- percentile(price, 0.9)
+-- error: function `percentile` cannot be translated to SQLite
+
+-- stddev(qty) + variance(qty)
+-- error: function `stddev` cannot be translated to SQLite
+
+-- sum(sales) [where ignore all filters]
+-- error: WHERE and GROUP BY modifiers cannot be translated to SQLite
+
+-- datepart("fortnight", orders.date)
+-- error: invalid argument of function `datepart`, unknown date part `fortnight`
+
+-- datepart(region, orders.date)
+-- error: date part that is not a string literal cannot be translated to SQLite
+
+-- discount * 2
+-- error: unknown field `discount`
+
+-- sales + "a"
+-- error: type mismatch, expected number but got string
+